@@ -0,0 +1,66 @@
+#![no_main]
+
+use arbitrary::Arbitrary;
+use clean_language_compiler::runtime::{fuzz_exports, HostHeap};
+use libfuzzer_sys::fuzz_target;
+
+/// One `(ptr, len)`-shaped read against the arbitrary `memory` buffer,
+/// mirroring the length-prefixed-string layout `string_to_int`/
+/// `string_to_float`/`string_concat` all expect.
+#[derive(Debug, Arbitrary)]
+struct Input {
+    memory: Vec<u8>,
+    ptr1: i32,
+    ptr2: i32,
+    alloc_sizes: Vec<u16>,
+}
+
+fuzz_target!(|input: Input| {
+    let Input { memory, ptr1, ptr2, alloc_sizes } = input;
+
+    // `string_to_int`/`string_to_float` must never panic or read outside
+    // `memory`, and must agree with Rust's own parsers on whatever string
+    // they actually decoded (if any).
+    let decoded1 = fuzz_exports::read_len_prefixed_str(&memory, ptr1).ok();
+
+    let int_result = fuzz_exports::string_to_int(&memory, ptr1);
+    let expected_int = decoded1.and_then(|s| s.parse::<i32>().ok()).unwrap_or(0);
+    assert_eq!(int_result, expected_int, "string_to_int diverged from str::parse::<i32>");
+
+    let float_result = fuzz_exports::string_to_float(&memory, ptr1);
+    let expected_float = decoded1.and_then(|s| s.parse::<f64>().ok()).unwrap_or(0.0);
+    assert!(
+        float_result == expected_float || (float_result.is_nan() && expected_float.is_nan()),
+        "string_to_float diverged from str::parse::<f64>"
+    );
+
+    // `string_concat(a, b)` must equal the concatenation of whatever each
+    // side decodes on its own (empty string for an invalid/out-of-bounds
+    // read, per the function's documented `unwrap_or("")` fallback).
+    let decoded2 = fuzz_exports::read_len_prefixed_str(&memory, ptr2).ok();
+    let concat_result = fuzz_exports::string_concat(&memory, ptr1, ptr2);
+    let expected_concat = format!("{}{}", decoded1.unwrap_or(""), decoded2.unwrap_or(""));
+    assert_eq!(concat_result, expected_concat, "string_concat round-trip mismatch");
+
+    // `HostHeap` allocations must never alias: every live block's byte
+    // range is disjoint from every other live block's.
+    let mut heap = HostHeap::new(HostHeap::DEFAULT_BASE);
+    let mut live: Vec<(usize, usize)> = Vec::new();
+    for (i, size) in alloc_sizes.iter().enumerate() {
+        if *size == 0 {
+            continue;
+        }
+        if let Some(ptr) = heap.alloc(*size as usize) {
+            for &(other_ptr, other_size) in &live {
+                let disjoint = ptr + *size as usize <= other_ptr || other_ptr + other_size <= ptr;
+                assert!(disjoint, "HostHeap allocation aliases a live allocation");
+            }
+            live.push((ptr, *size as usize));
+            // Free every third allocation immediately to exercise coalescing.
+            if i % 3 == 0 {
+                heap.free(ptr);
+                live.retain(|&(p, _)| p != ptr);
+            }
+        }
+    }
+});
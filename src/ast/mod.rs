@@ -194,6 +194,14 @@ pub enum Expression {
     ErrorVariable {
         location: SourceLocation,
     },
+
+    // Postfix `?` error-propagation: inner?
+    // Desugars to "evaluate inner; if it is an error, return that error from the
+    // enclosing function immediately; otherwise yield the success value".
+    TryPropagate {
+        inner: Box<Expression>,
+        location: SourceLocation,
+    },
     
     // Conditional expressions: if condition then value else value
     Conditional {
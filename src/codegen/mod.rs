@@ -2187,6 +2187,9 @@ impl CodeGenerator {
             Expression::Unary(op, expr) => {
                 self.generate_unary_operation(op, expr, instructions)
             },
+            Expression::TryPropagate { inner, .. } => {
+                self.generate_try_propagate(inner, instructions)
+            },
             _ => Err(CompilerError::codegen_error("Unsupported expression type in codegen", None, loc.clone())),
         }
     }
@@ -3677,20 +3680,25 @@ impl CodeGenerator {
         self.function_section.function(type_index);
         
         // Create a Function - parameters are automatically available as locals 0, 1, 2, ...
-        // For complex functions, we need additional local variables beyond parameters
-        // Determine how many locals are needed based on the highest LocalGet index in instructions
-        let _max_local_index = instructions.iter()
+        // For complex functions, we need additional local variables beyond parameters.
+        // Determine how many locals are needed based on the highest local index referenced;
+        // stdlib helpers that work with string/list pointers only ever need extra I32 scratch
+        // locals, so a single (count, I32) group covers every index past the parameters.
+        let max_local_index = instructions.iter()
             .filter_map(|inst| match inst {
                 Instruction::LocalGet(idx) | Instruction::LocalSet(idx) | Instruction::LocalTee(idx) => Some(*idx),
                 _ => None
             })
             .max()
             .unwrap_or(0);
-        
-        // For simple stdlib functions, we typically don't need extra locals beyond parameters
-        // The basic arithmetic functions only use LocalGet(0) and LocalGet(1) which are the parameters
-        let locals_needed: Vec<(u32, wasm_encoder::ValType)> = vec![];
-        
+
+        let param_count = params.len() as u32;
+        let locals_needed: Vec<(u32, wasm_encoder::ValType)> = if max_local_index >= param_count {
+            vec![(max_local_index - param_count + 1, wasm_encoder::ValType::I32)]
+        } else {
+            vec![]
+        };
+
         let mut func = Function::new(locals_needed);
         for inst in instructions {
             func.instruction(inst);
@@ -4600,6 +4608,43 @@ impl CodeGenerator {
         Ok(fallback_type)
     }
 
+    /// Generate code for the postfix `?` error-propagation operator.
+    ///
+    /// Desugars to "evaluate `inner`; if it produced an error, return that error
+    /// from the enclosing function immediately; otherwise yield the success value" -
+    /// the same early-return shape as Rust's own `?`. Clean Language does not yet
+    /// have a tagged error representation at the WASM level (see `generate_on_error`
+    /// above for the same limitation), so this reuses the -1-means-error sentinel
+    /// `generate_error_handler` already uses for I32 results: if `inner` evaluates
+    /// to -1, that's its error case, and it's returned from the enclosing function
+    /// immediately instead of being used as a value. Non-I32 results have no
+    /// sentinel convention yet, so they pass through unchanged; once errors are
+    /// represented as a real tagged union this should branch on an error flag
+    /// for every type instead.
+    fn generate_try_propagate(&mut self, inner: &Expression, instructions: &mut Vec<Instruction>) -> Result<WasmType, CompilerError> {
+        let inner_type = self.generate_expression(inner, instructions)?;
+
+        if inner_type != WasmType::I32 {
+            return Err(CompilerError::codegen_error(
+                format!("'?' is not yet supported on a result of type {:?} - only I32's -1-means-error sentinel is implemented", inner_type),
+                None,
+                None
+            ));
+        }
+
+        let value_local = self.add_local(WasmType::I32);
+        instructions.push(Instruction::LocalTee(value_local));
+        instructions.push(Instruction::I32Const(-1));
+        instructions.push(Instruction::I32Eq);
+        instructions.push(Instruction::If(BlockType::Empty));
+        instructions.push(Instruction::I32Const(-1));
+        instructions.push(Instruction::Return);
+        instructions.push(Instruction::End);
+        instructions.push(Instruction::LocalGet(value_local));
+
+        Ok(inner_type)
+    }
+
     /// Generate code for a class
     #[allow(dead_code)]
     fn generate_class(&mut self, class: &Class) -> Result<(), CompilerError> {
@@ -0,0 +1,385 @@
+// Tree-walking interpreter over the type checker's reduced expression
+// language. The compiler's only way to run a program is to emit WASM and
+// load it into `runtime::CleanRuntime` (or validate it with wasmtime);
+// this module gives tests, examples, and a REPL a much cheaper path —
+// evaluate a type-checked `Program` directly, without touching codegen
+// or wasmtime at all.
+
+use std::collections::HashMap;
+use crate::semantic::type_checker::{Expr, Statement, FunctionDef, Location};
+
+/// A lowered program, split into its top-level function definitions and
+/// everything else (the statements the `Evaluator` runs in program
+/// order). "Lowered" here just means flattened — `Statement::FunctionDef`
+/// entries are pulled out into `functions` so `Expr::Call` can look one
+/// up by name in one step instead of scanning the statement list.
+#[derive(Debug, Clone, Default)]
+pub struct ReducedIR {
+    pub functions: HashMap<String, FunctionDef>,
+    pub main: Vec<Statement>,
+}
+
+/// Lower a flat statement list (as produced by the parser/type checker)
+/// into a `ReducedIR`.
+pub fn lower(statements: &[Statement]) -> ReducedIR {
+    let mut ir = ReducedIR::default();
+    for stmt in statements {
+        match stmt {
+            Statement::FunctionDef(func) => {
+                ir.functions.insert(func.name.clone(), func.clone());
+            }
+            other => ir.main.push(other.clone()),
+        }
+    }
+    ir
+}
+
+/// A type-checked program ready to run, as produced by lowering a parsed
+/// statement list with `lower`.
+#[derive(Debug, Clone, Default)]
+pub struct Program {
+    pub statements: Vec<Statement>,
+}
+
+/// Runtime values the evaluator produces. Matrices are stored as nested
+/// `Vec`s rather than re-using `Expr::Matrix`'s row-of-expressions shape,
+/// since by evaluation time every element has already been reduced to a
+/// value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Number(f64),
+    Str(String),
+    Bool(bool),
+    Matrix(Vec<Vec<Value>>),
+    Void,
+}
+
+impl std::fmt::Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Number(n) => write!(f, "{}", n),
+            Value::Str(s) => write!(f, "{}", s),
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::Matrix(rows) => {
+                write!(f, "[")?;
+                for (i, row) in rows.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "[{}]", row.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(", "))?;
+                }
+                write!(f, "]")
+            }
+            Value::Void => write!(f, "void"),
+        }
+    }
+}
+
+/// Every way evaluating a `Program` can fail to produce a `Value`,
+/// including `Return`, which isn't really a failure — it's how
+/// `Statement::Return` unwinds back out to the enclosing `call_function`
+/// without every statement in between needing to explicitly propagate
+/// "we're returning now".
+#[derive(Debug, Clone)]
+pub enum RuntimeError {
+    /// Not an error: a `Statement::Return` unwinding to its call frame.
+    Return(Value),
+    UndefinedVariable { name: String, location: Location },
+    UndefinedFunction { name: String, location: Location },
+    ArityMismatch { name: String, expected: usize, found: usize, location: Location },
+    TypeError { message: String, location: Location },
+}
+
+impl std::fmt::Display for RuntimeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RuntimeError::Return(value) => write!(f, "unhandled return of {}", value),
+            RuntimeError::UndefinedVariable { name, location } => {
+                write!(f, "{}:{}: undefined variable '{}'", location.line, location.column, name)
+            }
+            RuntimeError::UndefinedFunction { name, location } => {
+                write!(f, "{}:{}: undefined function '{}'", location.line, location.column, name)
+            }
+            RuntimeError::ArityMismatch { name, expected, found, location } => {
+                write!(f, "{}:{}: function '{}' expects {} argument(s) but got {}", location.line, location.column, name, expected, found)
+            }
+            RuntimeError::TypeError { message, location } => {
+                write!(f, "{}:{}: {}", location.line, location.column, message)
+            }
+        }
+    }
+}
+
+/// A stack of lexical scopes, innermost last. Looking up a name searches
+/// from the innermost frame outward, the way `Statement::FunctionDef` in
+/// `TypeChecker::check_statement` threads parameters and `Statement::Let`
+/// bindings through nested blocks.
+#[derive(Debug, Default)]
+struct ScopeStack {
+    frames: Vec<HashMap<String, Value>>,
+}
+
+impl ScopeStack {
+    fn new() -> Self {
+        ScopeStack { frames: vec![HashMap::new()] }
+    }
+
+    fn push_frame(&mut self) {
+        self.frames.push(HashMap::new());
+    }
+
+    fn pop_frame(&mut self) {
+        self.frames.pop();
+    }
+
+    fn define(&mut self, name: &str, value: Value) {
+        self.frames.last_mut()
+            .expect("ScopeStack always has at least one frame")
+            .insert(name.to_string(), value);
+    }
+
+    /// Assign to an already-defined `name`, searching outward from the
+    /// innermost frame, returning `false` if no frame defines it.
+    fn assign(&mut self, name: &str, value: Value) -> bool {
+        for frame in self.frames.iter_mut().rev() {
+            if let std::collections::hash_map::Entry::Occupied(mut entry) = frame.entry(name.to_string()) {
+                entry.insert(value);
+                return true;
+            }
+        }
+        false
+    }
+
+    fn get(&self, name: &str) -> Option<Value> {
+        self.frames.iter().rev().find_map(|frame| frame.get(name).cloned())
+    }
+}
+
+/// Tree-walking evaluator for a `ReducedIR`. Holds the function table
+/// lowering produced plus a `ScopeStack` of variable bindings, so calling
+/// a function is just pushing a new frame, binding its parameters, and
+/// popping the frame again once the body finishes (normally or via a
+/// `RuntimeError::Return`).
+pub struct Evaluator {
+    ir: ReducedIR,
+    scopes: ScopeStack,
+}
+
+impl Evaluator {
+    pub fn new() -> Self {
+        Evaluator { ir: ReducedIR::default(), scopes: ScopeStack::new() }
+    }
+
+    /// Lower `program` into this evaluator's function table and run its
+    /// top-level statements in order, returning the value of the last
+    /// one (or `Value::Void` if `program` has none). A bare
+    /// `Statement::Return` at the top level unwinds all the way out as
+    /// an error, since there's no call frame to return it to.
+    pub fn eval_program(&mut self, program: &Program) -> Result<Value, RuntimeError> {
+        let ir = lower(&program.statements);
+        self.ir.functions.extend(ir.functions);
+
+        let mut last = Value::Void;
+        for stmt in &ir.main {
+            last = self.eval_statement(stmt)?;
+        }
+        Ok(last)
+    }
+
+    fn eval_statement(&mut self, stmt: &Statement) -> Result<Value, RuntimeError> {
+        match stmt {
+            Statement::Let { name, init, .. } => {
+                let value = match init {
+                    Some(expr) => self.eval_expr(expr)?,
+                    None => Value::Void,
+                };
+                self.scopes.define(name, value);
+                Ok(Value::Void)
+            }
+            Statement::Assign { target, value, location } => {
+                let evaluated = self.eval_expr(value)?;
+                if !self.scopes.assign(target, evaluated) {
+                    return Err(RuntimeError::UndefinedVariable { name: target.clone(), location: *location });
+                }
+                Ok(Value::Void)
+            }
+            Statement::FunctionDef(func) => {
+                self.ir.functions.insert(func.name.clone(), func.clone());
+                Ok(Value::Void)
+            }
+            Statement::Return { expr, location } => {
+                let value = match expr {
+                    Some(expr) => self.eval_expr(expr)?,
+                    None => Value::Void,
+                };
+                let _ = location;
+                Err(RuntimeError::Return(value))
+            }
+            Statement::If { condition, then_branch, else_branch, location } => {
+                match self.eval_expr(condition)? {
+                    Value::Bool(true) => self.eval_block(then_branch),
+                    Value::Bool(false) => match else_branch {
+                        Some(stmts) => self.eval_block(stmts),
+                        None => Ok(Value::Void),
+                    },
+                    other => Err(RuntimeError::TypeError {
+                        message: format!("If condition must be a boolean expression, found {:?}", other),
+                        location: *location,
+                    }),
+                }
+            }
+            Statement::While { condition, body, location } => {
+                loop {
+                    match self.eval_expr(condition)? {
+                        Value::Bool(true) => {
+                            self.eval_block(body)?;
+                        }
+                        Value::Bool(false) => break,
+                        other => {
+                            return Err(RuntimeError::TypeError {
+                                message: format!("While condition must be a boolean expression, found {:?}", other),
+                                location: *location,
+                            });
+                        }
+                    }
+                }
+                Ok(Value::Void)
+            }
+            Statement::Expression(expr) => self.eval_expr(expr),
+        }
+    }
+
+    /// Run `stmts` in the current scope (no new frame — `if`/`while`
+    /// bodies share their enclosing function's locals, only `call_function`
+    /// introduces a new one), returning the last statement's value.
+    fn eval_block(&mut self, stmts: &[Statement]) -> Result<Value, RuntimeError> {
+        let mut last = Value::Void;
+        for stmt in stmts {
+            last = self.eval_statement(stmt)?;
+        }
+        Ok(last)
+    }
+
+    fn eval_expr(&mut self, expr: &Expr) -> Result<Value, RuntimeError> {
+        match expr {
+            Expr::Number(n) => Ok(Value::Number(*n)),
+            Expr::String(s) => Ok(Value::Str(s.clone())),
+            Expr::Bool(b) => Ok(Value::Bool(*b)),
+            Expr::Variable { name, location } => {
+                self.scopes.get(name).ok_or_else(|| RuntimeError::UndefinedVariable { name: name.clone(), location: *location })
+            }
+            Expr::Binary { op, left, right, location } => {
+                let left = self.eval_expr(left)?;
+                let right = self.eval_expr(right)?;
+                self.eval_binary(op, left, right, *location)
+            }
+            Expr::Call { function, args, location } => {
+                let mut values = Vec::with_capacity(args.len());
+                for arg in args {
+                    values.push(self.eval_expr(arg)?);
+                }
+                self.call_function(function, values, *location)
+            }
+            Expr::Matrix { rows, location } => {
+                let mut values = Vec::with_capacity(rows.len());
+                for row in rows {
+                    let mut row_values = Vec::with_capacity(row.len());
+                    for elem in row {
+                        row_values.push(self.eval_expr(elem)?);
+                    }
+                    values.push(row_values);
+                }
+                let _ = location;
+                Ok(Value::Matrix(values))
+            }
+        }
+    }
+
+    fn eval_binary(&self, op: &str, left: Value, right: Value, location: Location) -> Result<Value, RuntimeError> {
+        match op {
+            "+" | "-" | "*" | "/" => match (left, right) {
+                (Value::Number(a), Value::Number(b)) => Ok(Value::Number(match op {
+                    "+" => a + b,
+                    "-" => a - b,
+                    "*" => a * b,
+                    "/" => a / b,
+                    _ => unreachable!(),
+                })),
+                (a, b) => Err(RuntimeError::TypeError {
+                    message: format!("Cannot perform arithmetic operation on {:?} and {:?}", a, b),
+                    location,
+                }),
+            },
+            "==" => Ok(Value::Bool(left == right)),
+            "!=" => Ok(Value::Bool(left != right)),
+            "<" | "<=" | ">" | ">=" => match (left, right) {
+                (Value::Number(a), Value::Number(b)) => Ok(Value::Bool(match op {
+                    "<" => a < b,
+                    "<=" => a <= b,
+                    ">" => a > b,
+                    ">=" => a >= b,
+                    _ => unreachable!(),
+                })),
+                (a, b) => Err(RuntimeError::TypeError {
+                    message: format!("Cannot compare {:?} and {:?}", a, b),
+                    location,
+                }),
+            },
+            "&&" | "||" => match (left, right) {
+                (Value::Bool(a), Value::Bool(b)) => Ok(Value::Bool(if op == "&&" { a && b } else { a || b })),
+                (a, b) => Err(RuntimeError::TypeError {
+                    message: format!("Logical operators require boolean operands, found {:?} and {:?}", a, b),
+                    location,
+                }),
+            },
+            _ => Err(RuntimeError::TypeError { message: format!("Unknown operator: {}", op), location }),
+        }
+    }
+
+    /// Call `name` with `args`: push a new frame, bind each parameter to
+    /// its argument, run the body, and pop the frame again whether the
+    /// body finished normally or unwound via `RuntimeError::Return`.
+    fn call_function(&mut self, name: &str, args: Vec<Value>, location: Location) -> Result<Value, RuntimeError> {
+        let func = self.ir.functions.get(name)
+            .cloned()
+            .ok_or_else(|| RuntimeError::UndefinedFunction { name: name.to_string(), location })?;
+
+        if func.params.len() != args.len() {
+            return Err(RuntimeError::ArityMismatch {
+                name: name.to_string(),
+                expected: func.params.len(),
+                found: args.len(),
+                location,
+            });
+        }
+
+        self.scopes.push_frame();
+        for (param, value) in func.params.iter().zip(args.into_iter()) {
+            self.scopes.define(&param.name, value);
+        }
+
+        let result = self.eval_block(&func.body);
+        self.scopes.pop_frame();
+
+        match result {
+            Err(RuntimeError::Return(value)) => Ok(value),
+            other => other,
+        }
+    }
+}
+
+impl Default for Evaluator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Evaluate one already-parsed statement and print its value — the
+/// "read" half of a REPL (turning a line of source text into a
+/// `Statement`) belongs to whatever front-end embeds this module, since
+/// this reduced expression language has no lexer/parser of its own yet.
+pub fn repl_eval(evaluator: &mut Evaluator, stmt: &Statement) -> Result<Value, RuntimeError> {
+    let value = evaluator.eval_statement(stmt)?;
+    println!("{}", value);
+    Ok(value)
+}
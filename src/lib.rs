@@ -19,6 +19,7 @@ pub mod module;
 pub mod package;
 pub mod runtime;
 pub mod debug;
+pub mod interpreter;
 
 use crate::parser::CleanParser;
 use crate::semantic::SemanticAnalyzer;
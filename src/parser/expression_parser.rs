@@ -30,6 +30,7 @@ impl ParsedOperator {
 }
 
 pub fn parse_expression(pair: Pair<Rule>) -> Result<Expression, CompilerError> {
+    let _depth_guard = super::RecursionGuard::enter()?;
     match pair.as_rule() {
         Rule::expression => {
             // Handle the top-level expression rule
@@ -324,7 +325,33 @@ pub fn parse_primary(pair: Pair<Rule>) -> Result<Expression, CompilerError> {
         Some(convert_to_ast_location(&location)),
         Some("Expected a value inside the primary expression".to_string())
     ))?;
-    
+
+    parse_primary_inner(inner, &location)
+}
+
+/// Parse the postfix `?` error-propagation operator: `inner?`.
+///
+/// `?` binds tighter than unary operators (so `!x?` parses as `!(x?)`) and
+/// chains naturally, since its operand may itself be a `try_expr`
+/// (`a()?.b()?` parses as `TryPropagate(MethodCall(TryPropagate(Call(a)), b))`).
+fn parse_try_expression(pair: Pair<Rule>) -> Result<Expression, CompilerError> {
+    let location = convert_to_ast_location(&get_location(&pair));
+    let operand = pair.into_inner().next().ok_or_else(|| CompilerError::parse_error(
+        "Empty try-propagation expression".to_string(),
+        Some(location.clone()),
+        Some("`?` must follow an expression, e.g. `f()?`".to_string())
+    ))?;
+
+    let operand_location = get_location(&operand);
+    let operand_expr = parse_primary_inner(operand, &operand_location)?;
+
+    Ok(Expression::TryPropagate {
+        inner: Box::new(operand_expr),
+        location,
+    })
+}
+
+fn parse_primary_inner(inner: Pair<Rule>, location: &super::SourceLocation) -> Result<Expression, CompilerError> {
     match inner.as_rule() {
         Rule::number => {
             parse_number_literal(inner)
@@ -336,7 +363,7 @@ pub fn parse_primary(pair: Pair<Rule>) -> Result<Expression, CompilerError> {
                 .map(Expression::Literal)
                 .map_err(|_| CompilerError::parse_error(
                     format!("Invalid integer: {num_str}"),
-                    Some(convert_to_ast_location(&location)),
+                    Some(convert_to_ast_location(location)),
                     Some("Check that the integer is in a valid format".to_string())
                 ))
         },
@@ -347,7 +374,7 @@ pub fn parse_primary(pair: Pair<Rule>) -> Result<Expression, CompilerError> {
                 .map(Expression::Literal)
                 .map_err(|_| CompilerError::parse_error(
                     format!("Invalid float: {num_str}"),
-                    Some(convert_to_ast_location(&location)),
+                    Some(convert_to_ast_location(location)),
                     Some("Check that the float is in a valid format".to_string())
                 ))
         },
@@ -357,7 +384,7 @@ pub fn parse_primary(pair: Pair<Rule>) -> Result<Expression, CompilerError> {
                 "false" => false,
                 _ => return Err(CompilerError::parse_error(
                     format!("Invalid boolean: {}", inner.as_str()),
-                    Some(convert_to_ast_location(&location)),
+                    Some(convert_to_ast_location(location)),
                     Some("Boolean values must be 'true' or 'false'".to_string())
                 )),
             };
@@ -373,7 +400,7 @@ pub fn parse_primary(pair: Pair<Rule>) -> Result<Expression, CompilerError> {
         Rule::error_variable => {
             // Parse error variable
             Ok(Expression::ErrorVariable {
-                location: convert_to_ast_location(&location),
+                location: convert_to_ast_location(location),
             })
         },
         Rule::identifier => {
@@ -396,9 +423,13 @@ pub fn parse_primary(pair: Pair<Rule>) -> Result<Expression, CompilerError> {
             // Handle base constructor calls: base(args...)
             parse_base_call(inner)
         },
+        Rule::try_expr => {
+            // Handle postfix `?` error-propagation: inner?
+            parse_try_expression(inner)
+        },
         _ => Err(CompilerError::parse_error(
             format!("Unexpected primary expression: {}", inner.as_str()),
-            Some(convert_to_ast_location(&location)),
+            Some(convert_to_ast_location(location)),
             Some("Expected a literal, identifier, or function call".to_string())
         )),
     }
@@ -597,6 +628,7 @@ pub fn parse_method_call(pair: Pair<Rule>) -> Result<Expression, CompilerError>
                 Rule::identifier => Expression::Variable(first.as_str().to_string()),
                 Rule::builtin_class_name => Expression::Variable(first.as_str().to_string()),
                 Rule::expression => parse_expression(first)?,
+                Rule::try_expr => parse_try_expression(first)?,
                 _ => return Err(CompilerError::parse_error(
                     "Invalid method call base".to_string(),
                     None,
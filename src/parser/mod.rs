@@ -1,5 +1,5 @@
 use pest_derive::Parser;
-use crate::ast::{Program};
+use crate::ast::Program;
 use crate::error::CompilerError;
 use crate::module::ModuleResolver;
 
@@ -44,9 +44,81 @@ pub use type_parser::parse_type;
 pub use class_parser::parse_class;
 pub use program_parser::parse_program_ast;
 
+/// Language edition selected by a [`ParseOptions`], gating grammar changes
+/// that would otherwise break existing programs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Edition {
+    /// The original Clean Language grammar, including the class
+    /// `properties:`/`methods:` block syntax exercised by
+    /// `test_parse_multiple_functions_with_class`.
+    V1,
+    /// Drops the class `properties:`/`methods:` block syntax in favor of
+    /// declaring class fields exclusively through a constructor or a
+    /// `functions:` block. No released program selects this edition yet.
+    V2,
+}
+
+impl Default for Edition {
+    fn default() -> Self {
+        Edition::V1
+    }
+}
+
+/// Options that gate optional grammar behavior for [`CleanParser::parse_program_with_options`].
+///
+/// `parse_program` and `parse_program_with_file` are thin wrappers around the
+/// options-taking variants that pass [`ParseOptions::default()`], so existing
+/// callers and tests are unaffected.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseOptions {
+    /// Selects which grammar revision to parse against. Under `Edition::V2`
+    /// the class `properties:`/`methods:` block syntax exercised by
+    /// `test_parse_multiple_functions_with_class` is rejected.
+    pub edition: Edition,
+    /// Maximum nesting depth allowed for expressions and statement bodies.
+    /// Protects against stack overflow on pathological deeply-nested input.
+    pub max_recursion_depth: usize,
+    /// When `true`, reject source files that mix tabs and spaces for
+    /// indentation. When `false`, mixed indentation is tolerated.
+    pub strict_indentation: bool,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            edition: Edition::V1,
+            max_recursion_depth: 256,
+            strict_indentation: false,
+        }
+    }
+}
+
 impl CleanParser {
     pub fn parse_program(source: &str) -> Result<Program, CompilerError> {
-        parser_impl::parse(source)
+        Self::parse_program_with_options(source, &ParseOptions::default())
+    }
+
+    /// Parse a program, applying the optional grammar/feature gates in `options`.
+    pub fn parse_program_with_options(source: &str, options: &ParseOptions) -> Result<Program, CompilerError> {
+        if options.strict_indentation {
+            check_strict_indentation(source)?;
+        }
+
+        // `max_recursion_depth` is enforced by `RecursionGuard` while
+        // `parse_statement`/`parse_expression` are still recursing into
+        // nested bodies, not by walking the AST afterwards - by the time a
+        // fully-built `Program` exists, a pathologically deep one may
+        // already have overflowed the stack building it.
+        set_max_recursion_depth(options.max_recursion_depth);
+        let program = parser_impl::parse(source);
+        set_max_recursion_depth(usize::MAX);
+        let program = program?;
+
+        if options.edition == Edition::V2 {
+            check_no_class_member_blocks(&program)?;
+        }
+
+        Ok(program)
     }
 
     /// Parse a program with file path information for better error reporting
@@ -113,6 +185,90 @@ pub fn parse_with_modules_and_recovery(source: &str, file_path: &str) -> Result<
     }
 }
 
+/// Reject source files that mix tabs and spaces within a single line's
+/// leading indentation.
+fn check_strict_indentation(source: &str) -> Result<(), CompilerError> {
+    for (line_no, line) in source.lines().enumerate() {
+        let indent: &str = &line[..line.len() - line.trim_start_matches([' ', '\t']).len()];
+        if indent.contains(' ') && indent.contains('\t') {
+            return Err(CompilerError::syntax_error(
+                format!("line {} mixes tabs and spaces for indentation", line_no + 1),
+                Some("Use either tabs or spaces consistently within a file".to_string()),
+                None,
+            ));
+        }
+    }
+    Ok(())
+}
+
+thread_local! {
+    /// Current parse-recursion depth, tracked by [`RecursionGuard`] as
+    /// `parse_statement`/`parse_expression` descend into nested bodies.
+    static RECURSION_DEPTH: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+    /// The limit `RecursionGuard` enforces, set for the duration of a single
+    /// [`CleanParser::parse_program_with_options`] call and reset to
+    /// `usize::MAX` (effectively unlimited) once it returns.
+    static MAX_RECURSION_DEPTH: std::cell::Cell<usize> = const { std::cell::Cell::new(usize::MAX) };
+}
+
+fn set_max_recursion_depth(max_depth: usize) {
+    MAX_RECURSION_DEPTH.with(|m| m.set(max_depth));
+    RECURSION_DEPTH.with(|d| d.set(0));
+}
+
+fn too_deep(max_depth: usize) -> CompilerError {
+    CompilerError::syntax_error(
+        format!("expression or statement nesting exceeds the configured limit of {}", max_depth),
+        Some("reduce nesting or raise ParseOptions::max_recursion_depth".to_string()),
+        None,
+    )
+}
+
+/// RAII guard that enforces `ParseOptions::max_recursion_depth` as
+/// `parse_statement`/`parse_expression` actually recurse into nested bodies,
+/// rather than after a (possibly already stack-overflowed) `Program` has
+/// been fully built. Increments the shared depth counter on `enter` and
+/// decrements it on drop, so every return path - including an early `?` -
+/// un-counts its own level.
+pub(crate) struct RecursionGuard;
+
+impl RecursionGuard {
+    pub(crate) fn enter() -> Result<Self, CompilerError> {
+        let (depth, max) = RECURSION_DEPTH.with(|d| {
+            let next = d.get() + 1;
+            d.set(next);
+            (next, MAX_RECURSION_DEPTH.with(|m| m.get()))
+        });
+        if depth > max {
+            return Err(too_deep(max));
+        }
+        Ok(RecursionGuard)
+    }
+}
+
+impl Drop for RecursionGuard {
+    fn drop(&mut self) {
+        RECURSION_DEPTH.with(|d| d.set(d.get().saturating_sub(1)));
+    }
+}
+
+/// Rejects any class that declares a field, since today the only grammar
+/// path that populates `Class::fields` is the `properties:` block syntax
+/// (see `class_parser::parse_class`'s `Rule::setup_block` arm) - a
+/// `functions:` block within a class only ever contributes methods.
+fn check_no_class_member_blocks(program: &Program) -> Result<(), CompilerError> {
+    for class in &program.classes {
+        if !class.fields.is_empty() {
+            return Err(CompilerError::parse_error(
+                format!("class '{}' uses the properties:/methods: block syntax, which Edition::V2 drops", class.name),
+                class.location.clone(),
+                Some("use ParseOptions::default() (Edition::V1), or declare fields through a constructor instead".to_string()),
+            ));
+        }
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -420,4 +576,96 @@ start()
         let result6 = CleanParser::parse_program(source6);
         println!("Variable + print result: {:?}", result6);
     }
+
+    #[test]
+    fn test_parse_options_default_matches_parse_program() {
+        let source = r#"
+start()
+	integer x = 5
+	print(x)
+        "#;
+
+        let default_result = CleanParser::parse_program_with_options(source, &ParseOptions::default());
+        assert!(default_result.is_ok(), "Default ParseOptions should accept what parse_program accepts");
+    }
+
+    #[test]
+    fn test_parse_options_recursion_depth_rejects_deep_nesting() {
+        let source = r#"
+start()
+	integer x = 1 + (1 + (1 + (1 + (1 + 1))))
+        "#;
+
+        let lenient = ParseOptions { max_recursion_depth: 64, ..ParseOptions::default() };
+        assert!(CleanParser::parse_program_with_options(source, &lenient).is_ok());
+
+        let strict = ParseOptions { max_recursion_depth: 2, ..ParseOptions::default() };
+        let result = CleanParser::parse_program_with_options(source, &strict);
+        assert!(result.is_err(), "A depth limit of 2 should reject the deeply nested expression");
+    }
+
+    #[test]
+    fn test_parse_options_strict_indentation_rejects_mixed_tabs_and_spaces() {
+        let source = "start()\n\t integer x = 5\n";
+
+        let lenient = ParseOptions { strict_indentation: false, ..ParseOptions::default() };
+        assert!(CleanParser::parse_program_with_options(source, &lenient).is_ok());
+
+        let strict = ParseOptions { strict_indentation: true, ..ParseOptions::default() };
+        let result = CleanParser::parse_program_with_options(source, &strict);
+        assert!(result.is_err(), "Strict indentation should reject a line mixing tabs and spaces");
+    }
+
+    #[test]
+    fn test_edition_v2_rejects_class_member_blocks() {
+        let source = r#"
+classes:
+    Point
+        properties:
+            number:
+                - x
+        "#;
+
+        let v1 = ParseOptions { edition: Edition::V1, ..ParseOptions::default() };
+        assert!(CleanParser::parse_program_with_options(source, &v1).is_ok());
+
+        let v2 = ParseOptions { edition: Edition::V2, ..ParseOptions::default() };
+        let result = CleanParser::parse_program_with_options(source, &v2);
+        assert!(result.is_err(), "Edition::V2 should reject a class using the properties: block syntax");
+    }
+
+    #[test]
+    fn test_try_propagate_operator_on_call() {
+        let source = r#"
+start()
+	integer result = processData()?
+	print(result)
+        "#;
+
+        let result = CleanParser::parse_program(source);
+        assert!(result.is_ok(), "Postfix `?` on a call should parse correctly: {:?}", result.err());
+    }
+
+    #[test]
+    fn test_try_propagate_operator_chains_through_method_calls() {
+        let source = r#"
+start()
+	integer result = obj.calculate()?.toInteger()?
+	print(result)
+        "#;
+
+        let result = CleanParser::parse_program(source);
+        assert!(result.is_ok(), "Chained `?` across method calls should parse correctly: {:?}", result.err());
+    }
+
+    #[test]
+    fn test_try_propagate_operator_binds_tighter_than_unary() {
+        let source = r#"
+start()
+	boolean result = !isReady()?
+        "#;
+
+        let result = CleanParser::parse_program(source);
+        assert!(result.is_ok(), "`!x?` should parse as `!(x?)`: {:?}", result.err());
+    }
 }
\ No newline at end of file
@@ -7,6 +7,7 @@ use super::type_parser::parse_type;
 use super::Rule;
 
 pub fn parse_statement(pair: Pair<Rule>) -> Result<Statement, CompilerError> {
+    let _depth_guard = super::RecursionGuard::enter()?;
     let ast_location = convert_to_ast_location(&get_location(&pair));
     let inner = pair.into_inner().next().unwrap();
 
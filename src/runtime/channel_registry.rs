@@ -0,0 +1,141 @@
+// Channel Registry Module for Clean Language
+// Bounded, handle-based message channels connecting background tasks back
+// to the main program, analogous to giving a spawned process a bootstrap
+// pipe instead of having it print straight to stdout.
+
+use super::future_resolver::FuturePayload;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Condvar, Mutex};
+
+/// `channel_send` succeeded: the message was enqueued.
+pub const SEND_OK: i32 = 0;
+/// `channel_send` failed: the bounded queue is full.
+pub const SEND_WOULD_BLOCK: i32 = 1;
+/// `channel_send` failed: the channel has been closed.
+pub const SEND_CLOSED: i32 = 2;
+
+/// Sentinel tag `channel_recv` returns once a channel is closed and fully
+/// drained, distinguishing "no more messages" from any real `ValueTag`.
+pub const EOF_TAG: i32 = -1;
+
+/// A single queued message; reuses the tagged-bytes shape futures already
+/// carry across the WASM boundary so both subsystems agree on one
+/// encoding.
+type Message = FuturePayload;
+
+struct ChannelState {
+    queue: VecDeque<Message>,
+    closed: bool,
+}
+
+/// A single bounded channel: a bookkeeping queue plus the two condvars
+/// that let `channel_send`/`channel_recv` coordinate across threads
+/// without polling.
+struct Channel {
+    state: Mutex<ChannelState>,
+    not_empty: Condvar,
+    capacity: usize,
+}
+
+impl Channel {
+    fn new(capacity: usize) -> Self {
+        Channel {
+            state: Mutex::new(ChannelState { queue: VecDeque::new(), closed: false }),
+            not_empty: Condvar::new(),
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// Enqueue `message` if there is room. Rather than blocking the host
+    /// call, a full queue reports `SEND_WOULD_BLOCK` so the WASM side can
+    /// decide whether to retry, matching the status-code contract
+    /// `channel_send` exposes.
+    fn try_send(&self, message: Message) -> i32 {
+        let mut state = self.state.lock().unwrap();
+        if state.closed {
+            return SEND_CLOSED;
+        }
+        if state.queue.len() >= self.capacity {
+            return SEND_WOULD_BLOCK;
+        }
+        state.queue.push_back(message);
+        drop(state);
+        self.not_empty.notify_one();
+        SEND_OK
+    }
+
+    /// Block the calling thread until a message is available or the
+    /// channel is closed and drained, then return it (`None` means EOF).
+    fn recv(&self) -> Option<Message> {
+        let mut state = self.state.lock().unwrap();
+        loop {
+            if let Some(message) = state.queue.pop_front() {
+                return Some(message);
+            }
+            if state.closed {
+                return None;
+            }
+            state = self.not_empty.wait(state).unwrap();
+        }
+    }
+
+    fn close(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.closed = true;
+        drop(state);
+        self.not_empty.notify_all();
+    }
+}
+
+/// Registry of bounded, handle-keyed channels backing the `channel_open`/
+/// `channel_send`/`channel_recv`/`channel_close` host functions. Cloning
+/// shares the same underlying registry (every field is `Arc`-backed),
+/// mirroring `future_resolver::FutureResolver`.
+#[derive(Clone)]
+pub struct ChannelRegistry {
+    channels: Arc<Mutex<HashMap<u32, Arc<Channel>>>>,
+    next_handle: Arc<Mutex<u32>>,
+}
+
+impl ChannelRegistry {
+    pub fn new() -> Self {
+        ChannelRegistry {
+            channels: Arc::new(Mutex::new(HashMap::new())),
+            next_handle: Arc::new(Mutex::new(1)),
+        }
+    }
+
+    /// Open a new bounded channel and return its handle.
+    pub fn open(&self, capacity: u32) -> u32 {
+        let handle = {
+            let mut next = self.next_handle.lock().unwrap();
+            let handle = *next;
+            *next += 1;
+            handle
+        };
+        self.channels.lock().unwrap().insert(handle, Arc::new(Channel::new(capacity as usize)));
+        handle
+    }
+
+    /// Enqueue `message` on `handle`. An unknown handle (never opened, or
+    /// already closed and dropped) is reported the same as `SEND_CLOSED`.
+    pub fn send(&self, handle: u32, message: Message) -> i32 {
+        match self.channels.lock().unwrap().get(&handle) {
+            Some(channel) => channel.try_send(message),
+            None => SEND_CLOSED,
+        }
+    }
+
+    /// Block until `handle` has a message, is closed and drained, or was
+    /// never opened at all (treated the same as closed-and-drained).
+    pub fn recv(&self, handle: u32) -> Option<Message> {
+        let channel = self.channels.lock().unwrap().get(&handle).cloned()?;
+        channel.recv()
+    }
+
+    pub fn close(&self, handle: u32) {
+        if let Some(channel) = self.channels.lock().unwrap().get(&handle) {
+            channel.close();
+        }
+    }
+}
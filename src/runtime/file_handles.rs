@@ -0,0 +1,158 @@
+// File Handle Registry Module for Clean Language
+// Handle-keyed table of open files backing the file_open/file_seek/
+// file_tell/file_close/file_pread/file_pwrite host functions, giving WASM
+// guests a persistent cursor and positional access instead of the
+// path-based, whole-file calls in file_io.rs (each of which re-opens and
+// closes the file on every call).
+
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Seek, SeekFrom};
+use std::sync::{Arc, Mutex};
+
+/// Open for reading only; the file must already exist.
+pub const MODE_READ: i32 = 0;
+/// Open for writing, truncating any existing content (creating the file
+/// if it doesn't exist).
+pub const MODE_WRITE: i32 = 1;
+/// Open for writing at the end of the file, creating it if it doesn't
+/// exist.
+pub const MODE_APPEND: i32 = 2;
+/// Open for both reading and writing, creating the file if it doesn't
+/// exist, without truncating existing content.
+pub const MODE_READ_WRITE: i32 = 3;
+
+/// `file_read_line` sentinel: the reader's cursor is already at end of
+/// file, so no line (not even a partial one) was available.
+pub const LINE_EOF: i32 = -1;
+/// `file_read_line` sentinel: a line was found but doesn't fit in the
+/// caller's buffer. Unlike `LINE_EOF`, the line stays queued — the next
+/// `file_read_line` call with a larger buffer returns the same line
+/// rather than skipping it.
+pub const LINE_TOO_LONG: i32 = -2;
+
+/// A file's raw handle plus the lazily-created line reader layered on top
+/// of it. The reader wraps a duplicated file descriptor (via
+/// `File::try_clone`) rather than `file`, so `file_fd_read`/`file_pread`/
+/// etc. can keep using `file` directly without fighting over ownership.
+struct FileEntry {
+    file: File,
+    reader: Option<BufReader<File>>,
+    /// A line already pulled off `reader` that didn't fit in a caller's
+    /// buffer, held here until a `file_read_line` call with enough room
+    /// claims it — so retrying with a bigger buffer re-reads the same
+    /// line instead of silently skipping it.
+    pending_line: Option<Vec<u8>>,
+}
+
+/// Registry of open files backing the handle-based file host functions.
+/// Cloning shares the same underlying table (every field is `Arc`-backed),
+/// mirroring `channel_registry::ChannelRegistry`.
+#[derive(Clone)]
+pub struct FileHandleTable {
+    files: Arc<Mutex<HashMap<i32, FileEntry>>>,
+    next_handle: Arc<Mutex<i32>>,
+}
+
+impl FileHandleTable {
+    pub fn new() -> Self {
+        FileHandleTable {
+            files: Arc::new(Mutex::new(HashMap::new())),
+            next_handle: Arc::new(Mutex::new(1)),
+        }
+    }
+
+    /// Open `path` under `mode` (one of the `MODE_*` constants) and
+    /// register it, returning its handle, or `-1` if `mode` is unknown or
+    /// the open fails.
+    pub fn open(&self, path: &str, mode: i32) -> i32 {
+        let opened = match mode {
+            MODE_READ => File::open(path),
+            MODE_WRITE => File::create(path),
+            MODE_APPEND => OpenOptions::new().create(true).append(true).open(path),
+            MODE_READ_WRITE => OpenOptions::new().create(true).read(true).write(true).open(path),
+            _ => return -1,
+        };
+        let file = match opened {
+            Ok(file) => file,
+            Err(_) => return -1,
+        };
+
+        let handle = {
+            let mut next = self.next_handle.lock().unwrap();
+            let handle = *next;
+            *next += 1;
+            handle
+        };
+        self.files.lock().unwrap().insert(handle, FileEntry { file, reader: None, pending_line: None });
+        handle
+    }
+
+    /// Run `f` against the file registered under `handle`, or return
+    /// `None` if `handle` is unknown (never opened, or already closed).
+    pub fn with_file<T>(&self, handle: i32, f: impl FnOnce(&mut File) -> T) -> Option<T> {
+        let mut files = self.files.lock().unwrap();
+        files.get_mut(&handle).map(|entry| f(&mut entry.file))
+    }
+
+    /// Read one line (terminating `\n` included, if present) from
+    /// `handle`'s line reader, creating it lazily on first call by
+    /// duplicating the underlying file descriptor, and reusing it across
+    /// calls so repeated calls stream forward through the file instead of
+    /// re-scanning from the start. Returns the line, bounded by
+    /// `max_len`, or `None` if `handle` is unknown, `Some(Ok(line))` with
+    /// `line.len() <= max_len`, `Some(Err(LINE_EOF))` at end of file with
+    /// no data queued, or `Some(Err(LINE_TOO_LONG))` if the next line
+    /// doesn't fit — call again with a bigger `max_len` to retrieve it.
+    pub fn read_line(&self, handle: i32, max_len: usize) -> Option<Result<Vec<u8>, i32>> {
+        let mut files = self.files.lock().unwrap();
+        let entry = files.get_mut(&handle)?;
+
+        if entry.pending_line.is_none() {
+            if entry.reader.is_none() {
+                let duplicate = entry.file.try_clone().ok()?;
+                entry.reader = Some(BufReader::new(duplicate));
+            }
+            let mut line = Vec::new();
+            match entry.reader.as_mut().unwrap().read_until(b'\n', &mut line) {
+                Ok(0) => return Some(Err(LINE_EOF)),
+                Ok(_) => entry.pending_line = Some(line),
+                Err(_) => return Some(Err(LINE_EOF)),
+            }
+        }
+
+        let pending = entry.pending_line.as_ref().unwrap();
+        if pending.len() > max_len {
+            return Some(Err(LINE_TOO_LONG));
+        }
+        Some(Ok(entry.pending_line.take().unwrap()))
+    }
+
+    /// Drop `handle`'s line reader and any queued partial line, and rewind
+    /// the underlying file to the start, so a subsequent `file_read_line`
+    /// re-iterates the file from its first line. Returns `0` on success,
+    /// `-1` if `handle` is unknown or the seek fails.
+    pub fn reset_reader(&self, handle: i32) -> i32 {
+        let mut files = self.files.lock().unwrap();
+        let entry = match files.get_mut(&handle) {
+            Some(entry) => entry,
+            None => return -1,
+        };
+        entry.reader = None;
+        entry.pending_line = None;
+        match entry.file.seek(SeekFrom::Start(0)) {
+            Ok(_) => 0,
+            Err(_) => -1,
+        }
+    }
+
+    /// Drop `handle` from the table, closing the underlying file. Returns
+    /// `0` on success, `-1` if `handle` was already closed or never
+    /// opened.
+    pub fn close(&self, handle: i32) -> i32 {
+        match self.files.lock().unwrap().remove(&handle) {
+            Some(_) => 0,
+            None => -1,
+        }
+    }
+}
@@ -2,9 +2,23 @@
 // Replaces mock/placeholder file operations with actual filesystem interactions
 
 use std::fs;
+use std::io::{Read, Seek, SeekFrom};
 use std::path::Path;
+use std::time::UNIX_EPOCH;
 use crate::error::CompilerError;
 
+/// One entry from `FileIO::list_directory`/`list_directory_detailed`:
+/// enough metadata for a caller to page through a directory tree without a
+/// second syscall per entry.
+pub struct DirEntryInfo {
+    pub name: String,
+    pub is_dir: bool,
+    pub size: u64,
+    /// Seconds since the Unix epoch, or `0` if the platform couldn't
+    /// report a modification time.
+    pub mtime: u64,
+}
+
 /// File I/O operations manager
 pub struct FileIO;
 
@@ -43,6 +57,25 @@ impl FileIO {
         }
     }
     
+    /// Write raw bytes to file, for content that isn't valid UTF-8 (images,
+    /// archives, etc.) and so can't go through `write_file`'s `&str`
+    /// parameter.
+    pub fn write_file_binary(path: &str, content: &[u8]) -> Result<(), CompilerError> {
+        println!("📁 [FILE WRITE BINARY] Writing {} bytes to: {}", content.len(), path);
+
+        match fs::write(path, content) {
+            Ok(()) => {
+                println!("✅ [FILE WRITE BINARY] Successfully wrote to {}", path);
+                Ok(())
+            }
+            Err(e) => {
+                let error_msg = format!("Failed to write file '{}': {}", path, e);
+                println!("❌ [FILE WRITE BINARY] {}", error_msg);
+                Err(CompilerError::runtime_error(error_msg, None, None))
+            }
+        }
+    }
+
     /// Append content to file
     pub fn append_file(path: &str, content: &str) -> Result<(), CompilerError> {
         println!("📁 [FILE APPEND] Appending {} bytes to: {}", content.len(), path);
@@ -92,6 +125,55 @@ impl FileIO {
         }
     }
     
+    /// Read only the `length`-byte window starting at `offset`, so large
+    /// files can be streamed instead of slurped whole (see `read_file`).
+    /// `length` is clamped to whatever remains in the file past `offset`;
+    /// the returned vector's length is the actual number of bytes read.
+    pub fn read_file_range(path: &str, offset: u64, length: u64) -> Result<Vec<u8>, CompilerError> {
+        println!("📁 [FILE READ RANGE] Reading {} bytes at offset {} from: {}", length, offset, path);
+
+        let mut file = fs::File::open(path).map_err(|e| {
+            let error_msg = format!("Failed to open file '{}': {}", path, e);
+            println!("❌ [FILE READ RANGE] {}", error_msg);
+            CompilerError::runtime_error(error_msg, None, None)
+        })?;
+
+        file.seek(SeekFrom::Start(offset)).map_err(|e| {
+            let error_msg = format!("Failed to seek in file '{}': {}", path, e);
+            println!("❌ [FILE READ RANGE] {}", error_msg);
+            CompilerError::runtime_error(error_msg, None, None)
+        })?;
+
+        // `length` is guest-controlled and can claim up to u64::MAX; clamp it
+        // to what's actually left in the file before allocating, so a
+        // pathological request can't force a multi-GB host allocation.
+        let file_len = file.metadata().map_err(|e| {
+            let error_msg = format!("Failed to stat file '{}': {}", path, e);
+            println!("❌ [FILE READ RANGE] {}", error_msg);
+            CompilerError::runtime_error(error_msg, None, None)
+        })?.len();
+        let remaining = file_len.saturating_sub(offset);
+        let to_read = length.min(remaining);
+
+        let mut buffer = vec![0u8; to_read as usize];
+        let mut total_read = 0;
+        while total_read < buffer.len() {
+            match file.read(&mut buffer[total_read..]) {
+                Ok(0) => break, // EOF before filling the buffer
+                Ok(n) => total_read += n,
+                Err(e) => {
+                    let error_msg = format!("Failed to read file '{}': {}", path, e);
+                    println!("❌ [FILE READ RANGE] {}", error_msg);
+                    return Err(CompilerError::runtime_error(error_msg, None, None));
+                }
+            }
+        }
+        buffer.truncate(total_read);
+
+        println!("✅ [FILE READ RANGE] Read {} bytes from {}", buffer.len(), path);
+        Ok(buffer)
+    }
+
     /// Get file size in bytes
     pub fn file_size(path: &str) -> Result<u64, CompilerError> {
         println!("📁 [FILE SIZE] Getting size of: {}", path);
@@ -110,28 +192,59 @@ impl FileIO {
         }
     }
     
-    /// List directory contents
-    pub fn list_directory(path: &str) -> Result<Vec<String>, CompilerError> {
-        println!("📁 [DIR LIST] Listing directory: {}", path);
-        
+    /// List directory contents, with each entry's `std::fs::metadata` size
+    /// and file type so the `list_directory` host function can serialize
+    /// real records into WASM memory instead of bare names. Delegates to
+    /// `list_directory_detailed`, which gathers the same metadata plus an
+    /// mtime this caller doesn't need.
+    pub fn list_directory(path: &str) -> Result<Vec<DirEntryInfo>, CompilerError> {
+        Self::list_directory_detailed(path)
+    }
+
+    /// List directory contents with per-entry type, size, and
+    /// modification time, following the same directory-index enumeration
+    /// pattern as `list_directory` but with enough metadata to walk a tree
+    /// without a separate `file_size`/`file_exists` call per entry.
+    pub fn list_directory_detailed(path: &str) -> Result<Vec<DirEntryInfo>, CompilerError> {
+        println!("📁 [DIR LIST] Listing directory (detailed): {}", path);
+
         match fs::read_dir(path) {
             Ok(entries) => {
-                let mut files = Vec::new();
+                let mut result = Vec::new();
                 for entry in entries {
                     match entry {
                         Ok(entry) => {
-                            if let Some(name) = entry.file_name().to_str() {
-                                files.push(name.to_string());
-                            }
+                            let Some(name) = entry.file_name().to_str().map(|s| s.to_string()) else {
+                                continue;
+                            };
+                            let metadata = match entry.metadata() {
+                                Ok(metadata) => metadata,
+                                Err(e) => {
+                                    println!("⚠️  [DIR LIST] Error reading metadata for {}: {}", name, e);
+                                    continue;
+                                }
+                            };
+                            let mtime = metadata
+                                .modified()
+                                .ok()
+                                .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                                .map(|duration| duration.as_secs())
+                                .unwrap_or(0);
+                            result.push(DirEntryInfo {
+                                name,
+                                is_dir: metadata.is_dir(),
+                                size: metadata.len(),
+                                mtime,
+                            });
                         }
                         Err(e) => {
                             println!("⚠️  [DIR LIST] Error reading entry: {}", e);
                         }
                     }
                 }
-                
-                println!("✅ [DIR LIST] Found {} entries in {}", files.len(), path);
-                Ok(files)
+
+                println!("✅ [DIR LIST] Found {} entries in {}", result.len(), path);
+                Ok(result)
             }
             Err(e) => {
                 let error_msg = format!("Failed to list directory '{}': {}", path, e);
@@ -140,7 +253,7 @@ impl FileIO {
             }
         }
     }
-    
+
     /// Create directory
     pub fn create_directory(path: &str) -> Result<(), CompilerError> {
         println!("📁 [DIR CREATE] Creating directory: {}", path);
@@ -157,4 +270,41 @@ impl FileIO {
             }
         }
     }
-} 
\ No newline at end of file
+}
+
+/// Map `path`'s extension (case-insensitively) to a MIME type, defaulting
+/// to `application/octet-stream` for unknown or missing extensions. A
+/// small fixed table rather than a dependency on a full MIME-sniffing
+/// crate, consistent with the rest of this hand-rolled file/HTTP stack.
+pub fn mime_type_for(path: &str) -> &'static str {
+    let extension = Path::new(path)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_ascii_lowercase());
+
+    match extension.as_deref() {
+        Some("html") | Some("htm") => "text/html",
+        Some("css") => "text/css",
+        Some("js") | Some("mjs") => "application/javascript",
+        Some("json") => "application/json",
+        Some("xml") => "application/xml",
+        Some("txt") => "text/plain",
+        Some("csv") => "text/csv",
+        Some("png") => "image/png",
+        Some("jpg") | Some("jpeg") => "image/jpeg",
+        Some("gif") => "image/gif",
+        Some("svg") => "image/svg+xml",
+        Some("webp") => "image/webp",
+        Some("ico") => "image/x-icon",
+        Some("pdf") => "application/pdf",
+        Some("zip") => "application/zip",
+        Some("gz") => "application/gzip",
+        Some("tar") => "application/x-tar",
+        Some("mp3") => "audio/mpeg",
+        Some("wav") => "audio/wav",
+        Some("mp4") => "video/mp4",
+        Some("woff") => "font/woff",
+        Some("woff2") => "font/woff2",
+        _ => "application/octet-stream",
+    }
+}
\ No newline at end of file
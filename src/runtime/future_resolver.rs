@@ -3,10 +3,55 @@
 
 use crate::error::CompilerError;
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 use tokio::sync::{mpsc, oneshot};
 
+/// Identifies how a `FuturePayload`'s bytes are encoded, so the two sides
+/// of `await_future`/`resolve_future` agree on how to decode them without
+/// needing a shared type across the host/WASM boundary.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueTag {
+    I32 = 0,
+    I64 = 1,
+    F64 = 2,
+    Str = 3,
+    Bytes = 4,
+}
+
+impl ValueTag {
+    pub fn from_i32(tag: i32) -> Option<Self> {
+        match tag {
+            0 => Some(ValueTag::I32),
+            1 => Some(ValueTag::I64),
+            2 => Some(ValueTag::F64),
+            3 => Some(ValueTag::Str),
+            4 => Some(ValueTag::Bytes),
+            _ => None,
+        }
+    }
+}
+
+/// A tagged, host-owned payload carried across the WASM boundary by
+/// `await_future`/`resolve_future`. Fixed-width tags (`I32`/`I64`/`F64`)
+/// store their raw little-endian bytes directly; `Str`/`Bytes` store the
+/// content with no length prefix (the prefix is a wire-format detail of
+/// the host functions, not of the payload itself).
+#[derive(Debug, Clone)]
+pub struct FuturePayload {
+    pub tag: ValueTag,
+    pub bytes: Vec<u8>,
+}
+
+/// Either side of a tagged future awaiting resolution: the sender half of
+/// a oneshot channel if `await_future` got there first, or the payload
+/// itself if `resolve_future` did.
+enum PayloadSlot {
+    Pending(oneshot::Sender<FuturePayload>),
+    Ready(FuturePayload),
+}
+
 /// Represents a future value that will be resolved later
 #[derive(Debug, Clone)]
 pub struct Future {
@@ -25,6 +70,10 @@ pub enum FutureValue {
     Float(f64),
     String(String),
     Boolean(bool),
+    /// Raw bytes, for futures carrying file contents or other binary
+    /// payloads rather than a printable `String` (see
+    /// `resolve_future_bytes`/`get_future_bytes`).
+    Bytes(Vec<u8>),
     Void,
     Error(String),
 }
@@ -43,6 +92,11 @@ pub struct FutureResolver {
     next_future_id: Arc<Mutex<u32>>,
     message_sender: mpsc::UnboundedSender<FutureMessage>,
     message_receiver: Arc<Mutex<Option<mpsc::UnboundedReceiver<FutureMessage>>>>,
+    /// Tagged payload futures backing the `await_future`/`resolve_future`
+    /// host functions, keyed by a real monotonic `u64` id rather than the
+    /// string ids above (those are for the `helpers` convenience API).
+    payload_slots: Arc<Mutex<HashMap<u64, PayloadSlot>>>,
+    next_payload_id: Arc<AtomicU64>,
 }
 
 /// Messages for future coordination
@@ -65,8 +119,56 @@ impl FutureResolver {
             next_future_id: Arc::new(Mutex::new(1)),
             message_sender: sender,
             message_receiver: Arc::new(Mutex::new(Some(receiver))),
+            payload_slots: Arc::new(Mutex::new(HashMap::new())),
+            next_payload_id: Arc::new(AtomicU64::new(1)),
+        }
+    }
+
+    /// Allocate a new monotonic id for a tagged-payload future. The WASM
+    /// side hands this back to `resolve_future` to deliver the value and
+    /// passes it to `await_future` to suspend until that happens.
+    pub fn new_payload_future(&self) -> u64 {
+        self.next_payload_id.fetch_add(1, Ordering::Relaxed)
+    }
+
+    /// Deliver `payload` to future `id`. If `await_future(id)` is already
+    /// suspended waiting, this wakes it immediately; otherwise the payload
+    /// is stashed until `await_future` is called.
+    pub fn resolve_payload(&self, id: u64, payload: FuturePayload) {
+        let mut slots = self.payload_slots.lock().unwrap();
+        match slots.remove(&id) {
+            Some(PayloadSlot::Pending(sender)) => {
+                let _ = sender.send(payload);
+            }
+            _ => {
+                slots.insert(id, PayloadSlot::Ready(payload));
+            }
         }
     }
+
+    /// Suspend until future `id` is resolved, genuinely yielding the
+    /// calling fiber via a oneshot channel rather than polling — so a
+    /// `resolve_future` call from a background task on another thread
+    /// wakes this one directly. Multiple concurrent `await_payload` calls
+    /// on different ids interleave normally since each awaits its own
+    /// channel.
+    pub async fn await_payload(&self, id: u64) -> FuturePayload {
+        let receiver = {
+            let mut slots = self.payload_slots.lock().unwrap();
+            match slots.remove(&id) {
+                Some(PayloadSlot::Ready(payload)) => return payload,
+                _ => {
+                    let (sender, receiver) = oneshot::channel();
+                    slots.insert(id, PayloadSlot::Pending(sender));
+                    receiver
+                }
+            }
+        };
+        // The sender is only ever dropped by `resolve_payload`'s `send`,
+        // so a recv error here would mean the resolver itself was torn
+        // down; fall back to an empty i32 payload rather than panicking.
+        receiver.await.unwrap_or(FuturePayload { tag: ValueTag::I32, bytes: vec![0; 4] })
+    }
     
     /// Create a new future with a unique ID
     pub fn create_future(&self, name_hint: Option<String>) -> String {
@@ -234,6 +336,21 @@ impl FutureResolver {
         let futures = self.futures.lock().unwrap();
         futures.get(future_id).and_then(|f| f.value.clone())
     }
+
+    /// Resolve `future_id` with a raw byte payload, for background work
+    /// (like `file_read_async`) whose result isn't naturally a `String`.
+    pub fn resolve_future_bytes(&self, future_id: String, bytes: Vec<u8>) -> Result<(), CompilerError> {
+        self.resolve_future(future_id, FutureValue::Bytes(bytes))
+    }
+
+    /// Get the byte payload of a resolved future, or `None` if it isn't
+    /// resolved, doesn't exist, or resolved to a non-`Bytes` value.
+    pub fn get_future_bytes(&self, future_id: &str) -> Option<Vec<u8>> {
+        match self.get_value(future_id) {
+            Some(FutureValue::Bytes(bytes)) => Some(bytes),
+            _ => None,
+        }
+    }
     
     /// Get statistics about futures
     pub fn get_statistics(&self) -> FutureStatistics {
@@ -316,6 +433,8 @@ impl Clone for FutureResolver {
             next_future_id: Arc::clone(&self.next_future_id),
             message_sender: self.message_sender.clone(),
             message_receiver: Arc::clone(&self.message_receiver),
+            payload_slots: Arc::clone(&self.payload_slots),
+            next_payload_id: Arc::clone(&self.next_payload_id),
         }
     }
 }
@@ -1,9 +1,37 @@
 // Simple HTTP Client Implementation for Clean Language (std only)
 use std::io::{Read, Write};
 use std::net::TcpStream;
-use std::sync::OnceLock;
+use std::sync::{Mutex, OnceLock};
+use std::thread;
+use std::time::Duration;
 use crate::error::CompilerError;
 
+/// Per-request behavior for the shared `HttpClient`, configurable via the
+/// `http_set_timeout`/`http_set_max_redirects`/`http_set_retries` host
+/// functions and applied to every `http_*` call a module makes.
+#[derive(Debug, Clone, Copy)]
+pub struct HttpConfig {
+    pub timeout: Duration,
+    pub max_redirects: u32,
+    pub retries: u32,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        HttpConfig {
+            timeout: Duration::from_secs(30),
+            max_redirects: 5,
+            retries: 2,
+        }
+    }
+}
+
+/// A substring present in every timeout-related `CompilerError` message
+/// this module produces, letting callers (see `write_http_response` in
+/// `mod.rs`) distinguish "the server was too slow" from other failures
+/// without a dedicated error variant.
+pub const TIMEOUT_MARKER: &str = "timed out";
+
 pub struct HttpClient;
 
 /// HTTP response structure
@@ -11,6 +39,18 @@ pub struct HttpClient;
 pub struct HttpResponse {
     pub status_code: u16,
     pub body: String,
+    pub headers: Vec<(String, String)>,
+}
+
+impl HttpResponse {
+    /// Look up a header by name, case-insensitively (as HTTP requires),
+    /// returning the first match.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
 }
 
 impl HttpClient {
@@ -18,79 +58,99 @@ impl HttpClient {
     pub fn new() -> Self {
         HttpClient
     }
-    
-    /// Make an HTTP GET request
-    pub fn get(&self, url: &str) -> Result<HttpResponse, CompilerError> {
+
+    /// Make an HTTP GET request, sending `headers` (e.g. `Authorization`)
+    /// in addition to the built-in `Host`/`User-Agent`/`Connection`,
+    /// honoring `config`'s timeout/redirect/retry policy.
+    pub fn get(&self, url: &str, headers: &[(String, String)], config: &HttpConfig) -> Result<HttpResponse, CompilerError> {
         println!("🌐 [HTTP GET] Making real request to: {}", url);
-        
-        // Parse URL (simple implementation)
-        let (host, path) = self.parse_url(url)?;
-        
-        // Create HTTP request
-        let request = format!(
-            "GET {} HTTP/1.1\r\nHost: {}\r\nUser-Agent: Clean-Language/1.0\r\nConnection: close\r\n\r\n",
-            path, host
-        );
-        
-        self.send_request(&host, &request)
+        self.execute("GET", url, None, headers, config)
     }
-    
-    /// Make an HTTP POST request
-    pub fn post(&self, url: &str, body: &str) -> Result<HttpResponse, CompilerError> {
+
+    /// Make an HTTP POST request. `headers` may override the default
+    /// `Content-Type: application/json` by including its own.
+    pub fn post(&self, url: &str, body: &str, headers: &[(String, String)], config: &HttpConfig) -> Result<HttpResponse, CompilerError> {
         println!("🌐 [HTTP POST] Making real request to: {}", url);
-        
-        let (host, path) = self.parse_url(url)?;
-        
-        let request = format!(
-            "POST {} HTTP/1.1\r\nHost: {}\r\nUser-Agent: Clean-Language/1.0\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
-            path, host, body.len(), body
-        );
-        
-        self.send_request(&host, &request)
+        self.execute("POST", url, Some(body), headers, config)
     }
-    
-    /// Make an HTTP PUT request
-    pub fn put(&self, url: &str, body: &str) -> Result<HttpResponse, CompilerError> {
+
+    /// Make an HTTP PUT request. `headers` may override the default
+    /// `Content-Type: application/json` by including its own.
+    pub fn put(&self, url: &str, body: &str, headers: &[(String, String)], config: &HttpConfig) -> Result<HttpResponse, CompilerError> {
         println!("🌐 [HTTP PUT] Making real request to: {}", url);
-        
-        let (host, path) = self.parse_url(url)?;
-        
-        let request = format!(
-            "PUT {} HTTP/1.1\r\nHost: {}\r\nUser-Agent: Clean-Language/1.0\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
-            path, host, body.len(), body
-        );
-        
-        self.send_request(&host, &request)
+        self.execute("PUT", url, Some(body), headers, config)
     }
-    
-    /// Make an HTTP PATCH request
-    pub fn patch(&self, url: &str, body: &str) -> Result<HttpResponse, CompilerError> {
+
+    /// Make an HTTP PATCH request. `headers` may override the default
+    /// `Content-Type: application/json` by including its own.
+    pub fn patch(&self, url: &str, body: &str, headers: &[(String, String)], config: &HttpConfig) -> Result<HttpResponse, CompilerError> {
         println!("🌐 [HTTP PATCH] Making real request to: {}", url);
-        
-        let (host, path) = self.parse_url(url)?;
-        
-        let request = format!(
-            "PATCH {} HTTP/1.1\r\nHost: {}\r\nUser-Agent: Clean-Language/1.0\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
-            path, host, body.len(), body
-        );
-        
-        self.send_request(&host, &request)
+        self.execute("PATCH", url, Some(body), headers, config)
     }
-    
-    /// Make an HTTP DELETE request
-    pub fn delete(&self, url: &str) -> Result<HttpResponse, CompilerError> {
+
+    /// Make an HTTP DELETE request, sending `headers` in addition to the
+    /// built-in `Host`/`User-Agent`/`Connection`.
+    pub fn delete(&self, url: &str, headers: &[(String, String)], config: &HttpConfig) -> Result<HttpResponse, CompilerError> {
         println!("🌐 [HTTP DELETE] Making real request to: {}", url);
-        
-        let (host, path) = self.parse_url(url)?;
-        
-        let request = format!(
-            "DELETE {} HTTP/1.1\r\nHost: {}\r\nUser-Agent: Clean-Language/1.0\r\nConnection: close\r\n\r\n",
-            path, host
-        );
-        
-        self.send_request(&host, &request)
+        self.execute("DELETE", url, None, headers, config)
     }
-    
+
+    /// Shared core of every `get`/`post`/`put`/`patch`/`delete`: build the
+    /// request, send it with retries (`send_with_retry`), and follow up to
+    /// `config.max_redirects` `3xx` responses that carry an absolute
+    /// `Location` header before returning whatever response is left.
+    fn execute(
+        &self,
+        method: &str,
+        url: &str,
+        body: Option<&str>,
+        headers: &[(String, String)],
+        config: &HttpConfig,
+    ) -> Result<HttpResponse, CompilerError> {
+        let mut current_url = url.to_string();
+        let mut redirects = 0;
+        loop {
+            let (host, path) = self.parse_url(&current_url)?;
+            let request = build_request(method, &path, &host, body, headers);
+            let response = self.send_with_retry(&host, &request, config)?;
+
+            let is_redirect = (300..400).contains(&response.status_code);
+            if is_redirect && redirects < config.max_redirects {
+                if let Some(location) = response.header("Location") {
+                    if location.starts_with("http://") || location.starts_with("https://") {
+                        current_url = location.to_string();
+                        redirects += 1;
+                        continue;
+                    }
+                }
+            }
+            return Ok(response);
+        }
+    }
+
+    /// Send `request` to `host`, retrying up to `config.retries` times
+    /// with exponential backoff on a connection failure or `5xx` response
+    /// (a timeout is not retried — it already waited `config.timeout`
+    /// once, so a caller who wants another attempt can set `retries`
+    /// themselves knowing that cost).
+    fn send_with_retry(&self, host: &str, request: &str, config: &HttpConfig) -> Result<HttpResponse, CompilerError> {
+        let mut attempt = 0;
+        loop {
+            match self.send_request(host, request, config.timeout) {
+                Ok(response) if response.status_code >= 500 && attempt < config.retries => {
+                    attempt += 1;
+                    thread::sleep(retry_backoff(attempt));
+                }
+                Ok(response) => return Ok(response),
+                Err(e) if attempt < config.retries && !e.to_string().contains(TIMEOUT_MARKER) => {
+                    attempt += 1;
+                    thread::sleep(retry_backoff(attempt));
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
     fn parse_url(&self, url: &str) -> Result<(String, String), CompilerError> {
         // Simple URL parsing for http://host/path or https://host/path
         let url = url.trim();
@@ -117,29 +177,44 @@ impl HttpClient {
         Ok((host, path))
     }
     
-    fn send_request(&self, host: &str, request: &str) -> Result<HttpResponse, CompilerError> {
+    fn send_request(&self, host: &str, request: &str, timeout: Duration) -> Result<HttpResponse, CompilerError> {
         // Connect to server (port 80 for HTTP, 443 for HTTPS not supported in this simple implementation)
         let address = format!("{}:80", host);
-        
+
         match TcpStream::connect(&address) {
             Ok(mut stream) => {
+                // A hung server must not stall the whole WASM execution:
+                // bound both directions by the configured timeout.
+                if let Err(e) = stream.set_read_timeout(Some(timeout)) {
+                    return Err(CompilerError::runtime_error(
+                        format!("Failed to set read timeout: {}", e),
+                        None, None
+                    ));
+                }
+                if let Err(e) = stream.set_write_timeout(Some(timeout)) {
+                    return Err(CompilerError::runtime_error(
+                        format!("Failed to set write timeout: {}", e),
+                        None, None
+                    ));
+                }
+
                 // Send request
                 if let Err(e) = stream.write_all(request.as_bytes()) {
                     return Err(CompilerError::runtime_error(
-                        format!("Failed to send HTTP request: {}", e),
+                        http_io_error_message("send", timeout, &e),
                         None, None
                     ));
                 }
-                
+
                 // Read response
                 let mut response = String::new();
                 if let Err(e) = stream.read_to_string(&mut response) {
                     return Err(CompilerError::runtime_error(
-                        format!("Failed to read HTTP response: {}", e),
+                        http_io_error_message("read", timeout, &e),
                         None, None
                     ));
                 }
-                
+
                 // Parse response
                 self.parse_response(&response)
             }
@@ -172,31 +247,93 @@ impl HttpClient {
             500
         };
         
-        // Find empty line separating headers from body
+        // Find empty line separating headers from body, collecting each
+        // "Name: value" header line we pass along the way.
         let mut body_start = 0;
-        for (i, line) in lines.iter().enumerate() {
+        let mut headers = Vec::new();
+        for (i, line) in lines.iter().enumerate().skip(1) {
             if line.is_empty() {
                 body_start = i + 1;
                 break;
             }
+            if let Some((name, value)) = line.split_once(':') {
+                headers.push((name.trim().to_string(), value.trim().to_string()));
+            }
         }
-        
+
         // Extract body
         let body = if body_start < lines.len() {
             lines[body_start..].join("\n")
         } else {
             String::new()
         };
-        
+
         println!("✅ [HTTP] Response received: {} bytes, status {}", body.len(), status_code);
-        
+
         Ok(HttpResponse {
             status_code,
             body,
+            headers,
         })
     }
 }
 
+/// Build the raw HTTP/1.1 request line and headers for `method`/`path` on
+/// `host`, attaching `body` (with a `Content-Type`/`Content-Length` pair)
+/// when present.
+fn build_request(method: &str, path: &str, host: &str, body: Option<&str>, headers: &[(String, String)]) -> String {
+    match body {
+        Some(body) => format!(
+            "{} {} HTTP/1.1\r\nHost: {}\r\nUser-Agent: Clean-Language/1.0\r\nContent-Type: {}\r\nContent-Length: {}\r\n{}Connection: close\r\n\r\n{}",
+            method, path, host, content_type(headers), body.len(), render_extra_headers(headers, Some("content-type")), body
+        ),
+        None => format!(
+            "{} {} HTTP/1.1\r\nHost: {}\r\nUser-Agent: Clean-Language/1.0\r\n{}Connection: close\r\n\r\n",
+            method, path, host, render_extra_headers(headers, None)
+        ),
+    }
+}
+
+/// Delay before retry attempt `attempt` (1-indexed): doubles each time,
+/// starting at 100ms, capped well below any sane request timeout.
+fn retry_backoff(attempt: u32) -> Duration {
+    Duration::from_millis(100u64.saturating_mul(1 << attempt.min(10)))
+}
+
+/// Turn a `std::io::Error` from a socket read/write into a
+/// `CompilerError` message, tagging it with `TIMEOUT_MARKER` when the
+/// error is the timeout configured via `set_read_timeout`/
+/// `set_write_timeout` rather than some other I/O failure.
+fn http_io_error_message(action: &str, timeout: Duration, e: &std::io::Error) -> String {
+    if matches!(e.kind(), std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut) {
+        format!("HTTP request {} {} after {:?}: {}", action, TIMEOUT_MARKER, timeout, e)
+    } else {
+        format!("Failed to {} HTTP request: {}", action, e)
+    }
+}
+
+/// Render `headers` as `"Name: value\r\n"` lines, skipping `skip_name`
+/// (case-insensitive) since the caller already renders that one itself —
+/// used to let a caller-supplied `Content-Type` replace the default
+/// instead of being sent twice.
+fn render_extra_headers(headers: &[(String, String)], skip_name: Option<&str>) -> String {
+    headers
+        .iter()
+        .filter(|(name, _)| !skip_name.is_some_and(|skip| name.eq_ignore_ascii_case(skip)))
+        .map(|(name, value)| format!("{}: {}\r\n", name, value))
+        .collect()
+}
+
+/// The `Content-Type` to send: whatever `headers` specifies, or
+/// `application/json` by default.
+fn content_type(headers: &[(String, String)]) -> &str {
+    headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("content-type"))
+        .map(|(_, value)| value.as_str())
+        .unwrap_or("application/json")
+}
+
 /// Global HTTP client instance
 static HTTP_CLIENT: OnceLock<HttpClient> = OnceLock::new();
 
@@ -218,4 +355,40 @@ pub fn response_to_string(response: &HttpResponse) -> String {
 /// Convert HttpResponse to a status code for Clean Language runtime
 pub fn response_to_status_code(response: &HttpResponse) -> i32 {
     response.status_code as i32
-} 
\ No newline at end of file
+}
+
+/// The most recent response any `http_get`/`http_post`/`http_put`/
+/// `http_patch`/`http_delete` call produced, so `http_last_status` and
+/// `http_last_header` can inspect it without changing those functions'
+/// existing return-the-body-pointer signature.
+static LAST_RESPONSE: OnceLock<Mutex<Option<HttpResponse>>> = OnceLock::new();
+
+fn last_response_slot() -> &'static Mutex<Option<HttpResponse>> {
+    LAST_RESPONSE.get_or_init(|| Mutex::new(None))
+}
+
+/// Record `response` as the one `http_last_status`/`http_last_header`
+/// should report.
+pub fn set_last_response(response: HttpResponse) {
+    *last_response_slot().lock().unwrap() = Some(response);
+}
+
+/// The status code of the most recent response, or `-1` if no request has
+/// completed yet.
+pub fn last_status() -> i32 {
+    match &*last_response_slot().lock().unwrap() {
+        Some(response) => response.status_code as i32,
+        None => -1,
+    }
+}
+
+/// The value of `name` (case-insensitive) in the most recent response's
+/// headers, if both a response and a matching header exist.
+pub fn last_header(name: &str) -> Option<String> {
+    last_response_slot()
+        .lock()
+        .unwrap()
+        .as_ref()
+        .and_then(|response| response.header(name))
+        .map(|value| value.to_string())
+}
@@ -3,26 +3,38 @@
 
 use crate::error::CompilerError;
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::{Duration, Instant};
-use wasmtime::{Config, Engine, Module, Store, Linker, Caller};
+use wasmtime::{Config, Engine, Module, Store, Linker, Caller, Memory};
 
 pub mod async_runtime;
 pub mod task_scheduler;
 pub mod future_resolver;
+pub mod channel_registry;
 pub mod http_client;
 pub mod file_io;
+pub mod file_handles;
+pub mod temp_files;
+pub mod url;
 
 use http_client::{init_http_client, get_http_client};
 use file_io::FileIO;
+use file_handles::FileHandleTable;
+use temp_files::TempFileRegistry;
+use future_resolver::{FutureResolver, FuturePayload, FutureValue, ValueTag};
+use channel_registry::ChannelRegistry;
 
 /// Enhanced WebAssembly runtime with async support
 pub struct CleanRuntime {
     engine: Engine,
     task_scheduler: Arc<Mutex<TaskScheduler>>,
-    future_resolver: Arc<Mutex<FutureResolver>>,
+    future_resolver: FutureResolver,
+    channel_registry: ChannelRegistry,
+    file_handles: FileHandleTable,
     background_tasks: Arc<Mutex<Vec<BackgroundTask>>>,
+    temp_files: TempFileRegistry,
 }
 
 /// Represents a background task running in the runtime
@@ -32,6 +44,10 @@ pub struct BackgroundTask {
     pub name: String,
     pub started_at: Instant,
     pub status: TaskStatus,
+    /// Bootstrap channel handed to the task at creation, analogous to
+    /// giving a spawned process a pipe: task results flow back through
+    /// `channel_recv` on this handle rather than a bare `println!`.
+    pub channel_handle: Option<u32>,
 }
 
 /// Status of a background task
@@ -42,62 +58,690 @@ pub enum TaskStatus {
     Failed(String),
 }
 
+/// Resource ceiling applied to a single `execute_async` run. Either field
+/// left `None` imposes no limit on that dimension; `Default` runs with no
+/// ceiling at all, matching the old unbounded behavior.
+#[derive(Debug, Clone, Default)]
+pub struct RuntimeLimits {
+    /// Fuel units the module may consume before trapping with
+    /// `Trap::OutOfFuel`. Requires `Config::consume_fuel(true)`.
+    pub fuel: Option<u64>,
+    /// Wall-clock deadline enforced via Wasmtime's epoch interruption.
+    /// An epoch-ticker thread advances the engine's epoch on a fixed
+    /// cadence so the deadline trips even while the module is stuck in a
+    /// tight loop with no host calls.
+    pub wall_timeout: Option<Duration>,
+}
+
+/// What a successful `execute_async_capturing` run produced: the entry
+/// function's return value (and, when it's a plain integer, the same
+/// value as `exit_code`), plus everything the program printed. Unlike
+/// `execute_async`, which writes straight to the process's inherited
+/// stdout, this collects output in memory so library callers (test
+/// harnesses, REPLs) can inspect it instead of scraping real file
+/// descriptors.
+#[derive(Debug, Clone, Default)]
+pub struct ExecutionOutcome {
+    /// The entry function's return value truncated to 32 bits, or `0` if
+    /// it returned nothing.
+    pub exit_code: i32,
+    /// Everything written via `print`/`println`/`printl`/`print_simple`/
+    /// `printl_simple` during the run.
+    pub stdout: String,
+    /// Always empty today: the host functions have no separate error
+    /// stream, so there's nothing to route here yet.
+    pub stderr: String,
+    /// The entry function's raw return value, or `None` if it returned
+    /// nothing or returned a type this runtime doesn't widen to an
+    /// integer (only `i32`/`i64` results are captured).
+    pub return_value: Option<i64>,
+}
+
 /// Task scheduler for managing async operations
 pub struct TaskScheduler {
     next_task_id: u32,
     running_tasks: HashMap<u32, BackgroundTask>,
 }
 
-/// Future resolver for handling later assignments
-pub struct FutureResolver {
-    futures: HashMap<String, FutureValue>,
+/// A single block in `HostHeap`'s free list. `offset`/`size` describe its
+/// extent within the reserved arena; `free` tracks whether it is currently
+/// handed out to a caller.
+#[derive(Debug, Clone, Copy)]
+struct HeapBlock {
+    offset: usize,
+    size: usize,
+    free: bool,
 }
 
-/// Represents a future value that will be resolved later
-#[derive(Debug, Clone)]
-pub struct FutureValue {
-    pub id: String,
-    pub value: Option<i32>, // For now, using i32 as the basic value type
-    pub resolved: bool,
-    pub created_at: Instant,
+/// Host-side allocator backing the string-producing host functions
+/// (`int_to_string`, `string_concat`, `file_read`, `input`, etc.).
+///
+/// Replaces the old "scan linear memory for an all-zero region" allocator,
+/// which was quadratic and could hand a caller a block that merely
+/// *looked* free because it happened to be zeroed, silently corrupting
+/// whatever actually lived there. `HostHeap` instead owns an explicit
+/// first-fit free list over a reserved arena, splitting blocks on `alloc`
+/// and coalescing adjacent free neighbors on `free`.
+///
+/// The arena's base is negotiated with the compiled module: if it exports
+/// an `__host_heap_base` global, `execute_async` rebases the heap to that
+/// offset so the host allocator never collides with the module's own
+/// compile-time allocations; otherwise `DEFAULT_BASE` is used.
+pub struct HostHeap {
+    blocks: Vec<HeapBlock>,
+}
+
+impl HostHeap {
+    /// Every allocation is rounded up to a multiple of this size.
+    const ALIGN: usize = 8;
+
+    /// Arena base used when the module does not export `__host_heap_base`.
+    pub const DEFAULT_BASE: usize = 1024;
+
+    /// Create a heap whose arena starts at `base` and extends for the rest
+    /// of the address space; the real upper bound is enforced separately
+    /// by each host function's `offset + size <= data.len()` check against
+    /// the module's actual linear memory.
+    pub fn new(base: usize) -> Self {
+        HostHeap {
+            blocks: vec![HeapBlock { offset: base, size: usize::MAX - base, free: true }],
+        }
+    }
+
+    /// Re-point the arena at a new base, discarding any existing
+    /// allocations. Only meaningful before the first `alloc`, i.e. right
+    /// after the module's `__host_heap_base` global has been read.
+    fn rebase(&mut self, base: usize) {
+        *self = HostHeap::new(base);
+    }
+
+    fn align_up(size: usize) -> usize {
+        (size + Self::ALIGN - 1) & !(Self::ALIGN - 1)
+    }
+
+    /// First-fit allocate `size` bytes, splitting the chosen free block if
+    /// it has spare room left over. Returns `None` if no free block is big
+    /// enough.
+    pub fn alloc(&mut self, size: usize) -> Option<usize> {
+        let size = Self::align_up(size.max(1));
+        let idx = self.blocks.iter().position(|b| b.free && b.size >= size)?;
+        let block = self.blocks[idx];
+        if block.size > size {
+            self.blocks[idx] = HeapBlock { offset: block.offset, size, free: false };
+            self.blocks.insert(idx + 1, HeapBlock {
+                offset: block.offset + size,
+                size: block.size - size,
+                free: true,
+            });
+        } else {
+            self.blocks[idx].free = false;
+        }
+        Some(block.offset)
+    }
+
+    /// Free a block previously returned by `alloc`, coalescing it with
+    /// adjacent free neighbors so repeated alloc/free cycles don't
+    /// fragment the arena. Does nothing if `ptr` isn't a live allocation.
+    pub fn free(&mut self, ptr: usize) {
+        if let Some(idx) = self.blocks.iter().position(|b| b.offset == ptr && !b.free) {
+            self.blocks[idx].free = true;
+            if idx + 1 < self.blocks.len() && self.blocks[idx + 1].free {
+                let next_size = self.blocks[idx + 1].size;
+                self.blocks[idx].size += next_size;
+                self.blocks.remove(idx + 1);
+            }
+            if idx > 0 && self.blocks[idx - 1].free {
+                self.blocks[idx - 1].size += self.blocks[idx].size;
+                self.blocks.remove(idx);
+            }
+        }
+    }
+}
+
+/// Bounds-checked view over a module's exported linear memory. The
+/// resolved `Memory` handle is cached once, right after instantiation
+/// (see `execute_async`), but every accessor re-fetches the underlying
+/// `data`/`data_mut` slice from that handle, so a `memory.grow` between
+/// two host calls can never leave a stale slice or base pointer around —
+/// `Memory` is a cheap `Copy` handle, not a pointer into the backing
+/// store, so `MemView` is `Copy` too.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MemView {
+    memory: Option<Memory>,
+}
+
+impl MemView {
+    fn set(&mut self, memory: Memory) {
+        self.memory = Some(memory);
+    }
+
+    fn memory(&self) -> Result<Memory, CompilerError> {
+        self.memory.ok_or_else(|| CompilerError::runtime_error(
+            "host function called before the module's memory export was cached".to_string(),
+            None, None,
+        ))
+    }
+
+    fn bounds(ptr: i32, len: i32) -> Result<(usize, usize), CompilerError> {
+        if ptr < 0 || len < 0 {
+            return Err(CompilerError::runtime_error(
+                format!("invalid pointer/length: ptr={}, len={}", ptr, len),
+                None, None,
+            ));
+        }
+        Ok((ptr as usize, len as usize))
+    }
+
+    /// Read `len` bytes at `ptr` as a UTF-8 string.
+    pub fn read_str<'c>(&self, caller: &'c mut Caller<'_, HostContext>, ptr: i32, len: i32) -> Result<&'c str, CompilerError> {
+        let memory = self.memory()?;
+        decode_str(memory.data(&*caller), ptr, len)
+    }
+
+    /// Read a little-endian `u32` at `ptr`.
+    pub fn read_u32(&self, caller: &mut Caller<'_, HostContext>, ptr: i32) -> Result<u32, CompilerError> {
+        let memory = self.memory()?;
+        decode_u32(memory.data(&*caller), ptr)
+    }
+
+    /// Read a host-allocated string: a 4-byte little-endian length prefix
+    /// at `ptr` followed by that many bytes of UTF-8 data (the layout
+    /// `HostHeap`-backed strings are written in).
+    pub fn read_len_prefixed_str<'c>(&self, caller: &'c mut Caller<'_, HostContext>, ptr: i32) -> Result<&'c str, CompilerError> {
+        let memory = self.memory()?;
+        decode_len_prefixed_str(memory.data(&*caller), ptr)
+    }
+
+    /// Read `len` raw bytes at `ptr`, copied out as an owned buffer. Unlike
+    /// `read_str`, which borrows directly from memory, this is for callers
+    /// (like `resolve_future`) that need to hand the bytes to a future
+    /// that may outlive the current host call.
+    pub fn read_bytes(&self, caller: &mut Caller<'_, HostContext>, ptr: i32, len: i32) -> Result<Vec<u8>, CompilerError> {
+        let memory = self.memory()?;
+        decode_bytes(memory.data(&*caller), ptr, len)
+    }
+
+    /// Read a host-allocated byte blob: a 4-byte little-endian length
+    /// prefix at `ptr` followed by that many raw bytes (the `Bytes`/`Str`
+    /// future payload counterpart to `read_len_prefixed_str`).
+    pub fn read_len_prefixed_bytes(&self, caller: &mut Caller<'_, HostContext>, ptr: i32) -> Result<Vec<u8>, CompilerError> {
+        let memory = self.memory()?;
+        decode_len_prefixed_bytes(memory.data(&*caller), ptr)
+    }
+
+    /// Write `bytes` at `ptr`.
+    pub fn write_bytes(&self, caller: &mut Caller<'_, HostContext>, ptr: i32, bytes: &[u8]) -> Result<(), CompilerError> {
+        let (start, length) = Self::bounds(ptr, bytes.len() as i32)?;
+        let memory = self.memory()?;
+        let data = memory.data_mut(&mut *caller);
+        if start + length > data.len() {
+            return Err(CompilerError::runtime_error(
+                format!("write out of bounds: ptr={}, len={}, memory_size={}", ptr, bytes.len(), data.len()),
+                None, None,
+            ));
+        }
+        data[start..start + length].copy_from_slice(bytes);
+        Ok(())
+    }
+}
+
+/// `Caller`-free core of `MemView::read_str`: validates `ptr`/`len`
+/// against `data.len()` and decodes UTF-8. Factored out of the method
+/// above so the differential fuzz target in `fuzz/` can drive the exact
+/// same bounds-checking and decoding logic with an arbitrary byte buffer,
+/// without constructing a `Linker`/`Module`/`Instance`.
+fn decode_str(data: &[u8], ptr: i32, len: i32) -> Result<&str, CompilerError> {
+    let (start, length) = MemView::bounds(ptr, len)?;
+    if start + length > data.len() {
+        return Err(CompilerError::runtime_error(
+            format!("string read out of bounds: ptr={}, len={}, memory_size={}", ptr, len, data.len()),
+            None, None,
+        ));
+    }
+    std::str::from_utf8(&data[start..start + length]).map_err(|e| CompilerError::runtime_error(
+        format!("invalid UTF-8 at ptr={}, len={}: {}", ptr, len, e),
+        None, None,
+    ))
+}
+
+/// `Caller`-free core of `MemView::read_u32`.
+fn decode_u32(data: &[u8], ptr: i32) -> Result<u32, CompilerError> {
+    let (start, _) = MemView::bounds(ptr, 4)?;
+    if start + 4 > data.len() {
+        return Err(CompilerError::runtime_error(
+            format!("u32 read out of bounds: ptr={}, memory_size={}", ptr, data.len()),
+            None, None,
+        ));
+    }
+    Ok(u32::from_le_bytes([data[start], data[start + 1], data[start + 2], data[start + 3]]))
+}
+
+/// `Caller`-free core of `MemView::read_len_prefixed_str`.
+fn decode_len_prefixed_str(data: &[u8], ptr: i32) -> Result<&str, CompilerError> {
+    let len = decode_u32(data, ptr)? as i32;
+    decode_str(data, ptr + 4, len)
+}
+
+/// `Caller`-free core of `MemView::read_bytes`.
+fn decode_bytes(data: &[u8], ptr: i32, len: i32) -> Result<Vec<u8>, CompilerError> {
+    let (start, length) = MemView::bounds(ptr, len)?;
+    if start + length > data.len() {
+        return Err(CompilerError::runtime_error(
+            format!("byte read out of bounds: ptr={}, len={}, memory_size={}", ptr, len, data.len()),
+            None, None,
+        ));
+    }
+    Ok(data[start..start + length].to_vec())
+}
+
+/// `Caller`-free core of `MemView::read_len_prefixed_bytes`.
+fn decode_len_prefixed_bytes(data: &[u8], ptr: i32) -> Result<Vec<u8>, CompilerError> {
+    let len = decode_u32(data, ptr)? as i32;
+    decode_bytes(data, ptr + 4, len)
+}
+
+/// Allocate room for `s` on the host heap and write it into linear memory
+/// as a length-prefixed string (the layout `file_read` and the `http_*`
+/// functions share), returning the allocation pointer, or `-1` if the
+/// module's memory couldn't accommodate it.
+fn write_len_prefixed_string(caller: &mut Caller<'_, HostContext>, s: &str) -> i32 {
+    let bytes = s.as_bytes();
+    let total_size = 4 + bytes.len();
+    let offset = match caller.data_mut().heap.alloc(total_size) {
+        Some(offset) => offset,
+        None => return -1,
+    };
+    let view = caller.data().mem;
+    let memory = match view.memory() {
+        Ok(memory) => memory,
+        Err(_) => {
+            caller.data_mut().heap.free(offset);
+            return -1;
+        }
+    };
+    let data = memory.data_mut(&mut *caller);
+    if offset + total_size > data.len() {
+        caller.data_mut().heap.free(offset);
+        return -1;
+    }
+    data[offset..offset + 4].copy_from_slice(&(bytes.len() as u32).to_le_bytes());
+    data[offset + 4..offset + 4 + bytes.len()].copy_from_slice(bytes);
+    offset as i32
+}
+
+/// Write `parts` into linear memory as a length-prefixed record: each of
+/// `scheme`/`host`/`path`/`query`/`fragment` as a 4-byte little-endian
+/// length followed by its UTF-8 bytes, with `port` as a plain 4-byte
+/// little-endian `i32` (`-1` when the URL had none) in between `host` and
+/// `path`. Returns the allocation pointer, or `-1` if memory couldn't
+/// accommodate it.
+fn write_url_record(caller: &mut Caller<'_, HostContext>, parts: &url::UrlParts) -> i32 {
+    let fields: [&[u8]; 5] = [
+        parts.scheme.as_bytes(),
+        parts.host.as_bytes(),
+        parts.path.as_bytes(),
+        parts.query.as_bytes(),
+        parts.fragment.as_bytes(),
+    ];
+    let strings_size: usize = fields.iter().map(|f| 4 + f.len()).sum();
+    let total_size = strings_size + 4; // + the port field
+    let offset = match caller.data_mut().heap.alloc(total_size) {
+        Some(offset) => offset,
+        None => return -1,
+    };
+    let view = caller.data().mem;
+    let memory = match view.memory() {
+        Ok(memory) => memory,
+        Err(_) => {
+            caller.data_mut().heap.free(offset);
+            return -1;
+        }
+    };
+    let data = memory.data_mut(&mut *caller);
+    if offset + total_size > data.len() {
+        caller.data_mut().heap.free(offset);
+        return -1;
+    }
+    let mut cursor = offset;
+    let mut write_str = |data: &mut [u8], cursor: &mut usize, bytes: &[u8]| {
+        data[*cursor..*cursor + 4].copy_from_slice(&(bytes.len() as u32).to_le_bytes());
+        *cursor += 4;
+        data[*cursor..*cursor + bytes.len()].copy_from_slice(bytes);
+        *cursor += bytes.len();
+    };
+    write_str(data, &mut cursor, fields[0]); // scheme
+    write_str(data, &mut cursor, fields[1]); // host
+    let port = parts.port.map(|p| p as i32).unwrap_or(-1);
+    data[cursor..cursor + 4].copy_from_slice(&port.to_le_bytes());
+    cursor += 4;
+    write_str(data, &mut cursor, fields[2]); // path
+    write_str(data, &mut cursor, fields[3]); // query
+    write_str(data, &mut cursor, fields[4]); // fragment
+    offset as i32
+}
+
+/// Allocate room for `bytes` on the host heap and write it into linear
+/// memory as a length-prefixed buffer (the raw-bytes counterpart to
+/// `write_len_prefixed_string`), returning the allocation pointer, or
+/// `-1` if the module's memory couldn't accommodate it.
+fn write_len_prefixed_bytes(caller: &mut Caller<'_, HostContext>, bytes: &[u8]) -> i32 {
+    let total_size = 4 + bytes.len();
+    let offset = match caller.data_mut().heap.alloc(total_size) {
+        Some(offset) => offset,
+        None => return -1,
+    };
+    let view = caller.data().mem;
+    let memory = match view.memory() {
+        Ok(memory) => memory,
+        Err(_) => {
+            caller.data_mut().heap.free(offset);
+            return -1;
+        }
+    };
+    let data = memory.data_mut(&mut *caller);
+    if offset + total_size > data.len() {
+        caller.data_mut().heap.free(offset);
+        return -1;
+    }
+    data[offset..offset + 4].copy_from_slice(&(bytes.len() as u32).to_le_bytes());
+    data[offset + 4..offset + 4 + bytes.len()].copy_from_slice(bytes);
+    offset as i32
+}
+
+/// Write `entries` into linear memory as a length-prefixed directory
+/// listing: a 4-byte little-endian entry count, then for each entry its
+/// name (length-prefixed string), a 1-byte type flag (`0` = file, `1` =
+/// directory), and its size/mtime as 8-byte little-endian `u64`s — the
+/// per-entry metadata `file_list_dir` callers need to walk a tree without
+/// a separate `file_size` call per entry. Returns the allocation pointer,
+/// or `-1` if memory couldn't accommodate it.
+fn write_dir_listing(caller: &mut Caller<'_, HostContext>, entries: &[file_io::DirEntryInfo]) -> i32 {
+    let total_size: usize = 4 + entries
+        .iter()
+        .map(|entry| 4 + entry.name.len() + 1 + 8 + 8)
+        .sum::<usize>();
+    let offset = match caller.data_mut().heap.alloc(total_size) {
+        Some(offset) => offset,
+        None => return -1,
+    };
+    let view = caller.data().mem;
+    let memory = match view.memory() {
+        Ok(memory) => memory,
+        Err(_) => {
+            caller.data_mut().heap.free(offset);
+            return -1;
+        }
+    };
+    let data = memory.data_mut(&mut *caller);
+    if offset + total_size > data.len() {
+        caller.data_mut().heap.free(offset);
+        return -1;
+    }
+    let mut cursor = offset;
+    data[cursor..cursor + 4].copy_from_slice(&(entries.len() as u32).to_le_bytes());
+    cursor += 4;
+    for entry in entries {
+        let name_bytes = entry.name.as_bytes();
+        data[cursor..cursor + 4].copy_from_slice(&(name_bytes.len() as u32).to_le_bytes());
+        cursor += 4;
+        data[cursor..cursor + name_bytes.len()].copy_from_slice(name_bytes);
+        cursor += name_bytes.len();
+        data[cursor] = entry.is_dir as u8;
+        cursor += 1;
+        data[cursor..cursor + 8].copy_from_slice(&entry.size.to_le_bytes());
+        cursor += 8;
+        data[cursor..cursor + 8].copy_from_slice(&entry.mtime.to_le_bytes());
+        cursor += 8;
+    }
+    offset as i32
+}
+
+/// Write `entries` as a packed directory listing at the caller-supplied
+/// `result_ptr`, the `list_directory` counterpart to `write_dir_listing`:
+/// a leading `u32` entry count, then for each entry a `u32` name length,
+/// the UTF-8 name bytes, a `u64` size, and a `u8` flag byte (bit 0 =
+/// is_directory) — no mtime, since `list_directory` callers write into a
+/// buffer they sized themselves rather than receiving a fresh allocation.
+/// Returns the entry count, or `-1` if the record wouldn't fit in
+/// `result_ptr..data.len()`.
+fn write_dir_entries_at(caller: &mut Caller<'_, HostContext>, result_ptr: usize, entries: &[file_io::DirEntryInfo]) -> i32 {
+    let total_size: usize = 4 + entries
+        .iter()
+        .map(|entry| 4 + entry.name.len() + 8 + 1)
+        .sum::<usize>();
+    let view = caller.data().mem;
+    let memory = match view.memory() {
+        Ok(memory) => memory,
+        Err(_) => return -1,
+    };
+    let data = memory.data_mut(&mut *caller);
+    if result_ptr + total_size > data.len() {
+        return -1;
+    }
+    let mut cursor = result_ptr;
+    data[cursor..cursor + 4].copy_from_slice(&(entries.len() as u32).to_le_bytes());
+    cursor += 4;
+    for entry in entries {
+        let name_bytes = entry.name.as_bytes();
+        data[cursor..cursor + 4].copy_from_slice(&(name_bytes.len() as u32).to_le_bytes());
+        cursor += 4;
+        data[cursor..cursor + name_bytes.len()].copy_from_slice(name_bytes);
+        cursor += name_bytes.len();
+        data[cursor..cursor + 8].copy_from_slice(&entry.size.to_le_bytes());
+        cursor += 8;
+        data[cursor] = entry.is_dir as u8;
+        cursor += 1;
+    }
+    entries.len() as i32
+}
+
+/// Shared tail of every `http_get`/`http_post`/`http_put`/`http_patch`/
+/// `http_delete` host function: on success, record the response so
+/// `http_last_status`/`http_last_header` can inspect it, write the body
+/// into memory, and return its pointer; on failure, return `-1`.
+fn write_http_response(
+    caller: &mut Caller<'_, HostContext>,
+    result: Result<http_client::HttpResponse, CompilerError>,
+) -> i32 {
+    match result {
+        Ok(response) => {
+            let pointer = write_len_prefixed_string(caller, &response.body);
+            http_client::set_last_response(response);
+            pointer
+        }
+        Err(e) => {
+            println!("❌ [HTTP] Request failed: {}", e);
+            if e.to_string().contains(http_client::TIMEOUT_MARKER) {
+                -2
+            } else {
+                -1
+            }
+        }
+    }
+}
+
+/// Pure, `Caller`-free mirrors of the string/number host functions that
+/// parse raw length-prefixed buffers out of guest memory, exposed only
+/// under `cfg(fuzzing)` (the cfg `cargo fuzz` passes automatically) so the
+/// differential fuzz target in `fuzz/` can call them directly against
+/// arbitrary byte buffers and `(ptr, len)` pairs, without constructing a
+/// full `Linker`/`Module`/`Instance`. Each delegates to the same
+/// `decode_*` functions the real host closures in `add_stdlib_functions`
+/// use, so there is exactly one implementation of the bounds-checking
+/// logic under test.
+#[cfg(fuzzing)]
+pub mod fuzz_exports {
+    use super::{decode_len_prefixed_str, CompilerError};
+
+    pub fn read_len_prefixed_str(data: &[u8], ptr: i32) -> Result<&str, CompilerError> {
+        decode_len_prefixed_str(data, ptr)
+    }
+
+    /// Mirrors the `string_to_int` host function.
+    pub fn string_to_int(data: &[u8], ptr: i32) -> i32 {
+        decode_len_prefixed_str(data, ptr).ok().and_then(|s| s.parse::<i32>().ok()).unwrap_or(0)
+    }
+
+    /// Mirrors the `string_to_float` host function.
+    pub fn string_to_float(data: &[u8], ptr: i32) -> f64 {
+        decode_len_prefixed_str(data, ptr).ok().and_then(|s| s.parse::<f64>().ok()).unwrap_or(0.0)
+    }
+
+    /// Mirrors the `string_concat` host function's read side (the
+    /// allocate-and-write side is exercised directly via `HostHeap`).
+    pub fn string_concat(data: &[u8], ptr1: i32, ptr2: i32) -> String {
+        let str1 = decode_len_prefixed_str(data, ptr1).unwrap_or("");
+        let str2 = decode_len_prefixed_str(data, ptr2).unwrap_or("");
+        format!("{}{}", str1, str2)
+    }
+}
+
+/// `Store` context for `CleanRuntime`'s async execution: the host heap
+/// allocator plus the cached view over the module's exported memory.
+pub struct HostContext {
+    heap: HostHeap,
+    mem: MemView,
+    /// Headers accumulated by `http_set_header` since the last `http_*`
+    /// request, consumed and cleared by the next one — a per-call builder
+    /// rather than a field on `HttpClient`, since the client is a shared
+    /// global singleton (see `get_http_client`) while headers are scoped
+    /// to a single request.
+    pending_headers: Vec<(String, String)>,
+    /// Timeout/redirect/retry policy for every `http_*` call made through
+    /// this `Store`, mutated by `http_set_timeout`/`http_set_max_redirects`/
+    /// `http_set_retries` and otherwise left at `HttpConfig::default()` —
+    /// scoped here rather than on the global `HttpClient` singleton since
+    /// different module instances may want different policies.
+    http_config: http_client::HttpConfig,
+    /// When set by `execute_async_capturing`, the `print`/`println`/
+    /// `printl`/`print_simple`/`printl_simple` host functions append here
+    /// instead of writing to the process's real stdout, so the caller gets
+    /// the program's output back as a value. `None` (the default, used by
+    /// plain `execute_async`) preserves the old behavior of printing
+    /// straight to the inherited stdout.
+    captured_output: Option<Arc<Mutex<String>>>,
+}
+
+impl HostContext {
+    fn new(heap_base: usize) -> Self {
+        HostContext {
+            heap: HostHeap::new(heap_base),
+            mem: MemView::default(),
+            pending_headers: Vec::new(),
+            http_config: http_client::HttpConfig::default(),
+            captured_output: None,
+        }
+    }
+
+    /// Take and clear the headers accumulated by `http_set_header`,
+    /// handing ownership to whichever `http_*` call consumes them next.
+    fn take_pending_headers(&mut self) -> Vec<(String, String)> {
+        std::mem::take(&mut self.pending_headers)
+    }
+}
+
+/// Write `text` (plus a trailing newline if `newline`) to `caller`'s
+/// output destination: the in-memory buffer set up by
+/// `execute_async_capturing`, if any, otherwise the process's real
+/// stdout — matching whatever `print`/`println`/`printl`/`print_simple`/
+/// `printl_simple` was called.
+fn emit_output(caller: &Caller<'_, HostContext>, text: &str, newline: bool) {
+    if let Some(buffer) = &caller.data().captured_output {
+        let mut buffer = buffer.lock().unwrap();
+        buffer.push_str(text);
+        if newline {
+            buffer.push('\n');
+        }
+    } else if newline {
+        println!("{}", text);
+    } else {
+        print!("{}", text);
+    }
 }
 
 impl CleanRuntime {
-    /// Create a new Clean Language runtime with async support
+    /// Create a new Clean Language runtime with async support, using the
+    /// OS temp dir for `temp_file_create`/`temp_dir_create` scratch space.
     pub fn new() -> Result<Self, CompilerError> {
+        Self::with_temp_dir(std::env::temp_dir())
+    }
+
+    /// Like `new`, but scratch files/directories created through
+    /// `temp_file_create`/`temp_dir_create` are placed under `temp_dir`
+    /// instead of the OS default — so batch-execution tooling can
+    /// centralize a run's inputs, error logs, and job queues under one
+    /// controllable location.
+    pub fn with_temp_dir(temp_dir: std::path::PathBuf) -> Result<Self, CompilerError> {
         // Initialize HTTP client
         init_http_client();
-        
+
         // Enable async support in Wasmtime configuration
         let mut config = Config::new();
         config.async_support(true);
         config.wasm_threads(true);
-        
+        config.consume_fuel(true);
+        config.epoch_interruption(true);
+
         let engine = Engine::new(&config)
             .map_err(|e| CompilerError::runtime_error(
                 format!("Failed to create async WebAssembly engine: {}", e),
                 None, None
             ))?;
-        
+
         Ok(CleanRuntime {
             engine,
             task_scheduler: Arc::new(Mutex::new(TaskScheduler::new())),
-            future_resolver: Arc::new(Mutex::new(FutureResolver::new())),
+            future_resolver: FutureResolver::new(),
+            channel_registry: ChannelRegistry::new(),
+            file_handles: FileHandleTable::new(),
             background_tasks: Arc::new(Mutex::new(Vec::new())),
+            temp_files: TempFileRegistry::new(temp_dir),
         })
     }
     
-    /// Execute a WebAssembly module with async support
-    pub async fn execute_async(&self, wasm_bytes: &[u8]) -> Result<(), CompilerError> {
+    /// Execute a WebAssembly module with async support, enforcing the
+    /// given resource ceiling. Pass `RuntimeLimits::default()` to run
+    /// unbounded. On success, returns the fuel remaining in the store if
+    /// `limits.fuel` was set, or `None` otherwise.
+    pub async fn execute_async(&self, wasm_bytes: &[u8], limits: RuntimeLimits) -> Result<Option<u64>, CompilerError> {
+        let (_, remaining_fuel) = self.execute_module(wasm_bytes, limits, false).await?;
+        Ok(remaining_fuel)
+    }
+
+    /// Like `execute_async`, but collects the program's output in memory
+    /// instead of writing it to the process's real stdout, and surfaces
+    /// the entry function's return value as an `ExecutionOutcome` instead
+    /// of discarding it. Intended for embedding this runtime as a library
+    /// (test harnesses, REPLs) where the caller needs the program's
+    /// output and status rather than inheriting the host process's file
+    /// descriptors.
+    pub async fn execute_async_capturing(&self, wasm_bytes: &[u8], limits: RuntimeLimits) -> Result<ExecutionOutcome, CompilerError> {
+        let (outcome, _) = self.execute_module(wasm_bytes, limits, true).await?;
+        Ok(outcome)
+    }
+
+    /// Shared implementation behind `execute_async`/`execute_async_capturing`.
+    /// When `capture_output` is `true`, the `print`-family host functions
+    /// append to an in-memory buffer instead of the process's real stdout;
+    /// either way, the entry function's return value and the store's
+    /// remaining fuel (if metered) are read back into the result.
+    async fn execute_module(&self, wasm_bytes: &[u8], limits: RuntimeLimits, capture_output: bool) -> Result<(ExecutionOutcome, Option<u64>), CompilerError> {
         let module = Module::new(&self.engine, wasm_bytes)
             .map_err(|e| CompilerError::runtime_error(
                 format!("Failed to create WebAssembly module: {}", e),
                 None, None
             ))?;
         
-        let mut store = Store::new(&self.engine, ());
+        let mut store = Store::new(&self.engine, HostContext::new(HostHeap::DEFAULT_BASE));
+        let capture_buffer = if capture_output {
+            let buffer = Arc::new(Mutex::new(String::new()));
+            store.data_mut().captured_output = Some(Arc::clone(&buffer));
+            Some(buffer)
+        } else {
+            None
+        };
         let mut linker = Linker::new(&self.engine);
-        
+
         // Add async-aware runtime functions
         self.add_async_runtime_functions(&mut linker)?;
         
@@ -107,102 +751,187 @@ impl CleanRuntime {
                 format!("Failed to instantiate WebAssembly module: {}", e),
                 None, None
             ))?;
-        
+
+        // Cache the module's exported memory once so host functions never
+        // need to re-resolve it; `MemView`'s accessors still re-fetch the
+        // data slice from this handle on every call.
+        if let Some(memory) = instance.get_memory(&mut store, "memory") {
+            store.data_mut().mem.set(memory);
+        }
+
+        // If the module negotiates a heap arena via `__host_heap_base`,
+        // rebase the host heap there so it never collides with the
+        // module's own compile-time allocations. Otherwise keep the
+        // default base the store was created with.
+        if let Some(global) = instance.get_global(&mut store, "__host_heap_base") {
+            if let wasmtime::Val::I32(base) = global.get(&mut store) {
+                if base >= 0 {
+                    store.data_mut().heap.rebase(base as usize);
+                }
+            }
+        }
+
+        if let Some(fuel) = limits.fuel {
+            store.set_fuel(fuel)
+                .map_err(|e| CompilerError::runtime_error(
+                    format!("Failed to set fuel budget: {}", e),
+                    None, None
+                ))?;
+        }
+
+        // If a wall-clock deadline is set, advance the engine's epoch on a
+        // fixed cadence from a dedicated thread so the deadline trips even
+        // if the module never yields back to a host call. The ticker is
+        // stopped as soon as `start` returns, whichever way it returns.
+        const EPOCH_TICK: Duration = Duration::from_millis(50);
+        let epoch_ticker_stop = Arc::new(AtomicBool::new(false));
+        if let Some(wall_timeout) = limits.wall_timeout {
+            let ticks = ((wall_timeout.as_secs_f64() / EPOCH_TICK.as_secs_f64()).ceil() as u64).max(1);
+            store.set_epoch_deadline(ticks);
+            let engine = self.engine.clone();
+            let stop = Arc::clone(&epoch_ticker_stop);
+            thread::spawn(move || {
+                while !stop.load(Ordering::Relaxed) {
+                    thread::sleep(EPOCH_TICK);
+                    engine.increment_epoch();
+                }
+            });
+        }
+
         // Execute the start function
         if let Some(start_func) = instance.get_func(&mut store, "start") {
             println!("🚀 Executing Clean Language program with async support...");
             println!("--- Output ---");
-            
+
             // Check the function signature to create the right results buffer
             let start_type = start_func.ty(&store);
             let results_len = start_type.results().len();
-            
+
             // Create a buffer to store return values
             let mut results = vec![wasmtime::Val::I32(0); results_len];
-            
-            start_func.call_async(&mut store, &[], &mut results).await
-                .map_err(|e| CompilerError::runtime_error(
-                    format!("Runtime error during execution: {}", e),
-                    None, None
-                ))?;
-            
+
+            let call_result = start_func.call_async(&mut store, &[], &mut results).await;
+            epoch_ticker_stop.store(true, Ordering::Relaxed);
+            call_result.map_err(|e| {
+                let message = e.to_string();
+                if limits.fuel.is_some() && message.contains("fuel") {
+                    let consumed = limits.fuel.unwrap().saturating_sub(store.get_fuel().unwrap_or(0));
+                    CompilerError::runtime_error(
+                        format!("Execution aborted: fuel budget exhausted after consuming {} units ({})", consumed, message),
+                        None, None
+                    )
+                } else if limits.wall_timeout.is_some() && (message.contains("epoch") || message.contains("interrupt")) {
+                    CompilerError::runtime_error(
+                        format!("Execution aborted: wall-clock deadline exceeded ({})", message),
+                        None, None
+                    )
+                } else {
+                    CompilerError::runtime_error(
+                        format!("Runtime error during execution: {}", message),
+                        None, None
+                    )
+                }
+            })?;
+
             println!("--- End Output ---");
-            
+
             // If there are return values, print them
             if !results.is_empty() {
                 println!("Return value: {:?}", results[0]);
             }
-            
+
             // Wait for background tasks to complete
             self.wait_for_background_tasks().await;
-            
+
             println!("✅ Execution completed successfully!");
+
+            let remaining_fuel = if limits.fuel.is_some() { store.get_fuel().ok() } else { None };
+            let return_value = results.first().and_then(|val| match val {
+                wasmtime::Val::I32(v) => Some(*v as i64),
+                wasmtime::Val::I64(v) => Some(*v),
+                _ => None,
+            });
+            let stdout = match capture_buffer {
+                Some(buffer) => Arc::try_unwrap(buffer)
+                    .map(|mutex| mutex.into_inner().unwrap())
+                    .unwrap_or_default(),
+                None => String::new(),
+            };
+            let outcome = ExecutionOutcome {
+                exit_code: return_value.map(|v| v as i32).unwrap_or(0),
+                stdout,
+                stderr: String::new(),
+                return_value,
+            };
+            return Ok((outcome, remaining_fuel));
         } else {
             return Err(CompilerError::runtime_error(
                 "No start function found in WebAssembly module".to_string(),
                 None, None
             ));
         }
-        
-        Ok(())
     }
-    
+
+    /// Run `wasm_bytes` to completion, trapping with
+    /// `CompilerError::runtime_error("execution exceeded time limit")` if
+    /// it's still running after `deadline` — a convenience wrapper over
+    /// `execute_async`'s epoch-interruption wall-clock limit for callers
+    /// who only care about a single timeout and don't need fuel or the
+    /// other `RuntimeLimits` knobs. Interruption trips even mid-computation
+    /// (a tight loop with no host calls), not just at I/O points, since
+    /// the epoch ticker advances the engine's epoch independently of the
+    /// running module.
+    pub async fn execute_with_deadline(&self, wasm_bytes: &[u8], deadline: Duration) -> Result<(), CompilerError> {
+        let limits = RuntimeLimits { wall_timeout: Some(deadline), ..RuntimeLimits::default() };
+        self.execute_async(wasm_bytes, limits).await.map(|_| ()).map_err(|e| {
+            let message = e.to_string();
+            if message.contains("wall-clock deadline exceeded") {
+                CompilerError::runtime_error("execution exceeded time limit".to_string(), None, None)
+            } else {
+                e
+            }
+        })
+    }
+
+    /// Run `wasm_bytes` to completion under a fixed fuel budget, trapping
+    /// with `CompilerError::runtime_error` (naming how much fuel was
+    /// consumed) if it runs out before finishing — a convenience wrapper
+    /// over `execute_async`'s fuel metering for callers who only care
+    /// about a deterministic instruction budget and don't need a
+    /// wall-clock deadline too. Unlike `execute_with_deadline`'s epoch
+    /// ticks, fuel consumption is identical across machines and runs,
+    /// making it suitable for test determinism and for giving many
+    /// untrusted programs a fair, reproducible CPU quota. On success,
+    /// returns the fuel left unspent so callers can meter actual cost.
+    pub async fn execute_with_fuel(&self, wasm_bytes: &[u8], fuel: u64) -> Result<u64, CompilerError> {
+        let limits = RuntimeLimits { fuel: Some(fuel), ..RuntimeLimits::default() };
+        let remaining = self.execute_async(wasm_bytes, limits).await?;
+        Ok(remaining.unwrap_or(0))
+    }
+
     /// Add async-aware runtime functions to the linker
-    fn add_async_runtime_functions(&self, linker: &mut Linker<()>) -> Result<(), CompilerError> {
+    fn add_async_runtime_functions(&self, linker: &mut Linker<HostContext>) -> Result<(), CompilerError> {
         let task_scheduler = Arc::clone(&self.task_scheduler);
-        let future_resolver = Arc::clone(&self.future_resolver);
+        let future_resolver = self.future_resolver.clone();
+        let channel_registry = self.channel_registry.clone();
         let background_tasks = Arc::clone(&self.background_tasks);
         
         // Enhanced print functions with async support
-        linker.func_wrap("env", "print", |mut caller: Caller<'_, ()>, str_ptr: i32, str_len: i32| {
-            if let Some(memory) = caller.get_export("memory") {
-                if let Some(memory) = memory.into_memory() {
-                    let data = memory.data(&caller);
-                    if str_ptr >= 0 && str_len >= 0 {
-                        let start = str_ptr as usize;
-                        let len = str_len as usize;
-                        if start + len <= data.len() {
-                            if let Ok(string) = std::str::from_utf8(&data[start..start + len]) {
-                                print!("{}", string);
-                            } else {
-                                print!("[invalid UTF-8]");
-                            }
-                        } else {
-                            print!("[out of bounds]");
-                        }
-                    } else {
-                        print!("[invalid pointer/length]");
-                    }
-                }
-            }
+        linker.func_wrap("env", "print", |mut caller: Caller<'_, HostContext>, str_ptr: i32, str_len: i32| {
+            let view = caller.data().mem;
+            let string = view.read_str(&mut caller, str_ptr, str_len)?.to_string();
+            emit_output(&caller, &string, false);
             Ok(())
         })
         .map_err(|e| CompilerError::runtime_error(
             format!("Failed to create print function: {}", e),
             None, None
         ))?;
-        
-        linker.func_wrap("env", "println", |mut caller: Caller<'_, ()>, str_ptr: i32, str_len: i32| {
-            if let Some(memory) = caller.get_export("memory") {
-                if let Some(memory) = memory.into_memory() {
-                    let data = memory.data(&caller);
-                    if str_ptr >= 0 && str_len >= 0 {
-                        let start = str_ptr as usize;
-                        let len = str_len as usize;
-                        if start + len <= data.len() {
-                            if let Ok(string) = std::str::from_utf8(&data[start..start + len]) {
-                                println!("{}", string);
-                            } else {
-                                println!("[invalid UTF-8]");
-                            }
-                        } else {
-                            println!("[out of bounds]");
-                        }
-                    } else {
-                        println!("[invalid pointer/length]");
-                    }
-                }
-            }
+
+        linker.func_wrap("env", "println", |mut caller: Caller<'_, HostContext>, str_ptr: i32, str_len: i32| {
+            let view = caller.data().mem;
+            let string = view.read_str(&mut caller, str_ptr, str_len)?.to_string();
+            emit_output(&caller, &string, true);
             Ok(())
         })
         .map_err(|e| CompilerError::runtime_error(
@@ -211,27 +940,10 @@ impl CleanRuntime {
         ))?;
 
         // Add printl function (alias for println for compatibility)
-        linker.func_wrap("env", "printl", |mut caller: Caller<'_, ()>, str_ptr: i32, str_len: i32| {
-            if let Some(memory) = caller.get_export("memory") {
-                if let Some(memory) = memory.into_memory() {
-                    let data = memory.data(&caller);
-                    if str_ptr >= 0 && str_len >= 0 {
-                        let start = str_ptr as usize;
-                        let len = str_len as usize;
-                        if start + len <= data.len() {
-                            if let Ok(string) = std::str::from_utf8(&data[start..start + len]) {
-                                println!("{}", string);
-                            } else {
-                                println!("[invalid UTF-8]");
-                            }
-                        } else {
-                            println!("[out of bounds]");
-                        }
-                    } else {
-                        println!("[invalid pointer/length]");
-                    }
-                }
-            }
+        linker.func_wrap("env", "printl", |mut caller: Caller<'_, HostContext>, str_ptr: i32, str_len: i32| {
+            let view = caller.data().mem;
+            let string = view.read_str(&mut caller, str_ptr, str_len)?.to_string();
+            emit_output(&caller, &string, true);
             Ok(())
         })
         .map_err(|e| CompilerError::runtime_error(
@@ -240,8 +952,8 @@ impl CleanRuntime {
         ))?;
 
         // Add simple print functions for compatibility
-        linker.func_wrap("env", "print_simple", |_caller: Caller<'_, ()>, value: i32| {
-            print!("{}", value);
+        linker.func_wrap("env", "print_simple", |caller: Caller<'_, HostContext>, value: i32| {
+            emit_output(&caller, &value.to_string(), false);
             Ok(())
         })
         .map_err(|e| CompilerError::runtime_error(
@@ -249,8 +961,8 @@ impl CleanRuntime {
             None, None
         ))?;
 
-        linker.func_wrap("env", "printl_simple", |_caller: Caller<'_, ()>, value: i32| {
-            println!("{}", value);
+        linker.func_wrap("env", "printl_simple", |caller: Caller<'_, HostContext>, value: i32| {
+            emit_output(&caller, &value.to_string(), true);
             Ok(())
         })
         .map_err(|e| CompilerError::runtime_error(
@@ -258,73 +970,382 @@ impl CleanRuntime {
             None, None
         ))?;
         
+        // Default capacity for the bootstrap channel handed to every
+        // background task, analogous to the pipe buffer size given to a
+        // spawned process.
+        const BOOTSTRAP_CHANNEL_CAPACITY: u32 = 16;
+
         // Async task management functions
         let task_scheduler_clone = Arc::clone(&task_scheduler);
-        linker.func_wrap("env", "start_background_task", move |_caller: Caller<'_, ()>, _task_name_ptr: i32, _task_name_len: i32| -> i32 {
+        let channel_registry_clone = channel_registry.clone();
+        linker.func_wrap("env", "start_background_task", move |_caller: Caller<'_, HostContext>, _task_name_ptr: i32, _task_name_len: i32| -> (i32, i32) {
+            let channel_handle = channel_registry_clone.open(BOOTSTRAP_CHANNEL_CAPACITY);
             let mut scheduler = task_scheduler_clone.lock().unwrap();
-            let task_id = scheduler.create_task("background_task".to_string());
-            println!("🔄 Started background task #{}", task_id);
-            task_id as i32
+            let task_id = scheduler.create_task("background_task".to_string(), channel_handle);
+            println!("🔄 Started background task #{} with channel #{}", task_id, channel_handle);
+            (task_id as i32, channel_handle as i32)
         })
         .map_err(|e| CompilerError::runtime_error(
             format!("Failed to create start_background_task function: {}", e),
             None, None
         ))?;
         
-        // Future resolution functions
-        let future_resolver_clone = Arc::clone(&future_resolver);
-        linker.func_wrap("env", "create_future", move |_caller: Caller<'_, ()>, _future_name_ptr: i32, _future_name_len: i32| -> i32 {
-            let mut resolver = future_resolver_clone.lock().unwrap();
-            let future_id = format!("future_{}", resolver.futures.len());
-            resolver.create_future(future_id.clone());
-            println!("🔮 Created future: {}", future_id);
-            1 // Return success
+        // Future resolution functions. Unlike the old `future_N` string-id,
+        // i32-only stubs, these back a real suspendable value: `await_future`
+        // genuinely yields the calling fiber (it's registered as an async
+        // host function) until a matching `resolve_future` call delivers a
+        // tagged payload, whether that call comes from this module or from
+        // a background task running on another thread.
+        let future_resolver_clone = future_resolver.clone();
+        linker.func_wrap("env", "create_future", move |_caller: Caller<'_, HostContext>| -> i64 {
+            let future_id = future_resolver_clone.new_payload_future();
+            println!("🔮 Created future #{}", future_id);
+            future_id as i64
         })
         .map_err(|e| CompilerError::runtime_error(
             format!("Failed to create create_future function: {}", e),
             None, None
         ))?;
-        
-        let future_resolver_clone2 = Arc::clone(&future_resolver);
-        linker.func_wrap("env", "resolve_future", move |_caller: Caller<'_, ()>, future_id: i32, value: i32| -> i32 {
-            let mut resolver = future_resolver_clone2.lock().unwrap();
-            let future_name = format!("future_{}", future_id);
-            resolver.resolve_future(future_name, value);
-            println!("✅ Resolved future #{} with value: {}", future_id, value);
-            1 // Return success
+
+        // `resolve_future(id, tag, ptr)`: fixed-width tags (I32/I64/F64)
+        // read their raw little-endian bytes directly at `ptr`; Str/Bytes
+        // read a 4-byte length prefix followed by the content, matching
+        // the layout `HostHeap`-backed strings are already written in.
+        let future_resolver_clone2 = future_resolver.clone();
+        linker.func_wrap("env", "resolve_future", move |mut caller: Caller<'_, HostContext>, future_id: i64, tag: i32, ptr: i32| -> i32 {
+            let tag = match ValueTag::from_i32(tag) {
+                Some(tag) => tag,
+                None => return 0,
+            };
+            let view = caller.data().mem;
+            let bytes = match tag {
+                ValueTag::I32 => view.read_bytes(&mut caller, ptr, 4),
+                ValueTag::I64 | ValueTag::F64 => view.read_bytes(&mut caller, ptr, 8),
+                ValueTag::Str | ValueTag::Bytes => view.read_len_prefixed_bytes(&mut caller, ptr),
+            };
+            match bytes {
+                Ok(bytes) => {
+                    future_resolver_clone2.resolve_payload(future_id as u64, FuturePayload { tag, bytes });
+                    println!("✅ Resolved future #{}", future_id);
+                    1 // Return success
+                }
+                Err(_) => 0,
+            }
         })
         .map_err(|e| CompilerError::runtime_error(
             format!("Failed to create resolve_future function: {}", e),
             None, None
         ))?;
-        
-        // Background processing function
-        let background_tasks_clone = Arc::clone(&background_tasks);
-        linker.func_wrap("env", "execute_background", move |_caller: Caller<'_, ()>, _operation_ptr: i32, _operation_len: i32| -> i32 {
-            let mut tasks = background_tasks_clone.lock().unwrap();
-            let task = BackgroundTask {
-                id: tasks.len() as u32,
-                name: "background_operation".to_string(),
-                started_at: Instant::now(),
-                status: TaskStatus::Running,
-            };
-            println!("🔄 Executing background operation #{}", task.id);
-            tasks.push(task);
-            
-            // Simulate background work
-            thread::spawn(move || {
-                thread::sleep(Duration::from_millis(100));
-                println!("✅ Background operation completed");
-            });
-            
-            1 // Return success
+
+        // `await_future(id) -> (tag, ptr)`: suspends the fiber until
+        // `resolve_future` delivers a payload, then copies it into a fresh
+        // `HostHeap` allocation the caller owns. For Str/Bytes, `ptr` holds
+        // a 4-byte length prefix followed by the content (see above); for
+        // the fixed-width tags the caller already knows how many bytes to
+        // read from `tag` alone.
+        let future_resolver_clone3 = future_resolver.clone();
+        linker.func_wrap_async("env", "await_future", move |mut caller: Caller<'_, HostContext>, (future_id,): (i64,)| {
+            let resolver = future_resolver_clone3.clone();
+            Box::new(async move {
+                let payload = resolver.await_payload(future_id as u64).await;
+                let view = caller.data().mem;
+                let needs_len_prefix = matches!(payload.tag, ValueTag::Str | ValueTag::Bytes);
+                let total_size = if needs_len_prefix { 4 + payload.bytes.len() } else { payload.bytes.len() };
+
+                let ptr = match caller.data_mut().heap.alloc(total_size.max(1)) {
+                    Some(offset) => {
+                        let written = if needs_len_prefix {
+                            view.write_bytes(&mut caller, offset as i32, &(payload.bytes.len() as u32).to_le_bytes()).is_ok()
+                                && view.write_bytes(&mut caller, offset as i32 + 4, &payload.bytes).is_ok()
+                        } else {
+                            view.write_bytes(&mut caller, offset as i32, &payload.bytes).is_ok()
+                        };
+                        if written {
+                            offset as i32
+                        } else {
+                            caller.data_mut().heap.free(offset);
+                            0
+                        }
+                    }
+                    None => 0,
+                };
+
+                Ok((payload.tag as i32, ptr))
+            })
         })
         .map_err(|e| CompilerError::runtime_error(
-            format!("Failed to create execute_background function: {}", e),
+            format!("Failed to create await_future function: {}", e),
             None, None
         ))?;
-        
-        // Add standard library functions
+
+        // Non-blocking file I/O: unlike `file_read`/`file_write` (which
+        // block the calling WASM instance until the syscall returns),
+        // these dispatch the actual read/write to a background thread the
+        // same way `start_background_task` dispatches abstract work
+        // (registered via `create_task`, tracked in `background_tasks` so
+        // `wait_for_background_tasks` sees it), and resolve a
+        // `FutureResolver` future with the result rather than blocking.
+        // The caller polls `is_future_resolved`/`get_future_bytes` (the
+        // string-id `FutureValue` API here, distinct from the tagged
+        // `create_future`/`await_future` i64-id system above) to pick up
+        // the result once ready.
+        let future_resolver_read = future_resolver.clone();
+        let task_scheduler_read = Arc::clone(&task_scheduler);
+        let channel_registry_read = channel_registry.clone();
+        let background_tasks_read = Arc::clone(&background_tasks);
+        linker.func_wrap("env", "file_read_async", move |mut caller: Caller<'_, HostContext>, path_ptr: i32, path_len: i32| -> i32 {
+            let view = caller.data().mem;
+            let path = match view.read_str(&mut caller, path_ptr, path_len) {
+                Ok(path) => path.to_string(),
+                Err(_) => return -1,
+            };
+
+            let future_id = future_resolver_read.create_future(Some("file_read".to_string()));
+            let channel_handle = channel_registry_read.open(BOOTSTRAP_CHANNEL_CAPACITY);
+            let task_id = task_scheduler_read.lock().unwrap().create_task(format!("file_read_async({})", path), channel_handle);
+            background_tasks_read.lock().unwrap().push(BackgroundTask {
+                id: task_id,
+                name: "file_read_async".to_string(),
+                started_at: Instant::now(),
+                status: TaskStatus::Running,
+                channel_handle: Some(channel_handle),
+            });
+
+            let resolver = future_resolver_read.clone();
+            let scheduler = Arc::clone(&task_scheduler_read);
+            let tasks = Arc::clone(&background_tasks_read);
+            let future_id_thread = future_id.clone();
+            thread::spawn(move || {
+                match FileIO::read_file(&path) {
+                    Ok(content) => {
+                        let _ = resolver.resolve_future_bytes(future_id_thread, content.into_bytes());
+                        scheduler.lock().unwrap().complete_task(task_id);
+                        if let Some(task) = tasks.lock().unwrap().iter_mut().find(|t| t.id == task_id) {
+                            task.status = TaskStatus::Completed;
+                        }
+                    }
+                    Err(e) => {
+                        let message = e.to_string();
+                        let _ = resolver.resolve_future(future_id_thread, FutureValue::Error(message.clone()));
+                        scheduler.lock().unwrap().fail_task(task_id, message.clone());
+                        if let Some(task) = tasks.lock().unwrap().iter_mut().find(|t| t.id == task_id) {
+                            task.status = TaskStatus::Failed(message);
+                        }
+                    }
+                }
+            });
+
+            write_len_prefixed_string(&mut caller, &future_id)
+        })
+        .map_err(|e| CompilerError::runtime_error(
+            format!("Failed to create file_read_async function: {}", e),
+            None, None
+        ))?;
+
+        let future_resolver_write = future_resolver.clone();
+        let task_scheduler_write = Arc::clone(&task_scheduler);
+        let channel_registry_write = channel_registry.clone();
+        let background_tasks_write = Arc::clone(&background_tasks);
+        linker.func_wrap("env", "file_write_async", move |mut caller: Caller<'_, HostContext>, path_ptr: i32, path_len: i32, content_ptr: i32, content_len: i32| -> i32 {
+            let view = caller.data().mem;
+            let path = match view.read_str(&mut caller, path_ptr, path_len) {
+                Ok(path) => path.to_string(),
+                Err(_) => return -1,
+            };
+            let content = match view.read_str(&mut caller, content_ptr, content_len) {
+                Ok(content) => content.to_string(),
+                Err(_) => return -1,
+            };
+
+            let future_id = future_resolver_write.create_future(Some("file_write".to_string()));
+            let channel_handle = channel_registry_write.open(BOOTSTRAP_CHANNEL_CAPACITY);
+            let task_id = task_scheduler_write.lock().unwrap().create_task(format!("file_write_async({})", path), channel_handle);
+            background_tasks_write.lock().unwrap().push(BackgroundTask {
+                id: task_id,
+                name: "file_write_async".to_string(),
+                started_at: Instant::now(),
+                status: TaskStatus::Running,
+                channel_handle: Some(channel_handle),
+            });
+
+            let resolver = future_resolver_write.clone();
+            let scheduler = Arc::clone(&task_scheduler_write);
+            let tasks = Arc::clone(&background_tasks_write);
+            let future_id_thread = future_id.clone();
+            thread::spawn(move || {
+                match FileIO::write_file(&path, &content) {
+                    Ok(()) => {
+                        let _ = resolver.resolve_future(future_id_thread, FutureValue::Boolean(true));
+                        scheduler.lock().unwrap().complete_task(task_id);
+                        if let Some(task) = tasks.lock().unwrap().iter_mut().find(|t| t.id == task_id) {
+                            task.status = TaskStatus::Completed;
+                        }
+                    }
+                    Err(e) => {
+                        let message = e.to_string();
+                        let _ = resolver.resolve_future(future_id_thread, FutureValue::Error(message.clone()));
+                        scheduler.lock().unwrap().fail_task(task_id, message.clone());
+                        if let Some(task) = tasks.lock().unwrap().iter_mut().find(|t| t.id == task_id) {
+                            task.status = TaskStatus::Failed(message);
+                        }
+                    }
+                }
+            });
+
+            write_len_prefixed_string(&mut caller, &future_id)
+        })
+        .map_err(|e| CompilerError::runtime_error(
+            format!("Failed to create file_write_async function: {}", e),
+            None, None
+        ))?;
+
+        // Poll whether the string-id future `id` (as returned by
+        // `file_read_async`/`file_write_async`) has been resolved yet,
+        // without blocking.
+        let future_resolver_poll = future_resolver.clone();
+        linker.func_wrap("env", "is_future_resolved", move |mut caller: Caller<'_, HostContext>, id_ptr: i32, id_len: i32| -> i32 {
+            let view = caller.data().mem;
+            let id = match view.read_str(&mut caller, id_ptr, id_len) {
+                Ok(id) => id.to_string(),
+                Err(_) => return 0,
+            };
+            if future_resolver_poll.is_resolved(&id) { 1 } else { 0 }
+        })
+        .map_err(|e| CompilerError::runtime_error(
+            format!("Failed to create is_future_resolved function: {}", e),
+            None, None
+        ))?;
+
+        // Copy the byte payload of a resolved `file_read_async` future
+        // into memory as a length-prefixed buffer, returning the
+        // allocation pointer, or `-1` if `id` isn't resolved, doesn't
+        // exist, or didn't resolve to bytes (e.g. a failed read, whose
+        // error is available as the future's `FutureValue::Error` but not
+        // through this byte-only accessor).
+        let future_resolver_bytes = future_resolver.clone();
+        linker.func_wrap("env", "get_future_bytes", move |mut caller: Caller<'_, HostContext>, id_ptr: i32, id_len: i32| -> i32 {
+            let view = caller.data().mem;
+            let id = match view.read_str(&mut caller, id_ptr, id_len) {
+                Ok(id) => id.to_string(),
+                Err(_) => return -1,
+            };
+            match future_resolver_bytes.get_future_bytes(&id) {
+                Some(bytes) => write_len_prefixed_bytes(&mut caller, &bytes),
+                None => -1,
+            }
+        })
+        .map_err(|e| CompilerError::runtime_error(
+            format!("Failed to create get_future_bytes function: {}", e),
+            None, None
+        ))?;
+
+        // Background processing function
+        let background_tasks_clone = Arc::clone(&background_tasks);
+        linker.func_wrap("env", "execute_background", move |_caller: Caller<'_, HostContext>, _operation_ptr: i32, _operation_len: i32| -> i32 {
+            let mut tasks = background_tasks_clone.lock().unwrap();
+            let task = BackgroundTask {
+                id: tasks.len() as u32,
+                name: "background_operation".to_string(),
+                started_at: Instant::now(),
+                status: TaskStatus::Running,
+                channel_handle: None,
+            };
+            println!("🔄 Executing background operation #{}", task.id);
+            tasks.push(task);
+
+            // Simulate background work with a tokio timer rather than a
+            // dedicated OS thread parked in `thread::sleep`, so the delay
+            // doesn't tie up a thread the executor could otherwise use.
+            tokio::spawn(async move {
+                tokio::time::sleep(Duration::from_millis(100)).await;
+                println!("✅ Background operation completed");
+            });
+
+            1 // Return success
+        })
+        .map_err(|e| CompilerError::runtime_error(
+            format!("Failed to create execute_background function: {}", e),
+            None, None
+        ))?;
+
+        // Typed message-passing channels: a bounded, handle-based pipe
+        // connecting a background task back to the main program, so
+        // results flow through `channel_recv` instead of a bare
+        // `println!`. `channel_open`/`channel_send`/`channel_close` never
+        // block; `channel_recv` blocks the calling thread on the
+        // channel's condvar until a message arrives or it is closed and
+        // drained, at which point it returns `channel_registry::EOF_TAG`.
+        let channel_registry_open = channel_registry.clone();
+        linker.func_wrap("env", "channel_open", move |_caller: Caller<'_, HostContext>, capacity: i32| -> i32 {
+            channel_registry_open.open(capacity.max(1) as u32) as i32
+        })
+        .map_err(|e| CompilerError::runtime_error(
+            format!("Failed to create channel_open function: {}", e),
+            None, None
+        ))?;
+
+        let channel_registry_send = channel_registry.clone();
+        linker.func_wrap("env", "channel_send", move |mut caller: Caller<'_, HostContext>, handle: i32, tag: i32, ptr: i32, len: i32| -> i32 {
+            let tag = match ValueTag::from_i32(tag) {
+                Some(tag) => tag,
+                None => return channel_registry::SEND_CLOSED,
+            };
+            let view = caller.data().mem;
+            match view.read_bytes(&mut caller, ptr, len) {
+                Ok(bytes) => channel_registry_send.send(handle as u32, FuturePayload { tag, bytes }),
+                Err(_) => channel_registry::SEND_CLOSED,
+            }
+        })
+        .map_err(|e| CompilerError::runtime_error(
+            format!("Failed to create channel_send function: {}", e),
+            None, None
+        ))?;
+
+        let channel_registry_recv = channel_registry.clone();
+        linker.func_wrap("env", "channel_recv", move |mut caller: Caller<'_, HostContext>, handle: i32| -> (i32, i32) {
+            let message = match channel_registry_recv.recv(handle as u32) {
+                Some(message) => message,
+                None => return (channel_registry::EOF_TAG, 0),
+            };
+            let view = caller.data().mem;
+            let needs_len_prefix = matches!(message.tag, ValueTag::Str | ValueTag::Bytes);
+            let total_size = if needs_len_prefix { 4 + message.bytes.len() } else { message.bytes.len() };
+
+            let ptr = match caller.data_mut().heap.alloc(total_size.max(1)) {
+                Some(offset) => {
+                    let written = if needs_len_prefix {
+                        view.write_bytes(&mut caller, offset as i32, &(message.bytes.len() as u32).to_le_bytes()).is_ok()
+                            && view.write_bytes(&mut caller, offset as i32 + 4, &message.bytes).is_ok()
+                    } else {
+                        view.write_bytes(&mut caller, offset as i32, &message.bytes).is_ok()
+                    };
+                    if written {
+                        offset as i32
+                    } else {
+                        caller.data_mut().heap.free(offset);
+                        0
+                    }
+                }
+                None => 0,
+            };
+
+            (message.tag as i32, ptr)
+        })
+        .map_err(|e| CompilerError::runtime_error(
+            format!("Failed to create channel_recv function: {}", e),
+            None, None
+        ))?;
+
+        let channel_registry_close = channel_registry.clone();
+        linker.func_wrap("env", "channel_close", move |_caller: Caller<'_, HostContext>, handle: i32| {
+            channel_registry_close.close(handle as u32);
+        })
+        .map_err(|e| CompilerError::runtime_error(
+            format!("Failed to create channel_close function: {}", e),
+            None, None
+        ))?;
+
+        // Add standard library functions
         self.add_stdlib_functions(linker)?;
         
         Ok(())
@@ -332,41 +1353,57 @@ impl CleanRuntime {
     
     /// Add standard library functions (HTTP, File I/O, etc.)
     #[allow(unused_mut)]
-    fn add_stdlib_functions(&self, linker: &mut Linker<()>) -> Result<(), CompilerError> {
+    fn add_stdlib_functions(&self, linker: &mut Linker<HostContext>) -> Result<(), CompilerError> {
+        let file_handles = self.file_handles.clone();
+
+        // Export the same `HostHeap` every string/file/HTTP/URL host
+        // function allocates from, so guest code can `malloc` a buffer,
+        // fill it itself, and hand the pointer to a host function (or
+        // `free` one a host function handed back) without either side
+        // needing its own separate arena.
+        linker.func_wrap("env", "malloc", |mut caller: Caller<'_, HostContext>, size: i32| -> i32 {
+            if size < 0 {
+                return 0;
+            }
+            caller.data_mut().heap.alloc(size as usize).map(|offset| offset as i32).unwrap_or(0)
+        })
+        .map_err(|e| CompilerError::runtime_error(
+            format!("Failed to create malloc function: {}", e),
+            None, None
+        ))?;
+
+        linker.func_wrap("env", "free", |mut caller: Caller<'_, HostContext>, ptr: i32| {
+            if ptr >= 0 {
+                caller.data_mut().heap.free(ptr as usize);
+            }
+        })
+        .map_err(|e| CompilerError::runtime_error(
+            format!("Failed to create free function: {}", e),
+            None, None
+        ))?;
+
         // Type conversion functions - CRITICAL for runtime functionality
-        linker.func_wrap("env", "int_to_string", |mut caller: Caller<'_, ()>, value: i32| -> i32 {
+        linker.func_wrap("env", "int_to_string", |mut caller: Caller<'_, HostContext>, value: i32| -> i32 {
             let string_value = value.to_string();
-            
-            // Get memory to store the string
-            if let Some(memory) = caller.get_export("memory") {
-                if let Some(memory) = memory.into_memory() {
-                    let mut data = memory.data_mut(&mut caller);
-                    
-                    // Simple string storage: length (4 bytes) + string data
-                    let string_bytes = string_value.as_bytes();
-                    let total_size = 4 + string_bytes.len();
-                    
-                    // Find a place to store the string (simple allocation at end of used memory)
-                    let mut offset = 1024; // Start after initial memory
-                    while offset + total_size < data.len() {
-                        // Check if this area is free (all zeros)
-                        let is_free = data[offset..offset + total_size].iter().all(|&b| b == 0);
-                        if is_free {
-                            break;
+            let string_bytes = string_value.as_bytes();
+            let total_size = 4 + string_bytes.len();
+
+            if let Some(offset) = caller.data_mut().heap.alloc(total_size) {
+                if let Some(memory) = caller.get_export("memory") {
+                    if let Some(memory) = memory.into_memory() {
+                        let data = memory.data_mut(&mut caller);
+                        if offset + total_size <= data.len() {
+                            // Store length
+                            data[offset..offset + 4].copy_from_slice(&(string_bytes.len() as u32).to_le_bytes());
+                            // Store string data
+                            data[offset + 4..offset + 4 + string_bytes.len()].copy_from_slice(string_bytes);
+                            return offset as i32;
                         }
-                        offset += 32; // Move in 32-byte chunks
-                    }
-                    
-                    if offset + total_size < data.len() {
-                        // Store length
-                        data[offset..offset + 4].copy_from_slice(&(string_bytes.len() as u32).to_le_bytes());
-                        // Store string data
-                        data[offset + 4..offset + 4 + string_bytes.len()].copy_from_slice(string_bytes);
-                        return offset as i32;
                     }
                 }
+                caller.data_mut().heap.free(offset);
             }
-            
+
             0 // Return null pointer on failure
         })
         .map_err(|e| CompilerError::runtime_error(
@@ -374,73 +1411,53 @@ impl CleanRuntime {
             None, None
         ))?;
         
-        linker.func_wrap("env", "float_to_string", |mut caller: Caller<'_, ()>, value: f64| -> i32 {
+        linker.func_wrap("env", "float_to_string", |mut caller: Caller<'_, HostContext>, value: f64| -> i32 {
             let string_value = value.to_string();
-            
-            // Get memory to store the string
-            if let Some(memory) = caller.get_export("memory") {
-                if let Some(memory) = memory.into_memory() {
-                    let mut data = memory.data_mut(&mut caller);
-                    
-                    // Simple string storage: length (4 bytes) + string data
-                    let string_bytes = string_value.as_bytes();
-                    let total_size = 4 + string_bytes.len();
-                    
-                    // Find a place to store the string
-                    let mut offset = 1024;
-                    while offset + total_size < data.len() {
-                        let is_free = data[offset..offset + total_size].iter().all(|&b| b == 0);
-                        if is_free {
-                            break;
+            let string_bytes = string_value.as_bytes();
+            let total_size = 4 + string_bytes.len();
+
+            if let Some(offset) = caller.data_mut().heap.alloc(total_size) {
+                if let Some(memory) = caller.get_export("memory") {
+                    if let Some(memory) = memory.into_memory() {
+                        let data = memory.data_mut(&mut caller);
+                        if offset + total_size <= data.len() {
+                            // Store length
+                            data[offset..offset + 4].copy_from_slice(&(string_bytes.len() as u32).to_le_bytes());
+                            // Store string data
+                            data[offset + 4..offset + 4 + string_bytes.len()].copy_from_slice(string_bytes);
+                            return offset as i32;
                         }
-                        offset += 32;
-                    }
-                    
-                    if offset + total_size < data.len() {
-                        // Store length
-                        data[offset..offset + 4].copy_from_slice(&(string_bytes.len() as u32).to_le_bytes());
-                        // Store string data
-                        data[offset + 4..offset + 4 + string_bytes.len()].copy_from_slice(string_bytes);
-                        return offset as i32;
                     }
                 }
+                caller.data_mut().heap.free(offset);
             }
-            
+
             0 // Return null pointer on failure
         })
         .map_err(|e| CompilerError::runtime_error(
             format!("Failed to create float_to_string function: {}", e),
             None, None
         ))?;
-        
-        linker.func_wrap("env", "bool_to_string", |mut caller: Caller<'_, ()>, value: i32| -> i32 {
+
+        linker.func_wrap("env", "bool_to_string", |mut caller: Caller<'_, HostContext>, value: i32| -> i32 {
             let string_value = if value != 0 { "true" } else { "false" };
-            
-            // Get memory to store the string
-            if let Some(memory) = caller.get_export("memory") {
-                if let Some(memory) = memory.into_memory() {
-                    let mut data = memory.data_mut(&mut caller);
-                    
-                    let string_bytes = string_value.as_bytes();
-                    let total_size = 4 + string_bytes.len();
-                    
-                    let mut offset = 1024;
-                    while offset + total_size < data.len() {
-                        let is_free = data[offset..offset + total_size].iter().all(|&b| b == 0);
-                        if is_free {
-                            break;
+            let string_bytes = string_value.as_bytes();
+            let total_size = 4 + string_bytes.len();
+
+            if let Some(offset) = caller.data_mut().heap.alloc(total_size) {
+                if let Some(memory) = caller.get_export("memory") {
+                    if let Some(memory) = memory.into_memory() {
+                        let data = memory.data_mut(&mut caller);
+                        if offset + total_size <= data.len() {
+                            data[offset..offset + 4].copy_from_slice(&(string_bytes.len() as u32).to_le_bytes());
+                            data[offset + 4..offset + 4 + string_bytes.len()].copy_from_slice(string_bytes);
+                            return offset as i32;
                         }
-                        offset += 32;
-                    }
-                    
-                    if offset + total_size < data.len() {
-                        data[offset..offset + 4].copy_from_slice(&(string_bytes.len() as u32).to_le_bytes());
-                        data[offset + 4..offset + 4 + string_bytes.len()].copy_from_slice(string_bytes);
-                        return offset as i32;
                     }
                 }
+                caller.data_mut().heap.free(offset);
             }
-            
+
             0
         })
         .map_err(|e| CompilerError::runtime_error(
@@ -449,118 +1466,49 @@ impl CleanRuntime {
         ))?;
         
         // String parsing functions
-        linker.func_wrap("env", "string_to_int", |mut caller: Caller<'_, ()>, str_ptr: i32| -> i32 {
-            if let Some(memory) = caller.get_export("memory") {
-                if let Some(memory) = memory.into_memory() {
-                    let data = memory.data(&caller);
-                    
-                    if str_ptr >= 0 && (str_ptr as usize) + 4 < data.len() {
-                        // Read string length
-                        let len_bytes = &data[str_ptr as usize..str_ptr as usize + 4];
-                        let str_len = u32::from_le_bytes([len_bytes[0], len_bytes[1], len_bytes[2], len_bytes[3]]) as usize;
-                        
-                        if str_ptr as usize + 4 + str_len < data.len() {
-                            // Read string data
-                            let str_data = &data[str_ptr as usize + 4..str_ptr as usize + 4 + str_len];
-                            if let Ok(string_value) = std::str::from_utf8(str_data) {
-                                return string_value.parse::<i32>().unwrap_or(0);
-                            }
-                        }
-                    }
-                }
+        linker.func_wrap("env", "string_to_int", |mut caller: Caller<'_, HostContext>, str_ptr: i32| -> i32 {
+            let view = caller.data().mem;
+            match view.read_len_prefixed_str(&mut caller, str_ptr) {
+                Ok(string_value) => string_value.parse::<i32>().unwrap_or(0),
+                Err(_) => 0,
             }
-            0
         })
         .map_err(|e| CompilerError::runtime_error(
             format!("Failed to create string_to_int function: {}", e),
             None, None
         ))?;
-        
-        linker.func_wrap("env", "string_to_float", |mut caller: Caller<'_, ()>, str_ptr: i32| -> f64 {
-            if let Some(memory) = caller.get_export("memory") {
-                if let Some(memory) = memory.into_memory() {
-                    let data = memory.data(&caller);
-                    
-                    if str_ptr >= 0 && (str_ptr as usize) + 4 < data.len() {
-                        let len_bytes = &data[str_ptr as usize..str_ptr as usize + 4];
-                        let str_len = u32::from_le_bytes([len_bytes[0], len_bytes[1], len_bytes[2], len_bytes[3]]) as usize;
-                        
-                        if str_ptr as usize + 4 + str_len < data.len() {
-                            let str_data = &data[str_ptr as usize + 4..str_ptr as usize + 4 + str_len];
-                            if let Ok(string_value) = std::str::from_utf8(str_data) {
-                                return string_value.parse::<f64>().unwrap_or(0.0);
-                            }
-                        }
-                    }
-                }
+
+        linker.func_wrap("env", "string_to_float", |mut caller: Caller<'_, HostContext>, str_ptr: i32| -> f64 {
+            let view = caller.data().mem;
+            match view.read_len_prefixed_str(&mut caller, str_ptr) {
+                Ok(string_value) => string_value.parse::<f64>().unwrap_or(0.0),
+                Err(_) => 0.0,
             }
-            0.0
         })
         .map_err(|e| CompilerError::runtime_error(
             format!("Failed to create string_to_float function: {}", e),
             None, None
         ))?;
-        
+
         // String concatenation function
-        linker.func_wrap("env", "string_concat", |mut caller: Caller<'_, ()>, str1_ptr: i32, str2_ptr: i32| -> i32 {
-            if let Some(memory) = caller.get_export("memory") {
-                if let Some(memory) = memory.into_memory() {
-                    let mut data = memory.data_mut(&mut caller);
-                    
-                    // Read first string
-                    let str1 = if str1_ptr >= 0 && (str1_ptr as usize) + 4 < data.len() {
-                        let len_bytes = &data[str1_ptr as usize..str1_ptr as usize + 4];
-                        let str1_len = u32::from_le_bytes([len_bytes[0], len_bytes[1], len_bytes[2], len_bytes[3]]) as usize;
-                        
-                        if str1_ptr as usize + 4 + str1_len < data.len() {
-                            let str1_data = &data[str1_ptr as usize + 4..str1_ptr as usize + 4 + str1_len];
-                            std::str::from_utf8(str1_data).unwrap_or("")
-                        } else { "" }
-                    } else { "" };
-                    
-                    // Read second string
-                    let str2 = if str2_ptr >= 0 && (str2_ptr as usize) + 4 < data.len() {
-                        let len_bytes = &data[str2_ptr as usize..str2_ptr as usize + 4];
-                        let str2_len = u32::from_le_bytes([len_bytes[0], len_bytes[1], len_bytes[2], len_bytes[3]]) as usize;
-                        
-                        if str2_ptr as usize + 4 + str2_len < data.len() {
-                            let str2_data = &data[str2_ptr as usize + 4..str2_ptr as usize + 4 + str2_len];
-                            std::str::from_utf8(str2_data).unwrap_or("")
-                        } else { "" }
-                    } else { "" };
-                    
-                    // Concatenate strings
-                    let result = format!("{}{}", str1, str2);
-                    let result_bytes = result.as_bytes();
-                    
-                    // For now, use a simple approach: find space in existing memory
-                    // This is a placeholder - proper memory management would be more complex
-                    let result_len = result_bytes.len() as u32;
-                    let total_size = 4 + result_len as usize; // 4 bytes for length + string content
-                    
-                    // Look for free space in memory (starting from offset 1024)
-                    let mut allocation_ptr = 1024;
-                    while allocation_ptr + total_size < data.len() {
-                        // Check if this space is free (first 4 bytes are 0)
-                        let check_bytes = &data[allocation_ptr..allocation_ptr + 4];
-                        if check_bytes == [0, 0, 0, 0] {
-                            // Found free space, write the string here
-                            // Write length
-                            let len_bytes = result_len.to_le_bytes();
-                            data[allocation_ptr..allocation_ptr + 4].copy_from_slice(&len_bytes);
-                            
-                            // Write string content
-                            data[allocation_ptr + 4..allocation_ptr + 4 + result_bytes.len()].copy_from_slice(result_bytes);
-                            
-                            return allocation_ptr as i32;
-                        }
-                        allocation_ptr += 16; // Check next 16-byte aligned position
-                    }
-                    
-                    // If no free space found, return 0 (allocation failed)
-                    return 0;
+        linker.func_wrap("env", "string_concat", |mut caller: Caller<'_, HostContext>, str1_ptr: i32, str2_ptr: i32| -> i32 {
+            let view = caller.data().mem;
+            let str1 = view.read_len_prefixed_str(&mut caller, str1_ptr).unwrap_or("").to_string();
+            let str2 = view.read_len_prefixed_str(&mut caller, str2_ptr).unwrap_or("").to_string();
+            let result = format!("{}{}", str1, str2);
+            let result_bytes = result.as_bytes();
+            let total_size = 4 + result_bytes.len();
+
+            if let Some(offset) = caller.data_mut().heap.alloc(total_size) {
+                if view.write_bytes(&mut caller, offset as i32, &(result_bytes.len() as u32).to_le_bytes()).is_ok()
+                    && view.write_bytes(&mut caller, offset as i32 + 4, result_bytes).is_ok()
+                {
+                    return offset as i32;
                 }
+                caller.data_mut().heap.free(offset);
             }
+
+            // If no free space found, return 0 (allocation failed)
             0
         })
         .map_err(|e| CompilerError::runtime_error(
@@ -569,7 +1517,7 @@ impl CleanRuntime {
         ))?;
         
         // String comparison function
-        linker.func_wrap("env", "string_compare", |mut caller: Caller<'_, ()>, str1_ptr: i32, str2_ptr: i32| -> i32 {
+        linker.func_wrap("env", "string_compare", |mut caller: Caller<'_, HostContext>, str1_ptr: i32, str2_ptr: i32| -> i32 {
             if let Some(memory) = caller.get_export("memory") {
                 if let Some(memory) = memory.into_memory() {
                     let data = memory.data(&caller);
@@ -611,85 +1559,116 @@ impl CleanRuntime {
             None, None
         ))?;
         
-        // HTTP functions with real network requests
-        linker.func_wrap("env", "http_get", |mut caller: Caller<'_, ()>, url_ptr: i32, url_len: i32| -> i32 {
-            // Extract URL from memory
-            if let Some(memory) = caller.get_export("memory") {
-                if let Some(memory) = memory.into_memory() {
-                    let data = memory.data(&caller);
-                    
-                    if url_ptr >= 0 && url_len >= 0 {
-                        let start = url_ptr as usize;
-                        let len = url_len as usize;
-                        
-                        if start + len <= data.len() {
-                            if let Ok(url) = std::str::from_utf8(&data[start..start + len]) {
-                                // Make real HTTP request
-                                let client = get_http_client();
-                                match client.get(url) {
-                                    Ok(response) => {
-                                        println!("✅ [HTTP GET] Real response received: {} bytes", response.body.len());
-                                        println!("📄 [HTTP GET] Response body:\n{}", response.body);
-                                        return 1; // Success indicator
-                                    }
-                                    Err(e) => {
-                                        println!("❌ [HTTP GET] Request failed: {}", e);
-                                        return 0; // Failure indicator
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
+        // Accumulate a request header to send with the next `http_*` call,
+        // mirroring the usual "insert header, then build/send" pattern:
+        // headers build up in `HostContext::pending_headers` and are taken
+        // (and cleared) by whichever `http_get`/`http_post`/`http_put`/
+        // `http_patch`/`http_delete` call runs next.
+        linker.func_wrap("env", "http_set_header", |mut caller: Caller<'_, HostContext>, name_ptr: i32, name_len: i32, value_ptr: i32, value_len: i32| {
+            let view = caller.data().mem;
+            let name = match view.read_str(&mut caller, name_ptr, name_len) {
+                Ok(name) => name.to_string(),
+                Err(_) => return,
+            };
+            let value = match view.read_str(&mut caller, value_ptr, value_len) {
+                Ok(value) => value.to_string(),
+                Err(_) => return,
+            };
+            caller.data_mut().pending_headers.push((name, value));
+        })
+        .map_err(|e| CompilerError::runtime_error(
+            format!("Failed to create http_set_header function: {}", e),
+            None, None
+        ))?;
+
+        // Configure the read/write timeout (in milliseconds) applied to
+        // every subsequent `http_*` call on this `Store`. Negative or zero
+        // values are ignored, leaving the current timeout unchanged.
+        linker.func_wrap("env", "http_set_timeout", |mut caller: Caller<'_, HostContext>, millis: i64| {
+            if millis > 0 {
+                caller.data_mut().http_config.timeout = std::time::Duration::from_millis(millis as u64);
             }
-            
-            println!("❌ [HTTP GET] Invalid URL parameters");
-            0 // Failure indicator
         })
         .map_err(|e| CompilerError::runtime_error(
-            format!("Failed to create http_get function: {}", e),
+            format!("Failed to create http_set_timeout function: {}", e),
             None, None
         ))?;
-        
-        linker.func_wrap("env", "http_post", |mut caller: Caller<'_, ()>, url_ptr: i32, url_len: i32, body_ptr: i32, body_len: i32| -> i32 {
-            // Extract URL and body from memory
-            if let Some(memory) = caller.get_export("memory") {
-                if let Some(memory) = memory.into_memory() {
-                    let data = memory.data(&caller);
-                    
-                    // Extract URL
-                    if url_ptr >= 0 && url_len >= 0 && body_ptr >= 0 && body_len >= 0 {
-                        let url_start = url_ptr as usize;
-                        let url_length = url_len as usize;
-                        let body_start = body_ptr as usize;
-                        let body_length = body_len as usize;
-                        
-                        if url_start + url_length <= data.len() && body_start + body_length <= data.len() {
-                            if let (Ok(url), Ok(body)) = (
-                                std::str::from_utf8(&data[url_start..url_start + url_length]),
-                                std::str::from_utf8(&data[body_start..body_start + body_length])
-                            ) {
-                                // Make real HTTP POST request
-                                let client = get_http_client();
-                                match client.post(url, body) {
-                                    Ok(response) => {
-                                        println!("✅ [HTTP POST] Real response received: {} bytes", response.body.len());
-                                        println!("📄 [HTTP POST] Response body:\n{}", response.body);
-                                        return 1; // Success indicator
-                                    }
-                                    Err(e) => {
-                                        println!("❌ [HTTP POST] Request failed: {}", e);
-                                        return 0; // Failure indicator
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
+
+        // Configure how many redirects `http_*` calls will follow before
+        // giving up and returning the redirect response as-is. Negative
+        // values are ignored.
+        linker.func_wrap("env", "http_set_max_redirects", |mut caller: Caller<'_, HostContext>, count: i32| {
+            if count >= 0 {
+                caller.data_mut().http_config.max_redirects = count as u32;
             }
-            
-            println!("❌ [HTTP POST] Invalid parameters");
-            0 // Failure indicator
+        })
+        .map_err(|e| CompilerError::runtime_error(
+            format!("Failed to create http_set_max_redirects function: {}", e),
+            None, None
+        ))?;
+
+        // Configure how many times `http_*` calls will retry a failed
+        // request (server error or non-timeout connection failure; see
+        // `HttpClient::send_with_retry`). Negative values are ignored.
+        linker.func_wrap("env", "http_set_retries", |mut caller: Caller<'_, HostContext>, count: i32| {
+            if count >= 0 {
+                caller.data_mut().http_config.retries = count as u32;
+            }
+        })
+        .map_err(|e| CompilerError::runtime_error(
+            format!("Failed to create http_set_retries function: {}", e),
+            None, None
+        ))?;
+
+        // HTTP functions with real network requests. Each `.await`s the
+        // request on a `tokio::task::spawn_blocking` thread rather than
+        // running `HttpClient`'s blocking `TcpStream` I/O directly on the
+        // executor thread, so a slow server no longer monopolizes a
+        // worker while other WASM instances (or this one's own epoch
+        // interruption) need to make progress. Each writes the response
+        // body into linear memory using the same length-prefixed layout
+        // `file_read` uses (4-byte little-endian length followed by UTF-8
+        // bytes) and returns the allocation pointer, or `-1` on failure;
+        // `http_last_status`/`http_last_header` below expose the status
+        // code and headers of whichever request completed most recently.
+        linker.func_wrap_async("env", "http_get", |mut caller: Caller<'_, HostContext>, (url_ptr, url_len): (i32, i32)| {
+            Box::new(async move {
+                let view = caller.data().mem;
+                let url = match view.read_str(&mut caller, url_ptr, url_len) {
+                    Ok(url) => url.to_string(),
+                    Err(_) => return Ok(-1),
+                };
+                let headers = caller.data_mut().take_pending_headers();
+                let config = caller.data().http_config;
+                let result = tokio::task::spawn_blocking(move || get_http_client().get(&url, &headers, &config))
+                    .await
+                    .unwrap_or_else(|e| Err(CompilerError::runtime_error(format!("http_get task panicked: {}", e), None, None)));
+                Ok(write_http_response(&mut caller, result))
+            })
+        })
+        .map_err(|e| CompilerError::runtime_error(
+            format!("Failed to create http_get function: {}", e),
+            None, None
+        ))?;
+
+        linker.func_wrap_async("env", "http_post", |mut caller: Caller<'_, HostContext>, (url_ptr, url_len, body_ptr, body_len): (i32, i32, i32, i32)| {
+            Box::new(async move {
+                let view = caller.data().mem;
+                let url = match view.read_str(&mut caller, url_ptr, url_len) {
+                    Ok(url) => url.to_string(),
+                    Err(_) => return Ok(-1),
+                };
+                let body = match view.read_str(&mut caller, body_ptr, body_len) {
+                    Ok(body) => body.to_string(),
+                    Err(_) => return Ok(-1),
+                };
+                let headers = caller.data_mut().take_pending_headers();
+                let config = caller.data().http_config;
+                let result = tokio::task::spawn_blocking(move || get_http_client().post(&url, &body, &headers, &config))
+                    .await
+                    .unwrap_or_else(|e| Err(CompilerError::runtime_error(format!("http_post task panicked: {}", e), None, None)));
+                Ok(write_http_response(&mut caller, result))
+            })
         })
         .map_err(|e| CompilerError::runtime_error(
             format!("Failed to create http_post function: {}", e),
@@ -697,44 +1676,24 @@ impl CleanRuntime {
         ))?;
 
         // HTTP PUT with real network requests
-        linker.func_wrap("env", "http_put", |mut caller: Caller<'_, ()>, url_ptr: i32, url_len: i32, body_ptr: i32, body_len: i32| -> i32 {
-            // Extract URL and body from memory
-            if let Some(memory) = caller.get_export("memory") {
-                if let Some(memory) = memory.into_memory() {
-                    let data = memory.data(&caller);
-                    
-                    if url_ptr >= 0 && url_len >= 0 && body_ptr >= 0 && body_len >= 0 {
-                        let url_start = url_ptr as usize;
-                        let url_length = url_len as usize;
-                        let body_start = body_ptr as usize;
-                        let body_length = body_len as usize;
-                        
-                        if url_start + url_length <= data.len() && body_start + body_length <= data.len() {
-                            if let (Ok(url), Ok(body)) = (
-                                std::str::from_utf8(&data[url_start..url_start + url_length]),
-                                std::str::from_utf8(&data[body_start..body_start + body_length])
-                            ) {
-                                // Make real HTTP PUT request
-                                let client = get_http_client();
-                                match client.put(url, body) {
-                                    Ok(response) => {
-                                        println!("✅ [HTTP PUT] Real response received: {} bytes", response.body.len());
-                                        println!("📄 [HTTP PUT] Response body:\n{}", response.body);
-                                        return 1; // Success indicator
-                                    }
-                                    Err(e) => {
-                                        println!("❌ [HTTP PUT] Request failed: {}", e);
-                                        return 0; // Failure indicator
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-            
-            println!("❌ [HTTP PUT] Invalid parameters");
-            0 // Failure indicator
+        linker.func_wrap_async("env", "http_put", |mut caller: Caller<'_, HostContext>, (url_ptr, url_len, body_ptr, body_len): (i32, i32, i32, i32)| {
+            Box::new(async move {
+                let view = caller.data().mem;
+                let url = match view.read_str(&mut caller, url_ptr, url_len) {
+                    Ok(url) => url.to_string(),
+                    Err(_) => return Ok(-1),
+                };
+                let body = match view.read_str(&mut caller, body_ptr, body_len) {
+                    Ok(body) => body.to_string(),
+                    Err(_) => return Ok(-1),
+                };
+                let headers = caller.data_mut().take_pending_headers();
+                let config = caller.data().http_config;
+                let result = tokio::task::spawn_blocking(move || get_http_client().put(&url, &body, &headers, &config))
+                    .await
+                    .unwrap_or_else(|e| Err(CompilerError::runtime_error(format!("http_put task panicked: {}", e), None, None)));
+                Ok(write_http_response(&mut caller, result))
+            })
         })
         .map_err(|e| CompilerError::runtime_error(
             format!("Failed to create http_put function: {}", e),
@@ -742,44 +1701,24 @@ impl CleanRuntime {
         ))?;
 
         // HTTP PATCH with real network requests
-        linker.func_wrap("env", "http_patch", |mut caller: Caller<'_, ()>, url_ptr: i32, url_len: i32, body_ptr: i32, body_len: i32| -> i32 {
-            // Extract URL and body from memory
-            if let Some(memory) = caller.get_export("memory") {
-                if let Some(memory) = memory.into_memory() {
-                    let data = memory.data(&caller);
-                    
-                    if url_ptr >= 0 && url_len >= 0 && body_ptr >= 0 && body_len >= 0 {
-                        let url_start = url_ptr as usize;
-                        let url_length = url_len as usize;
-                        let body_start = body_ptr as usize;
-                        let body_length = body_len as usize;
-                        
-                        if url_start + url_length <= data.len() && body_start + body_length <= data.len() {
-                            if let (Ok(url), Ok(body)) = (
-                                std::str::from_utf8(&data[url_start..url_start + url_length]),
-                                std::str::from_utf8(&data[body_start..body_start + body_length])
-                            ) {
-                                // Make real HTTP PATCH request
-                                let client = get_http_client();
-                                match client.patch(url, body) {
-                                    Ok(response) => {
-                                        println!("✅ [HTTP PATCH] Real response received: {} bytes", response.body.len());
-                                        println!("📄 [HTTP PATCH] Response body:\n{}", response.body);
-                                        return 1; // Success indicator
-                                    }
-                                    Err(e) => {
-                                        println!("❌ [HTTP PATCH] Request failed: {}", e);
-                                        return 0; // Failure indicator
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-            
-            println!("❌ [HTTP PATCH] Invalid parameters");
-            0 // Failure indicator
+        linker.func_wrap_async("env", "http_patch", |mut caller: Caller<'_, HostContext>, (url_ptr, url_len, body_ptr, body_len): (i32, i32, i32, i32)| {
+            Box::new(async move {
+                let view = caller.data().mem;
+                let url = match view.read_str(&mut caller, url_ptr, url_len) {
+                    Ok(url) => url.to_string(),
+                    Err(_) => return Ok(-1),
+                };
+                let body = match view.read_str(&mut caller, body_ptr, body_len) {
+                    Ok(body) => body.to_string(),
+                    Err(_) => return Ok(-1),
+                };
+                let headers = caller.data_mut().take_pending_headers();
+                let config = caller.data().http_config;
+                let result = tokio::task::spawn_blocking(move || get_http_client().patch(&url, &body, &headers, &config))
+                    .await
+                    .unwrap_or_else(|e| Err(CompilerError::runtime_error(format!("http_patch task panicked: {}", e), None, None)));
+                Ok(write_http_response(&mut caller, result))
+            })
         })
         .map_err(|e| CompilerError::runtime_error(
             format!("Failed to create http_patch function: {}", e),
@@ -787,141 +1726,263 @@ impl CleanRuntime {
         ))?;
 
         // HTTP DELETE with real network requests
-        linker.func_wrap("env", "http_delete", |mut caller: Caller<'_, ()>, url_ptr: i32, url_len: i32| -> i32 {
-            // Extract URL from memory
-            if let Some(memory) = caller.get_export("memory") {
-                if let Some(memory) = memory.into_memory() {
-                    let data = memory.data(&caller);
-                    
-                    if url_ptr >= 0 && url_len >= 0 {
-                        let start = url_ptr as usize;
-                        let len = url_len as usize;
-                        
-                        if start + len <= data.len() {
-                            if let Ok(url) = std::str::from_utf8(&data[start..start + len]) {
-                                // Make real HTTP DELETE request
-                                let client = get_http_client();
-                                match client.delete(url) {
-                                    Ok(response) => {
-                                        println!("✅ [HTTP DELETE] Real response received: {} bytes", response.body.len());
-                                        println!("📄 [HTTP DELETE] Response body:\n{}", response.body);
-                                        return 1; // Success indicator
-                                    }
-                                    Err(e) => {
-                                        println!("❌ [HTTP DELETE] Request failed: {}", e);
-                                        return 0; // Failure indicator
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-            
-            println!("❌ [HTTP DELETE] Invalid URL parameters");
-            0 // Failure indicator
+        linker.func_wrap_async("env", "http_delete", |mut caller: Caller<'_, HostContext>, (url_ptr, url_len): (i32, i32)| {
+            Box::new(async move {
+                let view = caller.data().mem;
+                let url = match view.read_str(&mut caller, url_ptr, url_len) {
+                    Ok(url) => url.to_string(),
+                    Err(_) => return Ok(-1),
+                };
+                let headers = caller.data_mut().take_pending_headers();
+                let config = caller.data().http_config;
+                let result = tokio::task::spawn_blocking(move || get_http_client().delete(&url, &headers, &config))
+                    .await
+                    .unwrap_or_else(|e| Err(CompilerError::runtime_error(format!("http_delete task panicked: {}", e), None, None)));
+                Ok(write_http_response(&mut caller, result))
+            })
         })
         .map_err(|e| CompilerError::runtime_error(
             format!("Failed to create http_delete function: {}", e),
             None, None
         ))?;
-        
+
+        // The status code of whichever `http_*` call completed most
+        // recently, or `-1` if none has completed yet.
+        linker.func_wrap("env", "http_last_status", |_caller: Caller<'_, HostContext>| -> i32 {
+            http_client::last_status()
+        })
+        .map_err(|e| CompilerError::runtime_error(
+            format!("Failed to create http_last_status function: {}", e),
+            None, None
+        ))?;
+
+        // Write the value of header `name` from the most recent `http_*`
+        // response into memory using the same length-prefixed layout
+        // `http_get` et al. use for bodies, returning the allocation
+        // pointer, or `-1` if there is no response or no such header.
+        linker.func_wrap("env", "http_last_header", |mut caller: Caller<'_, HostContext>, name_ptr: i32, name_len: i32| -> i32 {
+            let view = caller.data().mem;
+            let name = match view.read_str(&mut caller, name_ptr, name_len) {
+                Ok(name) => name.to_string(),
+                Err(_) => return -1,
+            };
+            match http_client::last_header(&name) {
+                Some(value) => write_len_prefixed_string(&mut caller, &value),
+                None => -1,
+            }
+        })
+        .map_err(|e| CompilerError::runtime_error(
+            format!("Failed to create http_last_header function: {}", e),
+            None, None
+        ))?;
+
+        // Validate a URL and write its components (scheme, host, port,
+        // path, query, fragment) into memory as a length-prefixed record
+        // (see `write_url_record`), returning the allocation pointer, or
+        // `-1` if the URL doesn't parse.
+        linker.func_wrap("env", "url_parse", |mut caller: Caller<'_, HostContext>, url_ptr: i32, url_len: i32| -> i32 {
+            let view = caller.data().mem;
+            let url_str = match view.read_str(&mut caller, url_ptr, url_len) {
+                Ok(url) => url.to_string(),
+                Err(_) => return -1,
+            };
+            match url::parse(&url_str) {
+                Ok(parts) => write_url_record(&mut caller, &parts),
+                Err(_) => -1,
+            }
+        })
+        .map_err(|e| CompilerError::runtime_error(
+            format!("Failed to create url_parse function: {}", e),
+            None, None
+        ))?;
+
+        // Percent-encode a string for safe use in a query parameter or
+        // path segment, writing the result as a length-prefixed string and
+        // returning its pointer.
+        linker.func_wrap("env", "url_encode", |mut caller: Caller<'_, HostContext>, str_ptr: i32, str_len: i32| -> i32 {
+            let view = caller.data().mem;
+            let s = match view.read_str(&mut caller, str_ptr, str_len) {
+                Ok(s) => s.to_string(),
+                Err(_) => return -1,
+            };
+            write_len_prefixed_string(&mut caller, &url::encode(&s))
+        })
+        .map_err(|e| CompilerError::runtime_error(
+            format!("Failed to create url_encode function: {}", e),
+            None, None
+        ))?;
+
+        // Reverse `url_encode`, writing the decoded string as a
+        // length-prefixed string and returning its pointer, or `-1` if the
+        // input has a malformed `%XX` escape or decodes to invalid UTF-8.
+        linker.func_wrap("env", "url_decode", |mut caller: Caller<'_, HostContext>, str_ptr: i32, str_len: i32| -> i32 {
+            let view = caller.data().mem;
+            let s = match view.read_str(&mut caller, str_ptr, str_len) {
+                Ok(s) => s.to_string(),
+                Err(_) => return -1,
+            };
+            match url::decode(&s) {
+                Some(decoded) => write_len_prefixed_string(&mut caller, &decoded),
+                None => -1,
+            }
+        })
+        .map_err(|e| CompilerError::runtime_error(
+            format!("Failed to create url_decode function: {}", e),
+            None, None
+        ))?;
+
         // File I/O functions with real filesystem operations
-        linker.func_wrap("env", "file_read", |mut caller: Caller<'_, ()>, path_ptr: i32, path_len: i32, _result_ptr: i32| -> i32 {
-            if let Some(memory) = caller.get_export("memory") {
-                if let Some(memory) = memory.into_memory() {
-                    let data = memory.data(&caller);
-                    
-                    if path_ptr >= 0 && path_len >= 0 {
-                        let start = path_ptr as usize;
-                        let len = path_len as usize;
-                        
-                        if start + len <= data.len() {
-                            if let Ok(path) = std::str::from_utf8(&data[start..start + len]) {
-                                // Make real file read
-                                match FileIO::read_file(path) {
-                                    Ok(content) => {
-                                        // Store content in memory and return pointer
-                                        let mut data = memory.data_mut(&mut caller);
-                                        let content_bytes = content.as_bytes();
-                                        let total_size = 4 + content_bytes.len();
-                                        
-                                        // Find a place to store the content
-                                        let mut offset = 1024;
-                                        while offset + total_size < data.len() {
-                                            let is_free = data[offset..offset + total_size].iter().all(|&b| b == 0);
-                                            if is_free {
-                                                break;
-                                            }
-                                            offset += 32;
-                                        }
-                                        
-                                        if offset + total_size < data.len() {
-                                            // Store length
-                                            data[offset..offset + 4].copy_from_slice(&(content_bytes.len() as u32).to_le_bytes());
-                                            // Store content
-                                            data[offset + 4..offset + 4 + content_bytes.len()].copy_from_slice(content_bytes);
-                                            return offset as i32;
-                                        }
-                                    }
-                                    Err(_) => {
-                                        return -1; // Error indicator
-                                    }
-                                }
-                            }
-                        }
-                    }
+        // Read the whole file at `path`, `.await`ing `tokio::fs::read_to_string`
+        // rather than blocking the executor thread on the syscall the way
+        // the old synchronous implementation did (see `file_io::FileIO::read_file`
+        // for the blocking equivalent other callers still use). Writes the
+        // content as a length-prefixed string and returns the allocation
+        // pointer, or `-1` on failure.
+        linker.func_wrap_async("env", "file_read", |mut caller: Caller<'_, HostContext>, (path_ptr, path_len, _result_ptr): (i32, i32, i32)| {
+            Box::new(async move {
+                let view = caller.data().mem;
+                let path = match view.read_str(&mut caller, path_ptr, path_len) {
+                    Ok(path) => path.to_string(),
+                    Err(_) => return Ok(-1),
+                };
+                match tokio::fs::read_to_string(&path).await {
+                    Ok(content) => Ok(write_len_prefixed_string(&mut caller, &content)),
+                    Err(_) => Ok(-1),
+                }
+            })
+        })
+        .map_err(|e| CompilerError::runtime_error(
+            format!("Failed to create file_read function: {}", e),
+            None, None
+        ))?;
+
+        // Seek-and-read only the `length`-byte window starting at
+        // `offset`, so large files can be streamed a chunk at a time
+        // instead of slurped whole like `file_read`. Writes the bytes
+        // actually read (clamped to what remains in the file) as a
+        // length-prefixed buffer and returns the allocation pointer, or
+        // `-1` on failure.
+        linker.func_wrap("env", "file_read_range", |mut caller: Caller<'_, HostContext>, path_ptr: i32, path_len: i32, offset: i64, length: i32| -> i32 {
+            let view = caller.data().mem;
+            let path = match view.read_str(&mut caller, path_ptr, path_len) {
+                Ok(path) => path.to_string(),
+                Err(_) => return -1,
+            };
+            if offset < 0 || length < 0 {
+                return -1;
+            }
+            match FileIO::read_file_range(&path, offset as u64, length as u64) {
+                Ok(bytes) => write_len_prefixed_bytes(&mut caller, &bytes),
+                Err(_) => -1,
+            }
+        })
+        .map_err(|e| CompilerError::runtime_error(
+            format!("Failed to create file_read_range function: {}", e),
+            None, None
+        ))?;
+
+        // The size of the file at `path` in bytes, or `-1` on failure —
+        // paired with `file_read_range` so callers can page through a
+        // file without reading it whole first.
+        linker.func_wrap("env", "file_size", |mut caller: Caller<'_, HostContext>, path_ptr: i32, path_len: i32| -> i64 {
+            let view = caller.data().mem;
+            let path = match view.read_str(&mut caller, path_ptr, path_len) {
+                Ok(path) => path.to_string(),
+                Err(_) => return -1,
+            };
+            FileIO::file_size(&path).map(|size| size as i64).unwrap_or(-1)
+        })
+        .map_err(|e| CompilerError::runtime_error(
+            format!("Failed to create file_size function: {}", e),
+            None, None
+        ))?;
+
+        // List the directory at `path`, writing each entry's name, type
+        // (file vs directory), size, and mtime as a length-prefixed
+        // record (see `write_dir_listing`) and returning the allocation
+        // pointer, or `-1` on failure.
+        linker.func_wrap("env", "file_list_dir", |mut caller: Caller<'_, HostContext>, path_ptr: i32, path_len: i32| -> i32 {
+            let view = caller.data().mem;
+            let path = match view.read_str(&mut caller, path_ptr, path_len) {
+                Ok(path) => path.to_string(),
+                Err(_) => return -1,
+            };
+            match FileIO::list_directory_detailed(&path) {
+                Ok(entries) => write_dir_listing(&mut caller, &entries),
+                Err(_) => -1,
+            }
+        })
+        .map_err(|e| CompilerError::runtime_error(
+            format!("Failed to create file_list_dir function: {}", e),
+            None, None
+        ))?;
+
+        // Write `content` to `path`, `.await`ing `tokio::fs::write` rather
+        // than blocking the executor thread (see `file_read` above).
+        // Returns `0` on success, `-1` on failure.
+        linker.func_wrap_async("env", "file_write", |mut caller: Caller<'_, HostContext>, (path_ptr, path_len, content_ptr, content_len): (i32, i32, i32, i32)| {
+            Box::new(async move {
+                let view = caller.data().mem;
+                let path = match view.read_str(&mut caller, path_ptr, path_len) {
+                    Ok(path) => path.to_string(),
+                    Err(_) => return Ok(-1),
+                };
+                let content = match view.read_str(&mut caller, content_ptr, content_len) {
+                    Ok(content) => content.to_string(),
+                    Err(_) => return Ok(-1),
+                };
+                match tokio::fs::write(&path, content).await {
+                    Ok(()) => Ok(0),
+                    Err(_) => Ok(-1),
                 }
-            }
-            
-            println!("❌ [FILE READ] Invalid path parameters");
-            -1 // Error indicator
+            })
         })
         .map_err(|e| CompilerError::runtime_error(
-            format!("Failed to create file_read function: {}", e),
+            format!("Failed to create file_write function: {}", e),
             None, None
         ))?;
-        
-        linker.func_wrap("env", "file_write", |mut caller: Caller<'_, ()>, path_ptr: i32, path_len: i32, content_ptr: i32, content_len: i32| -> i32 {
-            if let Some(memory) = caller.get_export("memory") {
-                if let Some(memory) = memory.into_memory() {
-                    let data = memory.data(&caller);
-                    
-                    if path_ptr >= 0 && path_len >= 0 && content_ptr >= 0 && content_len >= 0 {
-                        let path_start = path_ptr as usize;
-                        let path_length = path_len as usize;
-                        let content_start = content_ptr as usize;
-                        let content_length = content_len as usize;
-                        
-                        if path_start + path_length <= data.len() && content_start + content_length <= data.len() {
-                            if let (Ok(path), Ok(content)) = (
-                                std::str::from_utf8(&data[path_start..path_start + path_length]),
-                                std::str::from_utf8(&data[content_start..content_start + content_length])
-                            ) {
-                                // Make real file write
-                                match FileIO::write_file(path, content) {
-                                    Ok(()) => {
-                                        return 0; // Success
-                                    }
-                                    Err(_) => {
-                                        return -1; // Error
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
+
+        // Write raw bytes rather than requiring valid UTF-8, so binary
+        // assets (images, archives, etc.) that would be rejected by
+        // `file_write`'s `from_utf8` check can still be persisted.
+        linker.func_wrap("env", "file_write_binary", |mut caller: Caller<'_, HostContext>, path_ptr: i32, path_len: i32, data_ptr: i32, data_len: i32| -> i32 {
+            let view = caller.data().mem;
+            let path = match view.read_str(&mut caller, path_ptr, path_len) {
+                Ok(path) => path.to_string(),
+                Err(_) => return -1,
+            };
+            let data: Vec<u8> = match view.read_bytes(&mut caller, data_ptr, data_len) {
+                Ok(data) => data,
+                Err(_) => return -1,
+            };
+            match FileIO::write_file_binary(&path, &data) {
+                Ok(()) => 0,
+                Err(_) => -1,
             }
-            
-            println!("❌ [FILE WRITE] Invalid parameters");
-            -1 // Error indicator
         })
         .map_err(|e| CompilerError::runtime_error(
-            format!("Failed to create file_write function: {}", e),
+            format!("Failed to create file_write_binary function: {}", e),
+            None, None
+        ))?;
+
+        // Map `path`'s extension to a MIME type (see
+        // `file_io::mime_type_for`), writing the result as a
+        // length-prefixed string and returning its pointer.
+        linker.func_wrap("env", "file_mime_type", |mut caller: Caller<'_, HostContext>, path_ptr: i32, path_len: i32| -> i32 {
+            let view = caller.data().mem;
+            let path = match view.read_str(&mut caller, path_ptr, path_len) {
+                Ok(path) => path.to_string(),
+                Err(_) => return -1,
+            };
+            let mime_type = file_io::mime_type_for(&path);
+            write_len_prefixed_string(&mut caller, mime_type)
+        })
+        .map_err(|e| CompilerError::runtime_error(
+            format!("Failed to create file_mime_type function: {}", e),
             None, None
         ))?;
 
-        linker.func_wrap("env", "file_exists", |mut caller: Caller<'_, ()>, path_ptr: i32, path_len: i32| -> i32 {
+        linker.func_wrap("env", "file_exists", |mut caller: Caller<'_, HostContext>, path_ptr: i32, path_len: i32| -> i32 {
             if let Some(memory) = caller.get_export("memory") {
                 if let Some(memory) = memory.into_memory() {
                     let data = memory.data(&caller);
@@ -948,7 +2009,7 @@ impl CleanRuntime {
             None, None
         ))?;
 
-        linker.func_wrap("env", "file_delete", |mut caller: Caller<'_, ()>, path_ptr: i32, path_len: i32| -> i32 {
+        linker.func_wrap("env", "file_delete", |mut caller: Caller<'_, HostContext>, path_ptr: i32, path_len: i32| -> i32 {
             if let Some(memory) = caller.get_export("memory") {
                 if let Some(memory) = memory.into_memory() {
                     let data = memory.data(&caller);
@@ -982,7 +2043,7 @@ impl CleanRuntime {
             None, None
         ))?;
 
-        linker.func_wrap("env", "file_append", |mut caller: Caller<'_, ()>, path_ptr: i32, path_len: i32, content_ptr: i32, content_len: i32| -> i32 {
+        linker.func_wrap("env", "file_append", |mut caller: Caller<'_, HostContext>, path_ptr: i32, path_len: i32, content_ptr: i32, content_len: i32| -> i32 {
             if let Some(memory) = caller.get_export("memory") {
                 if let Some(memory) = memory.into_memory() {
                     let data = memory.data(&caller);
@@ -1021,43 +2082,284 @@ impl CleanRuntime {
             None, None
         ))?;
 
-        // Add file_size function
-        linker.func_wrap("env", "file_size", |mut caller: Caller<'_, ()>, path_ptr: i32, path_len: i32| -> i64 {
-            if let Some(memory) = caller.get_export("memory") {
-                if let Some(memory) = memory.into_memory() {
-                    let data = memory.data(&caller);
-                    
-                    if path_ptr >= 0 && path_len >= 0 {
-                        let start = path_ptr as usize;
-                        let len = path_len as usize;
-                        
-                        if start + len <= data.len() {
-                            if let Ok(path) = std::str::from_utf8(&data[start..start + len]) {
-                                // Get file size
-                                match FileIO::file_size(path) {
-                                    Ok(size) => {
-                                        return size as i64; // Return file size
-                                    }
-                                    Err(_) => {
-                                        return -1; // Error
-                                    }
-                                }
-                            }
-                        }
+        // Handle-based file access: unlike `file_read`/`file_write`/
+        // `file_append` above, which re-open and close the file on every
+        // call, `file_open` registers the file in `file_handles` and
+        // returns a small handle that `file_fd_read`/`file_fd_write`/
+        // `file_seek`/`file_tell`/`file_pread`/`file_pwrite`/`file_close`
+        // operate on, so a large file can be streamed through a cursor
+        // without a syscall per chunk to find it again. Named
+        // `file_fd_read`/`file_fd_write` rather than the plain
+        // `file_read`/`file_write` names, since those are already taken by
+        // the path-based functions above.
+        let file_handles_open = file_handles.clone();
+        linker.func_wrap("env", "file_open", move |mut caller: Caller<'_, HostContext>, path_ptr: i32, path_len: i32, mode: i32| -> i32 {
+            let view = caller.data().mem;
+            let path = match view.read_str(&mut caller, path_ptr, path_len) {
+                Ok(path) => path.to_string(),
+                Err(_) => return -1,
+            };
+            file_handles_open.open(&path, mode)
+        })
+        .map_err(|e| CompilerError::runtime_error(
+            format!("Failed to create file_open function: {}", e),
+            None, None
+        ))?;
+
+        // Read up to `buf_len` bytes from `handle` at its current cursor
+        // into guest memory at `buf_ptr`, advancing the cursor by however
+        // many bytes were actually read. Returns the number of bytes read
+        // (`0` at EOF), or `-1` on an invalid handle, an out-of-bounds
+        // buffer, or an I/O error.
+        let file_handles_read = file_handles.clone();
+        linker.func_wrap("env", "file_fd_read", move |mut caller: Caller<'_, HostContext>, handle: i32, buf_ptr: i32, buf_len: i32| -> i32 {
+            use std::io::Read;
+            if buf_len < 0 {
+                return -1;
+            }
+            let mut buffer = vec![0u8; buf_len as usize];
+            let read = match file_handles_read.with_file(handle, |file| file.read(&mut buffer)) {
+                Some(Ok(n)) => n,
+                _ => return -1,
+            };
+            let view = caller.data().mem;
+            match view.write_bytes(&mut caller, buf_ptr, &buffer[..read]) {
+                Ok(()) => read as i32,
+                Err(_) => -1,
+            }
+        })
+        .map_err(|e| CompilerError::runtime_error(
+            format!("Failed to create file_fd_read function: {}", e),
+            None, None
+        ))?;
+
+        // Write `buf_len` bytes from guest memory at `buf_ptr` to `handle`
+        // at its current cursor, advancing the cursor by however many
+        // bytes were actually written. Returns the number of bytes
+        // written, or `-1` on an invalid handle, an out-of-bounds buffer,
+        // or an I/O error.
+        let file_handles_write = file_handles.clone();
+        linker.func_wrap("env", "file_fd_write", move |mut caller: Caller<'_, HostContext>, handle: i32, buf_ptr: i32, buf_len: i32| -> i32 {
+            use std::io::Write;
+            let view = caller.data().mem;
+            let buffer: Vec<u8> = match view.read_bytes(&mut caller, buf_ptr, buf_len) {
+                Ok(buffer) => buffer,
+                Err(_) => return -1,
+            };
+            match file_handles_write.with_file(handle, |file| file.write(&buffer)) {
+                Some(Ok(n)) => n as i32,
+                _ => -1,
+            }
+        })
+        .map_err(|e| CompilerError::runtime_error(
+            format!("Failed to create file_fd_write function: {}", e),
+            None, None
+        ))?;
+
+        // Move `handle`'s cursor to `offset` interpreted relative to
+        // `whence` (`0` = start, `1` = current position, `2` = end,
+        // matching the usual POSIX `lseek` convention), returning the new
+        // absolute position, or `-1` on an invalid handle or `whence`.
+        let file_handles_seek = file_handles.clone();
+        linker.func_wrap("env", "file_seek", move |_caller: Caller<'_, HostContext>, handle: i32, offset: i64, whence: i32| -> i64 {
+            use std::io::{Seek, SeekFrom};
+            let seek_from = match whence {
+                0 => SeekFrom::Start(offset as u64),
+                1 => SeekFrom::Current(offset),
+                2 => SeekFrom::End(offset),
+                _ => return -1,
+            };
+            match file_handles_seek.with_file(handle, |file| file.seek(seek_from)) {
+                Some(Ok(pos)) => pos as i64,
+                _ => -1,
+            }
+        })
+        .map_err(|e| CompilerError::runtime_error(
+            format!("Failed to create file_seek function: {}", e),
+            None, None
+        ))?;
+
+        // The current cursor position of `handle`, or `-1` on an invalid
+        // handle.
+        let file_handles_tell = file_handles.clone();
+        linker.func_wrap("env", "file_tell", move |_caller: Caller<'_, HostContext>, handle: i32| -> i64 {
+            use std::io::{Seek, SeekFrom};
+            match file_handles_tell.with_file(handle, |file| file.seek(SeekFrom::Current(0))) {
+                Some(Ok(pos)) => pos as i64,
+                _ => -1,
+            }
+        })
+        .map_err(|e| CompilerError::runtime_error(
+            format!("Failed to create file_tell function: {}", e),
+            None, None
+        ))?;
+
+        // Positional read: like `file_fd_read`, but reads from `offset`
+        // without moving (or being affected by) `handle`'s cursor, via
+        // `FileExt::read_at`. Returns the number of bytes read, or `-1` on
+        // an invalid handle, a negative offset, an out-of-bounds buffer,
+        // or an I/O error.
+        let file_handles_pread = file_handles.clone();
+        linker.func_wrap("env", "file_pread", move |mut caller: Caller<'_, HostContext>, handle: i32, buf_ptr: i32, buf_len: i32, offset: i64| -> i32 {
+            use std::os::unix::fs::FileExt;
+            if buf_len < 0 || offset < 0 {
+                return -1;
+            }
+            let mut buffer = vec![0u8; buf_len as usize];
+            let read = match file_handles_pread.with_file(handle, |file| file.read_at(&mut buffer, offset as u64)) {
+                Some(Ok(n)) => n,
+                _ => return -1,
+            };
+            let view = caller.data().mem;
+            match view.write_bytes(&mut caller, buf_ptr, &buffer[..read]) {
+                Ok(()) => read as i32,
+                Err(_) => -1,
+            }
+        })
+        .map_err(|e| CompilerError::runtime_error(
+            format!("Failed to create file_pread function: {}", e),
+            None, None
+        ))?;
+
+        // Positional write: like `file_fd_write`, but writes at `offset`
+        // without moving (or being affected by) `handle`'s cursor, via
+        // `FileExt::write_at`. Returns the number of bytes written, or
+        // `-1` on an invalid handle, a negative offset, an out-of-bounds
+        // buffer, or an I/O error.
+        let file_handles_pwrite = file_handles.clone();
+        linker.func_wrap("env", "file_pwrite", move |mut caller: Caller<'_, HostContext>, handle: i32, buf_ptr: i32, buf_len: i32, offset: i64| -> i32 {
+            use std::os::unix::fs::FileExt;
+            if offset < 0 {
+                return -1;
+            }
+            let view = caller.data().mem;
+            let buffer: Vec<u8> = match view.read_bytes(&mut caller, buf_ptr, buf_len) {
+                Ok(buffer) => buffer,
+                Err(_) => return -1,
+            };
+            match file_handles_pwrite.with_file(handle, |file| file.write_at(&buffer, offset as u64)) {
+                Some(Ok(n)) => n as i32,
+                _ => -1,
+            }
+        })
+        .map_err(|e| CompilerError::runtime_error(
+            format!("Failed to create file_pwrite function: {}", e),
+            None, None
+        ))?;
+
+        // Read one line from `handle`'s lazily-created line reader into
+        // guest memory at `buf_ptr`, bounded by `buf_len`. Returns the
+        // number of bytes written (the terminating `\n` included, if the
+        // file had one), `file_handles::LINE_EOF` if there's no more
+        // data, or `file_handles::LINE_TOO_LONG` if the next line doesn't
+        // fit in `buf_len` bytes — call again with a bigger buffer to
+        // retrieve it, rather than losing the line. Also returns `-1` on
+        // an invalid handle or an out-of-bounds buffer.
+        let file_handles_read_line = file_handles.clone();
+        linker.func_wrap("env", "file_read_line", move |mut caller: Caller<'_, HostContext>, handle: i32, buf_ptr: i32, buf_len: i32| -> i32 {
+            if buf_len < 0 {
+                return -1;
+            }
+            match file_handles_read_line.read_line(handle, buf_len as usize) {
+                Some(Ok(line)) => {
+                    let view = caller.data().mem;
+                    match view.write_bytes(&mut caller, buf_ptr, &line) {
+                        Ok(()) => line.len() as i32,
+                        Err(_) => -1,
                     }
                 }
+                Some(Err(sentinel)) => sentinel,
+                None => -1,
             }
-            
-            println!("❌ [FILE SIZE] Invalid path parameters");
-            -1 // Error indicator
         })
         .map_err(|e| CompilerError::runtime_error(
-            format!("Failed to create file_size function: {}", e),
+            format!("Failed to create file_read_line function: {}", e),
+            None, None
+        ))?;
+
+        // Drop `handle`'s line reader and rewind the file to the start,
+        // so the next `file_read_line` re-iterates from the first line.
+        // Returns `0` on success, `-1` on an invalid handle or a seek
+        // error.
+        let file_handles_reader_reset = file_handles.clone();
+        linker.func_wrap("env", "file_reader_reset", move |_caller: Caller<'_, HostContext>, handle: i32| -> i32 {
+            file_handles_reader_reset.reset_reader(handle)
+        })
+        .map_err(|e| CompilerError::runtime_error(
+            format!("Failed to create file_reader_reset function: {}", e),
+            None, None
+        ))?;
+
+        // Close `handle`, releasing its entry in `file_handles`. Returns
+        // `0` on success, `-1` if `handle` was already closed or never
+        // opened.
+        let file_handles_close = file_handles.clone();
+        linker.func_wrap("env", "file_close", move |_caller: Caller<'_, HostContext>, handle: i32| -> i32 {
+            file_handles_close.close(handle)
+        })
+        .map_err(|e| CompilerError::runtime_error(
+            format!("Failed to create file_close function: {}", e),
+            None, None
+        ))?;
+
+        // Create an empty scratch file named `prefix`-something under the
+        // runtime's configured temp dir (see `CleanRuntime::with_temp_dir`)
+        // and register it for cleanup in `wait_for_background_tasks`.
+        // Returns its handle — pass it to `temp_file_path` to read back the
+        // generated path — or `-1` if the file couldn't be created.
+        let temp_files_create_file = self.temp_files.clone();
+        linker.func_wrap("env", "temp_file_create", move |mut caller: Caller<'_, HostContext>, prefix_ptr: i32, prefix_len: i32| -> i32 {
+            let view = caller.data().mem;
+            let prefix = match view.read_str(&mut caller, prefix_ptr, prefix_len) {
+                Ok(prefix) => prefix.to_string(),
+                Err(_) => return -1,
+            };
+            temp_files_create_file.create_file(&prefix)
+        })
+        .map_err(|e| CompilerError::runtime_error(
+            format!("Failed to create temp_file_create function: {}", e),
+            None, None
+        ))?;
+
+        // Like `temp_file_create`, but creates a scratch directory instead
+        // of a file.
+        let temp_files_create_dir = self.temp_files.clone();
+        linker.func_wrap("env", "temp_dir_create", move |mut caller: Caller<'_, HostContext>, prefix_ptr: i32, prefix_len: i32| -> i32 {
+            let view = caller.data().mem;
+            let prefix = match view.read_str(&mut caller, prefix_ptr, prefix_len) {
+                Ok(prefix) => prefix.to_string(),
+                Err(_) => return -1,
+            };
+            temp_files_create_dir.create_dir(&prefix)
+        })
+        .map_err(|e| CompilerError::runtime_error(
+            format!("Failed to create temp_dir_create function: {}", e),
+            None, None
+        ))?;
+
+        // Read back the path generated for `handle` by `temp_file_create`/
+        // `temp_dir_create` as a length-prefixed string, returning its
+        // allocation pointer, or `-1` if `handle` is unknown or the path
+        // isn't valid UTF-8.
+        let temp_files_path = self.temp_files.clone();
+        linker.func_wrap("env", "temp_file_path", move |mut caller: Caller<'_, HostContext>, handle: i32| -> i32 {
+            let path = match temp_files_path.path(handle) {
+                Some(path) => path,
+                None => return -1,
+            };
+            let path = match path.to_str() {
+                Some(path) => path.to_string(),
+                None => return -1,
+            };
+            write_len_prefixed_string(&mut caller, &path)
+        })
+        .map_err(|e| CompilerError::runtime_error(
+            format!("Failed to create temp_file_path function: {}", e),
             None, None
         ))?;
 
         // Add create_directory function
-        linker.func_wrap("env", "create_directory", |mut caller: Caller<'_, ()>, path_ptr: i32, path_len: i32| -> i32 {
+        linker.func_wrap("env", "create_directory", |mut caller: Caller<'_, HostContext>, path_ptr: i32, path_len: i32| -> i32 {
             if let Some(memory) = caller.get_export("memory") {
                 if let Some(memory) = memory.into_memory() {
                     let data = memory.data(&caller);
@@ -1091,42 +2393,42 @@ impl CleanRuntime {
             None, None
         ))?;
 
-        // Add list_directory function (returns number of files, files stored in memory)
-        linker.func_wrap("env", "list_directory", |mut caller: Caller<'_, ()>, path_ptr: i32, path_len: i32, result_ptr: i32| -> i32 {
-            if let Some(memory) = caller.get_export("memory") {
-                if let Some(memory) = memory.into_memory() {
-                    let data = memory.data(&caller);
-                    
-                    if path_ptr >= 0 && path_len >= 0 && result_ptr >= 0 {
-                        let start = path_ptr as usize;
-                        let len = path_len as usize;
-                        
-                        if start + len <= data.len() {
-                            if let Ok(path) = std::str::from_utf8(&data[start..start + len]) {
-                                // List directory contents
-                                match FileIO::list_directory(path) {
-                                    Ok(files) => {
-                                        // For now, just return the count of files
-                                        // TODO: In a full implementation, we'd serialize the file list
-                                        // into WebAssembly memory at result_ptr
-                                        println!("📁 [LIST DIRECTORY] Found {} files in {}", files.len(), path);
-                                        for (i, file) in files.iter().enumerate() {
-                                            println!("  {}: {}", i, file);
-                                        }
-                                        return files.len() as i32; // Return count
-                                    }
-                                    Err(_) => {
-                                        return -1; // Error
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
+        // The number of entries `list_directory` would serialize for
+        // `path`, or `-1` on failure — lets Clean programs size a buffer
+        // before calling `list_directory` instead of guessing.
+        linker.func_wrap("env", "dir_entry_count", |mut caller: Caller<'_, HostContext>, path_ptr: i32, path_len: i32| -> i32 {
+            let view = caller.data().mem;
+            let path = match view.read_str(&mut caller, path_ptr, path_len) {
+                Ok(path) => path.to_string(),
+                Err(_) => return -1,
+            };
+            FileIO::list_directory(&path).map(|entries| entries.len() as i32).unwrap_or(-1)
+        })
+        .map_err(|e| CompilerError::runtime_error(
+            format!("Failed to create dir_entry_count function: {}", e),
+            None, None
+        ))?;
+
+        // List the directory at `path`, writing a packed record at
+        // `result_ptr`: a leading `u32` entry count, then for each entry a
+        // `u32` name length, the UTF-8 name bytes, a `u64` size, and a `u8`
+        // flag byte (bit 0 = is_directory). Returns the entry count, or
+        // `-1` if the listing couldn't fit in `result_ptr..data.len()` (use
+        // `dir_entry_count` to size the buffer first) or the path couldn't
+        // be read.
+        linker.func_wrap("env", "list_directory", |mut caller: Caller<'_, HostContext>, path_ptr: i32, path_len: i32, result_ptr: i32| -> i32 {
+            let view = caller.data().mem;
+            let path = match view.read_str(&mut caller, path_ptr, path_len) {
+                Ok(path) => path.to_string(),
+                Err(_) => return -1,
+            };
+            if result_ptr < 0 {
+                return -1;
+            }
+            match FileIO::list_directory(&path) {
+                Ok(entries) => write_dir_entries_at(&mut caller, result_ptr as usize, &entries),
+                Err(_) => -1,
             }
-            
-            println!("❌ [LIST DIRECTORY] Invalid parameters");
-            -1 // Error indicator
         })
         .map_err(|e| CompilerError::runtime_error(
             format!("Failed to create list_directory function: {}", e),
@@ -1134,7 +2436,7 @@ impl CleanRuntime {
         ))?;
         
         // Mathematical functions
-        linker.func_wrap("env", "pow", |_caller: Caller<'_, ()>, base: f64, exponent: f64| -> f64 {
+        linker.func_wrap("env", "pow", |_caller: Caller<'_, HostContext>, base: f64, exponent: f64| -> f64 {
             base.powf(exponent)
         })
         .map_err(|e| CompilerError::runtime_error(
@@ -1142,7 +2444,7 @@ impl CleanRuntime {
             None, None
         ))?;
         
-        linker.func_wrap("env", "sin", |_caller: Caller<'_, ()>, x: f64| -> f64 {
+        linker.func_wrap("env", "sin", |_caller: Caller<'_, HostContext>, x: f64| -> f64 {
             x.sin()
         })
         .map_err(|e| CompilerError::runtime_error(
@@ -1150,7 +2452,7 @@ impl CleanRuntime {
             None, None
         ))?;
         
-        linker.func_wrap("env", "cos", |_caller: Caller<'_, ()>, x: f64| -> f64 {
+        linker.func_wrap("env", "cos", |_caller: Caller<'_, HostContext>, x: f64| -> f64 {
             x.cos()
         })
         .map_err(|e| CompilerError::runtime_error(
@@ -1158,7 +2460,7 @@ impl CleanRuntime {
             None, None
         ))?;
         
-        linker.func_wrap("env", "tan", |_caller: Caller<'_, ()>, x: f64| -> f64 {
+        linker.func_wrap("env", "tan", |_caller: Caller<'_, HostContext>, x: f64| -> f64 {
             x.tan()
         })
         .map_err(|e| CompilerError::runtime_error(
@@ -1166,7 +2468,7 @@ impl CleanRuntime {
             None, None
         ))?;
         
-        linker.func_wrap("env", "ln", |_caller: Caller<'_, ()>, x: f64| -> f64 {
+        linker.func_wrap("env", "ln", |_caller: Caller<'_, HostContext>, x: f64| -> f64 {
             x.ln()
         })
         .map_err(|e| CompilerError::runtime_error(
@@ -1174,7 +2476,7 @@ impl CleanRuntime {
             None, None
         ))?;
         
-        linker.func_wrap("env", "log10", |_caller: Caller<'_, ()>, x: f64| -> f64 {
+        linker.func_wrap("env", "log10", |_caller: Caller<'_, HostContext>, x: f64| -> f64 {
             x.log10()
         })
         .map_err(|e| CompilerError::runtime_error(
@@ -1182,7 +2484,7 @@ impl CleanRuntime {
             None, None
         ))?;
         
-        linker.func_wrap("env", "log2", |_caller: Caller<'_, ()>, x: f64| -> f64 {
+        linker.func_wrap("env", "log2", |_caller: Caller<'_, HostContext>, x: f64| -> f64 {
             x.log2()
         })
         .map_err(|e| CompilerError::runtime_error(
@@ -1190,7 +2492,7 @@ impl CleanRuntime {
             None, None
         ))?;
         
-        linker.func_wrap("env", "exp", |_caller: Caller<'_, ()>, x: f64| -> f64 {
+        linker.func_wrap("env", "exp", |_caller: Caller<'_, HostContext>, x: f64| -> f64 {
             x.exp()
         })
         .map_err(|e| CompilerError::runtime_error(
@@ -1198,7 +2500,7 @@ impl CleanRuntime {
             None, None
         ))?;
         
-        linker.func_wrap("env", "exp2", |_caller: Caller<'_, ()>, x: f64| -> f64 {
+        linker.func_wrap("env", "exp2", |_caller: Caller<'_, HostContext>, x: f64| -> f64 {
             x.exp2()
         })
         .map_err(|e| CompilerError::runtime_error(
@@ -1206,7 +2508,7 @@ impl CleanRuntime {
             None, None
         ))?;
         
-        linker.func_wrap("env", "sqrt", |_caller: Caller<'_, ()>, x: f64| -> f64 {
+        linker.func_wrap("env", "sqrt", |_caller: Caller<'_, HostContext>, x: f64| -> f64 {
             x.sqrt()
         })
         .map_err(|e| CompilerError::runtime_error(
@@ -1214,7 +2516,7 @@ impl CleanRuntime {
             None, None
         ))?;
         
-        linker.func_wrap("env", "sinh", |_caller: Caller<'_, ()>, x: f64| -> f64 {
+        linker.func_wrap("env", "sinh", |_caller: Caller<'_, HostContext>, x: f64| -> f64 {
             x.sinh()
         })
         .map_err(|e| CompilerError::runtime_error(
@@ -1222,7 +2524,7 @@ impl CleanRuntime {
             None, None
         ))?;
         
-        linker.func_wrap("env", "cosh", |_caller: Caller<'_, ()>, x: f64| -> f64 {
+        linker.func_wrap("env", "cosh", |_caller: Caller<'_, HostContext>, x: f64| -> f64 {
             x.cosh()
         })
         .map_err(|e| CompilerError::runtime_error(
@@ -1230,7 +2532,7 @@ impl CleanRuntime {
             None, None
         ))?;
         
-        linker.func_wrap("env", "tanh", |_caller: Caller<'_, ()>, x: f64| -> f64 {
+        linker.func_wrap("env", "tanh", |_caller: Caller<'_, HostContext>, x: f64| -> f64 {
             x.tanh()
         })
         .map_err(|e| CompilerError::runtime_error(
@@ -1238,7 +2540,7 @@ impl CleanRuntime {
             None, None
         ))?;
         
-        linker.func_wrap("env", "asin", |_caller: Caller<'_, ()>, x: f64| -> f64 {
+        linker.func_wrap("env", "asin", |_caller: Caller<'_, HostContext>, x: f64| -> f64 {
             x.asin()
         })
         .map_err(|e| CompilerError::runtime_error(
@@ -1246,7 +2548,7 @@ impl CleanRuntime {
             None, None
         ))?;
         
-        linker.func_wrap("env", "acos", |_caller: Caller<'_, ()>, x: f64| -> f64 {
+        linker.func_wrap("env", "acos", |_caller: Caller<'_, HostContext>, x: f64| -> f64 {
             x.acos()
         })
         .map_err(|e| CompilerError::runtime_error(
@@ -1254,7 +2556,7 @@ impl CleanRuntime {
             None, None
         ))?;
         
-        linker.func_wrap("env", "atan", |_caller: Caller<'_, ()>, x: f64| -> f64 {
+        linker.func_wrap("env", "atan", |_caller: Caller<'_, HostContext>, x: f64| -> f64 {
             x.atan()
         })
         .map_err(|e| CompilerError::runtime_error(
@@ -1262,7 +2564,7 @@ impl CleanRuntime {
             None, None
         ))?;
         
-        linker.func_wrap("env", "pi", |_caller: Caller<'_, ()>| -> f64 {
+        linker.func_wrap("env", "pi", |_caller: Caller<'_, HostContext>| -> f64 {
             std::f64::consts::PI
         })
         .map_err(|e| CompilerError::runtime_error(
@@ -1270,7 +2572,7 @@ impl CleanRuntime {
             None, None
         ))?;
         
-        linker.func_wrap("env", "e", |_caller: Caller<'_, ()>| -> f64 {
+        linker.func_wrap("env", "e", |_caller: Caller<'_, HostContext>| -> f64 {
             std::f64::consts::E
         })
         .map_err(|e| CompilerError::runtime_error(
@@ -1278,7 +2580,7 @@ impl CleanRuntime {
             None, None
         ))?;
         
-        linker.func_wrap("env", "abs", |_caller: Caller<'_, ()>, x: f64| -> f64 {
+        linker.func_wrap("env", "abs", |_caller: Caller<'_, HostContext>, x: f64| -> f64 {
             x.abs()
         })
         .map_err(|e| CompilerError::runtime_error(
@@ -1286,81 +2588,40 @@ impl CleanRuntime {
             None, None
         ))?;
         
-        // Console input functions
-        linker.func_wrap("env", "input", |mut caller: Caller<'_, ()>, prompt_ptr: i32, prompt_len: i32| -> i32 {
-            // Extract prompt from memory
-            let prompt = if let Some(memory) = caller.get_export("memory") {
-                if let Some(memory) = memory.into_memory() {
-                    let data = memory.data(&caller);
-                    if prompt_ptr >= 0 && prompt_len >= 0 {
-                        let start = prompt_ptr as usize;
-                        let len = prompt_len as usize;
-                        if start + len <= data.len() {
-                            std::str::from_utf8(&data[start..start + len]).unwrap_or("")
-                        } else {
-                            ""
-                        }
-                    } else {
-                        ""
-                    }
-                } else {
-                    ""
-                }
-            } else {
-                ""
-            };
-            
-            // Display prompt and get user input
-            print!("{}", prompt);
-            use std::io::{self, Write};
-            io::stdout().flush().unwrap();
-            
-            let mut input = String::new();
-            match io::stdin().read_line(&mut input) {
-                Ok(_) => {
-                    input = input.trim().to_string();
-                    
-                    // Store the input string in memory
-                    if let Some(memory) = caller.get_export("memory") {
-                        if let Some(memory) = memory.into_memory() {
-                            let mut data = memory.data_mut(&mut caller);
-                            
-                            let string_bytes = input.as_bytes();
-                            let total_size = 4 + string_bytes.len();
-                            
-                            // Find a place to store the string
-                            let mut offset = 1024;
-                            while offset + total_size < data.len() {
-                                let is_free = data[offset..offset + total_size].iter().all(|&b| b == 0);
-                                if is_free {
-                                    break;
-                                }
-                                offset += 32;
-                            }
-                            
-                            if offset + total_size < data.len() {
-                                // Store length
-                                data[offset..offset + 4].copy_from_slice(&(string_bytes.len() as u32).to_le_bytes());
-                                // Store string data
-                                data[offset + 4..offset + 4 + string_bytes.len()].copy_from_slice(string_bytes);
-                                return offset as i32;
-                            }
-                        }
+        // Console input functions.
+        //
+        // `input` `.await`s `tokio::io::stdin()` rather than blocking the
+        // executor thread on the synchronous `std::io::stdin().read_line`
+        // the way `input_integer`/`input_float`/`input_yesno` below still
+        // do, so a Clean program waiting on a human at a terminal no
+        // longer ties up a whole worker thread (and stays interruptible
+        // via epoch deadlines in the meantime).
+        linker.func_wrap_async("env", "input", |mut caller: Caller<'_, HostContext>, (prompt_ptr, prompt_len): (i32, i32)| {
+            Box::new(async move {
+                let view = caller.data().mem;
+                let prompt = view.read_str(&mut caller, prompt_ptr, prompt_len).unwrap_or("").to_string();
+
+                use std::io::Write;
+                use tokio::io::AsyncBufReadExt;
+                print!("{}", prompt);
+                std::io::stdout().flush().unwrap();
+
+                let mut line = String::new();
+                match tokio::io::BufReader::new(tokio::io::stdin()).read_line(&mut line).await {
+                    Ok(_) => Ok(write_len_prefixed_string(&mut caller, line.trim())),
+                    Err(e) => {
+                        println!("❌ [INPUT] Error reading input: {}", e);
+                        Ok(0) // Return null pointer on failure
                     }
                 }
-                Err(e) => {
-                    println!("❌ [INPUT] Error reading input: {}", e);
-                }
-            }
-            
-            0 // Return null pointer on failure
+            })
         })
         .map_err(|e| CompilerError::runtime_error(
             format!("Failed to create input function: {}", e),
             None, None
         ))?;
         
-        linker.func_wrap("env", "input_integer", |mut caller: Caller<'_, ()>, prompt_ptr: i32, prompt_len: i32| -> i32 {
+        linker.func_wrap("env", "input_integer", |mut caller: Caller<'_, HostContext>, prompt_ptr: i32, prompt_len: i32| -> i32 {
             // Extract prompt from memory
             let prompt = if let Some(memory) = caller.get_export("memory") {
                 if let Some(memory) = memory.into_memory() {
@@ -1413,7 +2674,7 @@ impl CleanRuntime {
             None, None
         ))?;
         
-        linker.func_wrap("env", "input_float", |mut caller: Caller<'_, ()>, prompt_ptr: i32, prompt_len: i32| -> f64 {
+        linker.func_wrap("env", "input_float", |mut caller: Caller<'_, HostContext>, prompt_ptr: i32, prompt_len: i32| -> f64 {
             // Extract prompt from memory
             let prompt = if let Some(memory) = caller.get_export("memory") {
                 if let Some(memory) = memory.into_memory() {
@@ -1466,7 +2727,7 @@ impl CleanRuntime {
             None, None
         ))?;
         
-        linker.func_wrap("env", "input_yesno", |mut caller: Caller<'_, ()>, prompt_ptr: i32, prompt_len: i32| -> i32 {
+        linker.func_wrap("env", "input_yesno", |mut caller: Caller<'_, HostContext>, prompt_ptr: i32, prompt_len: i32| -> i32 {
             // Extract prompt from memory
             let prompt = if let Some(memory) = caller.get_export("memory") {
                 if let Some(memory) = memory.into_memory() {
@@ -1549,10 +2810,21 @@ impl CleanRuntime {
         }
         
         if iterations >= MAX_WAIT_ITERATIONS {
+            let mut tasks = self.background_tasks.lock().unwrap();
+            for task in tasks.iter_mut() {
+                if matches!(task.status, TaskStatus::Running) {
+                    task.status = TaskStatus::Failed("timed out".to_string());
+                }
+            }
             println!("⚠️  Timeout waiting for background tasks to complete");
         } else {
             println!("✅ All background tasks completed");
         }
+
+        // Runtime teardown: delete every temp file/directory created via
+        // `temp_file_create`/`temp_dir_create` so a finished program
+        // leaves no litter in the configured temp dir.
+        self.temp_files.cleanup();
     }
 }
 
@@ -1564,17 +2836,18 @@ impl TaskScheduler {
         }
     }
     
-    pub fn create_task(&mut self, name: String) -> u32 {
+    pub fn create_task(&mut self, name: String, channel_handle: u32) -> u32 {
         let task_id = self.next_task_id;
         self.next_task_id += 1;
-        
+
         let task = BackgroundTask {
             id: task_id,
             name,
             started_at: Instant::now(),
             status: TaskStatus::Running,
+            channel_handle: Some(channel_handle),
         };
-        
+
         self.running_tasks.insert(task_id, task);
         task_id
     }
@@ -1592,52 +2865,119 @@ impl TaskScheduler {
     }
 }
 
-impl FutureResolver {
-    pub fn new() -> Self {
-        FutureResolver {
-            futures: HashMap::new(),
-        }
-    }
-    
-    pub fn create_future(&mut self, id: String) {
-        let future = FutureValue {
-            id: id.clone(),
-            value: None,
-            resolved: false,
-            created_at: Instant::now(),
-        };
-        self.futures.insert(id, future);
-    }
-    
-    pub fn resolve_future(&mut self, id: String, value: i32) {
-        if let Some(future) = self.futures.get_mut(&id) {
-            future.value = Some(value);
-            future.resolved = true;
-        }
-    }
-    
-    pub fn get_future_value(&self, id: &str) -> Option<i32> {
-        self.futures.get(id).and_then(|f| if f.resolved { f.value } else { None })
-    }
-    
-    pub fn is_future_resolved(&self, id: &str) -> bool {
-        self.futures.get(id).map(|f| f.resolved).unwrap_or(false)
-    }
-}
-
 /// Convenience function to create and run a Clean Language program with async support
 pub async fn run_clean_program_async(wasm_bytes: &[u8]) -> Result<(), CompilerError> {
     let runtime = CleanRuntime::new()?;
-    runtime.execute_async(wasm_bytes).await
+    runtime.execute_async(wasm_bytes, RuntimeLimits::default()).await.map(|_| ())
+}
+
+/// Run a Clean Language program against an already-running tokio runtime,
+/// for embedders (servers, other async applications) that own their own
+/// `Handle` and can't hand control to a second, nested one. Uses
+/// `tokio::task::block_in_place` to drive `run_clean_program_async` to
+/// completion on the current thread without blocking the runtime's other
+/// worker threads.
+pub fn run_clean_program_on(handle: &tokio::runtime::Handle, wasm_bytes: &[u8]) -> Result<(), CompilerError> {
+    tokio::task::block_in_place(|| handle.block_on(run_clean_program_async(wasm_bytes)))
 }
 
-/// Synchronous wrapper for async execution (for backward compatibility)
+/// Synchronous wrapper for async execution (for backward compatibility).
+/// If called from inside an existing tokio runtime (e.g. the compiler is
+/// embedded in an async host application), delegates to
+/// `run_clean_program_on` instead of spinning up a nested runtime, which
+/// would panic with "Cannot start a runtime from within a runtime".
 pub fn run_clean_program_sync(wasm_bytes: &[u8]) -> Result<(), CompilerError> {
+    if let Ok(handle) = tokio::runtime::Handle::try_current() {
+        return run_clean_program_on(&handle, wasm_bytes);
+    }
+
     let rt = tokio::runtime::Runtime::new()
         .map_err(|e| CompilerError::runtime_error(
             format!("Failed to create async runtime: {}", e),
             None, None
         ))?;
-    
+
     rt.block_on(run_clean_program_async(wasm_bytes))
-} 
\ No newline at end of file
+}
+
+/// Instantiate `wasm` with no host imports and call its exported function
+/// `func` with `args`, coercing each `ast::Value` into the `wasmtime::Val`
+/// its exported signature expects and the (single) result back into an
+/// `ast::Value`. This is the synchronous counterpart to `CleanRuntime`'s
+/// async `execute_*` methods: those run a full Clean Language program end
+/// to end through its host imports, while `run_export` is for golden-testing
+/// a single exported function of a self-contained module — such as the
+/// numeric stdlib functions registered directly onto a `CodeGenerator` in
+/// tests — where validating the generated bytes isn't enough to know they
+/// compute the right answer.
+pub fn run_export(wasm: &[u8], func: &str, args: &[crate::ast::Value]) -> Result<crate::ast::Value, CompilerError> {
+    let engine = Engine::default();
+    let module = Module::new(&engine, wasm)
+        .map_err(|e| CompilerError::runtime_error(format!("Failed to load module: {}", e), None, None))?;
+    let mut store = Store::new(&engine, ());
+    let instance = Linker::new(&engine)
+        .instantiate(&mut store, &module)
+        .map_err(|e| CompilerError::runtime_error(format!("Failed to instantiate module: {}", e), None, None))?;
+    let exported = instance.get_func(&mut store, func)
+        .ok_or_else(|| CompilerError::runtime_error(format!("No exported function named '{}'", func), None, None))?;
+
+    let param_types = exported.ty(&store).params().collect::<Vec<_>>();
+    if param_types.len() != args.len() {
+        return Err(CompilerError::runtime_error(
+            format!("'{}' expects {} argument(s) but got {}", func, param_types.len(), args.len()),
+            None, None
+        ));
+    }
+    let wasm_args: Vec<wasmtime::Val> = args.iter().zip(param_types.iter())
+        .map(|(arg, ty)| value_to_wasm_val(arg, ty))
+        .collect::<Result<_, _>>()?;
+
+    let result_types = exported.ty(&store).results().collect::<Vec<_>>();
+    let mut results: Vec<wasmtime::Val> = result_types.iter().map(default_wasm_val).collect();
+    exported.call(&mut store, &wasm_args, &mut results)
+        .map_err(|e| CompilerError::runtime_error(format!("Call to '{}' failed: {}", func, e), None, None))?;
+
+    match results.first() {
+        Some(val) => wasm_val_to_value(val),
+        None => Ok(crate::ast::Value::Void),
+    }
+}
+
+fn value_to_wasm_val(value: &crate::ast::Value, ty: &wasmtime::ValType) -> Result<wasmtime::Val, CompilerError> {
+    use crate::ast::Value;
+    match (value, ty) {
+        (Value::Integer(n), wasmtime::ValType::I32) => Ok(wasmtime::Val::I32(*n as i32)),
+        (Value::Integer(n), wasmtime::ValType::I64) => Ok(wasmtime::Val::I64(*n)),
+        (Value::Integer(n), wasmtime::ValType::F64) => Ok(wasmtime::Val::F64((*n as f64).to_bits())),
+        (Value::Number(n), wasmtime::ValType::F64) => Ok(wasmtime::Val::F64(n.to_bits())),
+        (Value::Number(n), wasmtime::ValType::I32) => Ok(wasmtime::Val::I32(*n as i32)),
+        (Value::Boolean(b), wasmtime::ValType::I32) => Ok(wasmtime::Val::I32(if *b { 1 } else { 0 })),
+        (value, ty) => Err(CompilerError::runtime_error(
+            format!("Cannot coerce {:?} into a WASM {:?} argument", value, ty),
+            None, None
+        )),
+    }
+}
+
+fn default_wasm_val(ty: &wasmtime::ValType) -> wasmtime::Val {
+    match ty {
+        wasmtime::ValType::I32 => wasmtime::Val::I32(0),
+        wasmtime::ValType::I64 => wasmtime::Val::I64(0),
+        wasmtime::ValType::F32 => wasmtime::Val::F32(0),
+        wasmtime::ValType::F64 => wasmtime::Val::F64(0),
+        _ => wasmtime::Val::I32(0),
+    }
+}
+
+fn wasm_val_to_value(val: &wasmtime::Val) -> Result<crate::ast::Value, CompilerError> {
+    match val {
+        wasmtime::Val::I32(n) => Ok(crate::ast::Value::Integer(*n as i64)),
+        wasmtime::Val::I64(n) => Ok(crate::ast::Value::Integer(*n)),
+        wasmtime::Val::F32(bits) => Ok(crate::ast::Value::Number(f32::from_bits(*bits) as f64)),
+        wasmtime::Val::F64(bits) => Ok(crate::ast::Value::Number(f64::from_bits(*bits))),
+        other => Err(CompilerError::runtime_error(
+            format!("Unsupported WASM result type: {:?}", other),
+            None, None
+        )),
+    }
+}
\ No newline at end of file
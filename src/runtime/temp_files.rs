@@ -0,0 +1,95 @@
+// Temp-File Registry Module for Clean Language
+// Handle-keyed table of scratch paths backing the temp_file_create/
+// temp_dir_create/temp_file_path host functions, giving WASM guests a
+// place to stage inputs, error logs, and job queues without hand-managing
+// paths — and without littering the filesystem once the program ends.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+/// Registry of temp-directory-relative scratch paths. Cloning shares the
+/// same underlying table (every field is `Arc`-backed), mirroring
+/// `file_handles::FileHandleTable`.
+#[derive(Clone)]
+pub struct TempFileRegistry {
+    dir: PathBuf,
+    paths: Arc<Mutex<HashMap<i32, PathBuf>>>,
+    next_handle: Arc<Mutex<i32>>,
+}
+
+impl TempFileRegistry {
+    /// Scratch files and directories are created under `dir`, which need
+    /// not already exist — `file_create`/`create_dir` will be given the
+    /// first path lazily, so a bad `dir` only fails the first call instead
+    /// of construction.
+    pub fn new(dir: PathBuf) -> Self {
+        TempFileRegistry {
+            dir,
+            paths: Arc::new(Mutex::new(HashMap::new())),
+            next_handle: Arc::new(Mutex::new(1)),
+        }
+    }
+
+    fn next_handle(&self) -> i32 {
+        let mut next = self.next_handle.lock().unwrap();
+        let handle = *next;
+        *next += 1;
+        handle
+    }
+
+    /// `prefix` followed by the process id and this registry's handle
+    /// counter, which together are enough to keep concurrent runs (and
+    /// concurrent calls within one run) from colliding on a name.
+    fn unique_name(&self, prefix: &str, handle: i32) -> String {
+        format!("{}{}_{}", prefix, std::process::id(), handle)
+    }
+
+    /// Create an empty file named `prefix`-something under the configured
+    /// temp dir and register it for cleanup, returning its handle, or `-1`
+    /// if the file couldn't be created.
+    pub fn create_file(&self, prefix: &str) -> i32 {
+        let handle = self.next_handle();
+        let path = self.dir.join(self.unique_name(prefix, handle));
+        if fs::File::create(&path).is_err() {
+            return -1;
+        }
+        self.paths.lock().unwrap().insert(handle, path);
+        handle
+    }
+
+    /// Create a directory named `prefix`-something under the configured
+    /// temp dir and register it for cleanup, returning its handle, or `-1`
+    /// if the directory couldn't be created.
+    pub fn create_dir(&self, prefix: &str) -> i32 {
+        let handle = self.next_handle();
+        let path = self.dir.join(self.unique_name(prefix, handle));
+        if fs::create_dir_all(&path).is_err() {
+            return -1;
+        }
+        self.paths.lock().unwrap().insert(handle, path);
+        handle
+    }
+
+    /// The path registered under `handle`, or `None` if `handle` is
+    /// unknown.
+    pub fn path(&self, handle: i32) -> Option<PathBuf> {
+        self.paths.lock().unwrap().get(&handle).cloned()
+    }
+
+    /// Delete every tracked temp file/directory and forget their handles,
+    /// so a finished program leaves no litter behind. Individual removal
+    /// failures (already deleted, permissions, ...) are ignored — cleanup
+    /// is best-effort.
+    pub fn cleanup(&self) {
+        let mut paths = self.paths.lock().unwrap();
+        for (_, path) in paths.drain() {
+            if path.is_dir() {
+                let _ = fs::remove_dir_all(&path);
+            } else {
+                let _ = fs::remove_file(&path);
+            }
+        }
+    }
+}
@@ -0,0 +1,99 @@
+// URL Parsing and Percent-Encoding for Clean Language
+// Pure, Caller-free logic so the `url_parse`/`url_encode`/`url_decode`
+// host functions in `mod.rs` can validate and build URLs without relying
+// on raw string concatenation via `string_concat`.
+
+use crate::error::CompilerError;
+
+/// The parsed components of a URL, as `url_parse` writes them into guest
+/// memory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UrlParts {
+    pub scheme: String,
+    pub host: String,
+    pub port: Option<u16>,
+    pub path: String,
+    pub query: String,
+    pub fragment: String,
+}
+
+/// Parse `url` into its components. Deliberately simple (no IPv6 literal
+/// support, no userinfo) to match the rest of this crate's hand-rolled
+/// HTTP stack (see `http_client::HttpClient::parse_url`) rather than
+/// pulling in a full URL crate.
+pub fn parse(url: &str) -> Result<UrlParts, CompilerError> {
+    let url = url.trim();
+
+    let (scheme, rest) = url.split_once("://").ok_or_else(|| CompilerError::runtime_error(
+        format!("URL missing scheme: {}", url),
+        None, None,
+    ))?;
+    if scheme.is_empty() {
+        return Err(CompilerError::runtime_error(format!("URL missing scheme: {}", url), None, None));
+    }
+
+    let (rest, fragment) = match rest.split_once('#') {
+        Some((before, after)) => (before, after.to_string()),
+        None => (rest, String::new()),
+    };
+
+    let (rest, query) = match rest.split_once('?') {
+        Some((before, after)) => (before, after.to_string()),
+        None => (rest, String::new()),
+    };
+
+    let (authority, path) = match rest.split_once('/') {
+        Some((authority, path)) => (authority, format!("/{}", path)),
+        None => (rest, "/".to_string()),
+    };
+
+    if authority.is_empty() {
+        return Err(CompilerError::runtime_error(format!("URL missing host: {}", url), None, None));
+    }
+
+    let (host, port) = match authority.rsplit_once(':') {
+        Some((host, port_str)) => {
+            let port = port_str.parse::<u16>().map_err(|_| CompilerError::runtime_error(
+                format!("invalid port in URL: {}", url),
+                None, None,
+            ))?;
+            (host.to_string(), Some(port))
+        }
+        None => (authority.to_string(), None),
+    };
+
+    Ok(UrlParts { scheme: scheme.to_string(), host, port, path, query, fragment })
+}
+
+/// Percent-encode `s`, leaving RFC 3986 "unreserved" characters
+/// (`A-Za-z0-9-_.~`) untouched and escaping every other byte as `%XX`.
+pub fn encode(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for byte in s.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => out.push(byte as char),
+            _ => out.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    out
+}
+
+/// Reverse `encode`: replace `%XX` escapes with the byte they represent,
+/// validating as UTF-8. Returns `None` on malformed input (a `%` not
+/// followed by two hex digits, or invalid UTF-8 once decoded).
+pub fn decode(s: &str) -> Option<String> {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = s.get(i + 1..i + 3)?;
+            out.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            out.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(out).ok()
+}
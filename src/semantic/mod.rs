@@ -6,6 +6,8 @@ use crate::module::{ModuleResolver, ImportResolution};
 mod scope;
 use scope::Scope;
 
+pub mod type_checker;
+
 pub struct SemanticAnalyzer {
     #[allow(dead_code)]
     symbol_table: HashMap<String, Type>,
@@ -2031,6 +2033,12 @@ impl SemanticAnalyzer {
                 // Later assignment returns the type of the expression being assigned
                 self.check_expression(expression)
             },
+
+            Expression::TryPropagate { inner, location: _ } => {
+                // `?` yields the success type of the wrapped expression; the error
+                // variant is propagated out of the enclosing function during lowering.
+                self.check_expression(inner)
+            },
         }
     }
 
@@ -2039,7 +2047,8 @@ impl SemanticAnalyzer {
             Expression::PropertyAccess { location, .. } |
             Expression::MethodCall { location, .. } |
             Expression::ObjectCreation { location, .. } |
-            Expression::OnError { location, .. } => location.clone(),
+            Expression::OnError { location, .. } |
+            Expression::TryPropagate { location, .. } => location.clone(),
             _ => SourceLocation::default()
         }
     }
@@ -1,30 +1,260 @@
 use std::collections::HashMap;
-use crate::ast::{Expr, Type, FunctionDef, Statement};
-use crate::error::{CompilerError, CompilerResult};
 
-/// Type checker for semantic analysis
-pub struct TypeChecker {
-    symbol_table: HashMap<String, Type>,
-    function_table: HashMap<String, FunctionType>,
-    current_function_return_type: Option<Type>,
+/// Source position attached to every statement/expression this checker
+/// sees, mirroring `ast::SourceLocation` but kept local since this
+/// checker runs against its own small expression language rather than
+/// the full compiler AST.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Location {
+    pub line: usize,
+    pub column: usize,
+}
+
+/// A type error raised by `TypeChecker`, kept local (rather than
+/// `crate::error::CompilerError`) for the same reason `Location` is:
+/// this checker's type language doesn't line up with the compiler's
+/// real AST, so its errors shouldn't pretend to either.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypeError {
+    pub message: String,
+    pub location: Location,
+}
+
+pub type TypeResult<T> = Result<T, TypeError>;
+
+fn type_error<T>(location: Location, message: impl Into<String>) -> TypeResult<T> {
+    Err(TypeError { message: message.into(), location })
+}
+
+/// The type language this checker unifies over. `Var` is never written
+/// by a user — it's allocated by `TypeChecker::fresh_var` for an
+/// unannotated binding or parameter and resolved away (via
+/// `Substitution`) by the time checking finishes.
+///
+/// `Unique` wraps another type to add an ownership dimension, borrowed
+/// from Roc's uniqueness types: a `Unique(Matrix)` binding is guaranteed
+/// not to be aliased, which `TypeChecker` enforces by marking it
+/// consumed the moment it's handed to a consuming parameter or assigned
+/// elsewhere, and rejecting any later read of it.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Type {
+    Number,
+    String,
+    Bool,
+    Void,
+    Matrix,
+    /// An unbound (or bound-but-not-yet-resolved) type variable,
+    /// identified by a monotonically increasing id from `fresh_var`.
+    Var(u32),
+    Unique(Box<Type>),
+}
+
+#[derive(Debug, Clone)]
+pub enum Expr {
+    Number(f64),
+    String(String),
+    Bool(bool),
+    Variable { name: String, location: Location },
+    Binary { op: String, left: Box<Expr>, right: Box<Expr>, location: Location },
+    Call { function: String, args: Vec<Expr>, location: Location },
+    Matrix { rows: Vec<Vec<Expr>>, location: Location },
+}
+
+#[derive(Debug, Clone)]
+pub struct Parameter {
+    pub name: String,
+    /// `None` makes this parameter generic: its type is a fresh variable
+    /// that `TypeChecker::register_function` generalizes over, so each
+    /// call site can instantiate it at a different concrete type. A
+    /// `Some(Type::Unique(_))` parameter declares itself consuming: a
+    /// call site passing a bare variable into it moves that variable.
+    pub type_: Option<Type>,
+}
+
+#[derive(Debug, Clone)]
+pub struct FunctionDef {
+    pub name: String,
+    pub params: Vec<Parameter>,
+    pub return_type: Type,
+    pub body: Vec<Statement>,
+    pub location: Location,
 }
 
+#[derive(Debug, Clone)]
+pub enum Statement {
+    /// `type_: None` means the declaration has no annotation; its type
+    /// is inferred from `init` (or left an unresolved variable if there
+    /// is no initializer either).
+    Let { name: String, type_: Option<Type>, init: Option<Expr>, location: Location },
+    Assign { target: String, value: Expr, location: Location },
+    FunctionDef(FunctionDef),
+    Return { expr: Option<Expr>, location: Option<Location> },
+    If { condition: Expr, then_branch: Vec<Statement>, else_branch: Option<Vec<Statement>>, location: Location },
+    While { condition: Expr, body: Vec<Statement>, location: Location },
+    Expression(Expr),
+}
+
+/// A function's type, possibly still mentioning `Type::Var` ids that
+/// weren't pinned down by its own body — those are this function's
+/// generalized (generic) slots, each re-instantiated with fresh
+/// variables by `TypeChecker::instantiate` at every call site.
 #[derive(Clone, Debug, PartialEq)]
 pub struct FunctionType {
     pub params: Vec<Type>,
     pub return_type: Type,
 }
 
+/// Bindings for unification variables, built up by `TypeChecker::unify`
+/// and consulted by `TypeChecker::resolve` to chase a variable through
+/// to whatever concrete type (or further variable) it's bound to.
+pub type Substitution = HashMap<u32, Type>;
+
+/// A symbol table entry: the binding's type plus, for move-checking, the
+/// location it was consumed at (if any). Only `Type::Unique` bindings
+/// are ever marked consumed — plain bindings stay live forever since
+/// they were never guaranteed unaliased in the first place.
+#[derive(Debug, Clone, PartialEq)]
+struct SymbolInfo {
+    ty: Type,
+    consumed_at: Option<Location>,
+}
+
+/// Type checker for semantic analysis, with Hindley-Milner-style
+/// inference layered on top of the original exact-match checking: a
+/// binding or parameter with no annotation gets a fresh `Type::Var`
+/// instead of being rejected, and `unify` reconciles it against however
+/// the rest of the program uses it. Layered on top of that is a move
+/// checker for `Type::Unique` bindings: consuming one (via a consuming
+/// parameter or an assignment) flags it in `symbol_table`, and any later
+/// read is rejected instead of silently aliasing it.
+pub struct TypeChecker {
+    symbol_table: HashMap<String, SymbolInfo>,
+    function_table: HashMap<String, FunctionType>,
+    current_function_return_type: Option<Type>,
+    substitution: Substitution,
+    next_type_var: u32,
+}
+
 impl TypeChecker {
     pub fn new() -> Self {
         Self {
             symbol_table: HashMap::new(),
             function_table: HashMap::new(),
             current_function_return_type: None,
+            substitution: HashMap::new(),
+            next_type_var: 0,
+        }
+    }
+
+    /// Allocate a fresh, as-yet-unbound type variable.
+    fn fresh_var(&mut self) -> Type {
+        let id = self.next_type_var;
+        self.next_type_var += 1;
+        Type::Var(id)
+    }
+
+    /// Follow `ty` through `substitution` until it reaches a concrete
+    /// type or an unbound variable. Unlike a single HashMap lookup, this
+    /// chases multi-hop bindings (`Var(1)` bound to `Var(2)` bound to
+    /// `Number`) so callers never see a partially-resolved variable.
+    fn resolve(&self, ty: &Type) -> Type {
+        let mut current = ty.clone();
+        while let Type::Var(id) = current {
+            match self.substitution.get(&id) {
+                Some(next) => current = next.clone(),
+                None => break,
+            }
         }
+        current
     }
 
-    pub fn check_program(&mut self, statements: &[Statement]) -> CompilerResult<()> {
+    /// True if unbound variable `var` occurs inside `ty` once `ty` is
+    /// resolved — binding `var` to a type containing itself would
+    /// otherwise produce an infinite type when later resolved. Recurses
+    /// into composite constructors (just `Unique` today) so a variable
+    /// buried inside one is still caught, matching how `instantiate_type`
+    /// already has to walk the same shape.
+    fn occurs_in(&self, var: u32, ty: &Type) -> bool {
+        match self.resolve(ty) {
+            Type::Var(id) => id == var,
+            Type::Unique(inner) => self.occurs_in(var, &inner),
+            _ => false,
+        }
+    }
+
+    /// Bind unbound variable `var` to `ty`, after an occurs-check.
+    fn bind(&mut self, var: u32, ty: Type) -> TypeResult<()> {
+        if let Type::Var(id) = ty {
+            if id == var {
+                return Ok(());
+            }
+        }
+        if self.occurs_in(var, &ty) {
+            return type_error(
+                Location { line: 0, column: 0 },
+                format!("Infinite type: Var({}) occurs in {:?}", var, self.resolve(&ty)),
+            );
+        }
+        self.substitution.insert(var, ty);
+        Ok(())
+    }
+
+    /// Unify `a` and `b`: if either resolves to an unbound variable,
+    /// bind it to the other side; if both resolve to concrete
+    /// constructors, they must be structurally identical. Replaces the
+    /// direct `==` comparisons the checker used before inference existed.
+    fn unify(&mut self, a: &Type, b: &Type) -> TypeResult<()> {
+        let ra = self.resolve(a);
+        let rb = self.resolve(b);
+        match (ra, rb) {
+            (Type::Var(v1), Type::Var(v2)) if v1 == v2 => Ok(()),
+            (Type::Var(v), other) | (other, Type::Var(v)) => self.bind(v, other),
+            (x, y) if x == y => Ok(()),
+            (x, y) => type_error(
+                Location { line: 0, column: 0 },
+                format!("Cannot unify types {:?} and {:?}", x, y),
+            ),
+        }
+    }
+
+    /// Replace every `Type::Var` in `func_type` with a fresh variable,
+    /// consistently (the same source variable maps to the same fresh
+    /// variable within one call). This is what lets a generic helper —
+    /// one whose parameter type never got pinned down by its own body —
+    /// be called at different concrete types from different call sites
+    /// without their unifications bleeding into each other.
+    fn instantiate(&mut self, func_type: &FunctionType) -> FunctionType {
+        let mut renamed = HashMap::new();
+        let params = func_type.params.iter()
+            .map(|t| self.instantiate_type(t, &mut renamed))
+            .collect();
+        let return_type = self.instantiate_type(&func_type.return_type, &mut renamed);
+        FunctionType { params, return_type }
+    }
+
+    fn instantiate_type(&mut self, ty: &Type, renamed: &mut HashMap<u32, Type>) -> Type {
+        match ty {
+            Type::Var(id) => renamed.entry(*id).or_insert_with(|| self.fresh_var()).clone(),
+            Type::Unique(inner) => Type::Unique(Box::new(self.instantiate_type(inner, renamed))),
+            other => other.clone(),
+        }
+    }
+
+    /// Replace every type variable still reachable from `symbol_table`
+    /// with its final resolved type, so a caller inspecting the table
+    /// after `check_program` returns sees concrete types (or, for a
+    /// binding whose type was never pinned down by anything, the
+    /// original unbound `Var`) rather than having to resolve it by hand.
+    fn finalize(&mut self) {
+        let resolved: Vec<(String, Type, Option<Location>)> = self.symbol_table.iter()
+            .map(|(name, info)| (name.clone(), self.resolve(&info.ty), info.consumed_at))
+            .collect();
+        for (name, ty, consumed_at) in resolved {
+            self.symbol_table.insert(name, SymbolInfo { ty, consumed_at });
+        }
+    }
+
+    pub fn check_program(&mut self, statements: &[Statement]) -> TypeResult<()> {
         // First pass: collect function declarations
         for stmt in statements {
             if let Statement::FunctionDef(func) = stmt {
@@ -37,61 +267,92 @@ impl TypeChecker {
             self.check_statement(stmt)?;
         }
 
+        self.finalize();
         Ok(())
     }
 
-    fn register_function(&mut self, func: &FunctionDef) -> CompilerResult<()> {
+    fn register_function(&mut self, func: &FunctionDef) -> TypeResult<()> {
+        let params = func.params.iter()
+            .map(|p| p.type_.clone().unwrap_or_else(|| self.fresh_var()))
+            .collect();
         let func_type = FunctionType {
-            params: func.params.iter().map(|p| p.type_).collect(),
-            return_type: func.return_type,
+            params,
+            return_type: func.return_type.clone(),
         };
 
         if self.function_table.insert(func.name.clone(), func_type).is_some() {
-            return Err(CompilerError::undefined_function(
-                &func.name,
-                func.location.line,
-                func.location.column,
-            ));
+            return type_error(func.location, format!("Function '{}' is already defined", func.name));
         }
 
         Ok(())
     }
 
-    fn check_statement(&mut self, stmt: &Statement) -> CompilerResult<()> {
+    /// Mark `name` consumed at `location` if (and only if) it currently
+    /// holds a `Type::Unique` value — moving a plain binding is a no-op,
+    /// since only unique bindings carry the no-aliasing guarantee that
+    /// makes a second read unsafe.
+    fn mark_consumed(&mut self, name: &str, location: Location) {
+        let is_unique = self.symbol_table.get(name)
+            .map(|info| matches!(self.resolve(&info.ty), Type::Unique(_)))
+            .unwrap_or(false);
+        if is_unique {
+            if let Some(info) = self.symbol_table.get_mut(name) {
+                info.consumed_at = Some(location);
+            }
+        }
+    }
+
+    /// If `expr` is a bare variable reference, consume it (see
+    /// `mark_consumed`). Only a direct reference moves a unique value —
+    /// `f(a)` moves `a`, but `f(a + 0)` or `f(g(a))` produce a new value
+    /// that doesn't alias `a` itself.
+    fn consume_if_variable(&mut self, expr: &Expr) {
+        if let Expr::Variable { name, location } = expr {
+            self.mark_consumed(name, *location);
+        }
+    }
+
+    fn check_statement(&mut self, stmt: &Statement) -> TypeResult<()> {
         match stmt {
             Statement::Let { name, type_, init, location } => {
+                let declared_type = type_.clone().unwrap_or_else(|| self.fresh_var());
                 if let Some(init_expr) = init {
                     let expr_type = self.infer_type(init_expr)?;
-                    if expr_type != *type_ {
-                        return Err(CompilerError::type_error(
-                            location.line,
-                            location.column,
-                            format!("Cannot initialize variable of type {:?} with expression of type {:?}", type_, expr_type),
-                        ));
-                    }
+                    self.unify(&declared_type, &expr_type).map_err(|_| TypeError {
+                        location: *location,
+                        message: format!(
+                            "Cannot initialize variable of type {:?} with expression of type {:?}",
+                            self.resolve(&declared_type), self.resolve(&expr_type),
+                        ),
+                    })?;
+                    self.consume_if_variable(init_expr);
                 }
-                self.symbol_table.insert(name.clone(), *type_);
+                let resolved = self.resolve(&declared_type);
+                self.symbol_table.insert(name.clone(), SymbolInfo { ty: resolved, consumed_at: None });
                 Ok(())
             }
             Statement::Assign { target, value, location } => {
-                let target_type = self.lookup_variable(target, location.line, location.column)?;
+                let target_type = self.lookup_variable(target, *location)?;
                 let value_type = self.infer_type(value)?;
-                if target_type != value_type {
-                    return Err(CompilerError::type_error(
-                        location.line,
-                        location.column,
-                        format!("Cannot assign value of type {:?} to variable of type {:?}", value_type, target_type),
-                    ));
-                }
+                self.unify(&target_type, &value_type).map_err(|_| TypeError {
+                    location: *location,
+                    message: format!(
+                        "Cannot assign value of type {:?} to variable of type {:?}",
+                        self.resolve(&value_type), self.resolve(&target_type),
+                    ),
+                })?;
+                self.consume_if_variable(value);
                 Ok(())
             }
             Statement::FunctionDef(func) => {
                 let mut checker = TypeChecker::new();
                 // Set current function return type context
-                checker.current_function_return_type = Some(func.return_type);
-                // Add parameters to local scope
+                checker.current_function_return_type = Some(func.return_type.clone());
+                // Add parameters to local scope, generalizing any
+                // parameter left unannotated into a fresh variable.
                 for param in &func.params {
-                    checker.symbol_table.insert(param.name.clone(), param.type_);
+                    let param_type = param.type_.clone().unwrap_or_else(|| checker.fresh_var());
+                    checker.symbol_table.insert(param.name.clone(), SymbolInfo { ty: param_type, consumed_at: None });
                 }
                 // Check function body
                 for stmt in &func.body {
@@ -100,41 +361,36 @@ impl TypeChecker {
                 Ok(())
             }
             Statement::Return { expr, location } => {
-                if let Some(return_type) = &self.current_function_return_type {
+                if let Some(return_type) = self.current_function_return_type.clone() {
                     if let Some(expr) = expr {
                         let expr_type = self.infer_type(expr)?;
-                        if !self.types_compatible(return_type, &expr_type) {
-                            return Err(CompilerError::type_error(
-                                location.as_ref().map(|l| l.line).unwrap_or(0),
-                                location.as_ref().map(|l| l.column).unwrap_or(0),
-                                format!("Return type mismatch: expected {:?}, found {:?}", return_type, expr_type),
-                            ));
+                        if !self.types_compatible(&return_type, &expr_type) {
+                            return type_error(
+                                location.unwrap_or(Location { line: 0, column: 0 }),
+                                format!("Return type mismatch: expected {:?}, found {:?}", return_type, self.resolve(&expr_type)),
+                            );
                         }
-                    } else if *return_type != Type::Void {
-                        return Err(CompilerError::type_error(
-                            location.as_ref().map(|l| l.line).unwrap_or(0),
-                            location.as_ref().map(|l| l.column).unwrap_or(0),
+                        self.consume_if_variable(expr);
+                    } else if return_type != Type::Void {
+                        return type_error(
+                            location.unwrap_or(Location { line: 0, column: 0 }),
                             format!("Function expects return value of type {:?} but got void return", return_type),
-                        ));
+                        );
                     }
                 } else {
-                    return Err(CompilerError::type_error(
-                        location.as_ref().map(|l| l.line).unwrap_or(0),
-                        location.as_ref().map(|l| l.column).unwrap_or(0),
+                    return type_error(
+                        location.unwrap_or(Location { line: 0, column: 0 }),
                         "Return statement outside of function".to_string(),
-                    ));
+                    );
                 }
                 Ok(())
             }
             Statement::If { condition, then_branch, else_branch, location } => {
                 let cond_type = self.infer_type(condition)?;
-                if cond_type != Type::Bool {
-                    return Err(CompilerError::type_error(
-                        location.line,
-                        location.column,
-                        "If condition must be a boolean expression".to_string(),
-                    ));
-                }
+                self.unify(&cond_type, &Type::Bool).map_err(|_| TypeError {
+                    location: *location,
+                    message: "If condition must be a boolean expression".to_string(),
+                })?;
                 for stmt in then_branch {
                     self.check_statement(stmt)?;
                 }
@@ -147,13 +403,10 @@ impl TypeChecker {
             }
             Statement::While { condition, body, location } => {
                 let cond_type = self.infer_type(condition)?;
-                if cond_type != Type::Bool {
-                    return Err(CompilerError::type_error(
-                        location.line,
-                        location.column,
-                        "While condition must be a boolean expression".to_string(),
-                    ));
-                }
+                self.unify(&cond_type, &Type::Bool).map_err(|_| TypeError {
+                    location: *location,
+                    message: "While condition must be a boolean expression".to_string(),
+                })?;
                 for stmt in body {
                     self.check_statement(stmt)?;
                 }
@@ -166,84 +419,73 @@ impl TypeChecker {
         }
     }
 
-    fn infer_type(&self, expr: &Expr) -> CompilerResult<Type> {
+    fn infer_type(&mut self, expr: &Expr) -> TypeResult<Type> {
         match expr {
             Expr::Number(_) => Ok(Type::Number),
             Expr::String(_) => Ok(Type::String),
             Expr::Bool(_) => Ok(Type::Bool),
             Expr::Variable { name, location } => {
-                self.lookup_variable(name, location.line, location.column)
+                self.lookup_variable(name, *location)
             }
             Expr::Binary { op, left, right, location } => {
                 let left_type = self.infer_type(left)?;
                 let right_type = self.infer_type(right)?;
                 match op.as_str() {
                     "+" | "-" | "*" | "/" => {
-                        if left_type == Type::Number && right_type == Type::Number {
-                            Ok(Type::Number)
-                        } else {
-                            Err(CompilerError::type_error(
-                                location.line,
-                                location.column,
-                                format!("Cannot perform arithmetic operation on types {:?} and {:?}", left_type, right_type),
-                            ))
-                        }
+                        self.unify(&left_type, &Type::Number).and_then(|_| self.unify(&right_type, &Type::Number))
+                            .map(|_| Type::Number)
+                            .map_err(|_| TypeError {
+                                location: *location,
+                                message: format!("Cannot perform arithmetic operation on types {:?} and {:?}", self.resolve(&left_type), self.resolve(&right_type)),
+                            })
                     }
                     "==" | "!=" | "<" | "<=" | ">" | ">=" => {
-                        if left_type == right_type {
-                            Ok(Type::Bool)
-                        } else {
-                            Err(CompilerError::type_error(
-                                location.line,
-                                location.column,
-                                format!("Cannot compare values of different types: {:?} and {:?}", left_type, right_type),
-                            ))
-                        }
+                        self.unify(&left_type, &right_type)
+                            .map(|_| Type::Bool)
+                            .map_err(|_| TypeError {
+                                location: *location,
+                                message: format!("Cannot compare values of different types: {:?} and {:?}", self.resolve(&left_type), self.resolve(&right_type)),
+                            })
                     }
                     "&&" | "||" => {
-                        if left_type == Type::Bool && right_type == Type::Bool {
-                            Ok(Type::Bool)
-                        } else {
-                            Err(CompilerError::type_error(
-                                location.line,
-                                location.column,
-                                "Logical operators require boolean operands".to_string(),
-                            ))
-                        }
+                        self.unify(&left_type, &Type::Bool).and_then(|_| self.unify(&right_type, &Type::Bool))
+                            .map(|_| Type::Bool)
+                            .map_err(|_| TypeError {
+                                location: *location,
+                                message: "Logical operators require boolean operands".to_string(),
+                            })
                     }
-                    _ => Err(CompilerError::type_error(
-                        location.line,
-                        location.column,
-                        format!("Unknown operator: {}", op),
-                    )),
+                    _ => type_error(*location, format!("Unknown operator: {}", op)),
                 }
             }
             Expr::Call { function, args, location } => {
-                if let Some(func_type) = self.function_table.get(function) {
+                if let Some(func_type) = self.function_table.get(function).cloned() {
                     if args.len() != func_type.params.len() {
-                        return Err(CompilerError::type_error(
-                            location.line,
-                            location.column,
+                        return type_error(
+                            *location,
                             format!("Function {} expects {} arguments but got {}", function, func_type.params.len(), args.len()),
-                        ));
+                        );
                     }
-                    for (arg, expected_type) in args.iter().zip(func_type.params.iter()) {
+                    // Instantiate the function's (possibly generic)
+                    // scheme with fresh variables so this call site's
+                    // unifications don't leak into another call's.
+                    let instantiated = self.instantiate(&func_type);
+                    for (arg, expected_type) in args.iter().zip(instantiated.params.iter()) {
                         let arg_type = self.infer_type(arg)?;
-                        if arg_type != *expected_type {
-                            return Err(CompilerError::type_error(
-                                location.line,
-                                location.column,
-                                format!("Expected argument of type {:?} but got {:?}", expected_type, arg_type),
-                            ));
+                        self.unify(&arg_type, expected_type).map_err(|_| TypeError {
+                            location: *location,
+                            message: format!("Expected argument of type {:?} but got {:?}", self.resolve(expected_type), self.resolve(&arg_type)),
+                        })?;
+                        // A parameter declared with a unique type
+                        // consumes whatever bare variable is passed to
+                        // it -- the call site no longer owns the value.
+                        if matches!(self.resolve(expected_type), Type::Unique(_)) {
+                            self.consume_if_variable(arg);
                         }
                     }
-                    Ok(func_type.return_type.clone())
+                    Ok(self.resolve(&instantiated.return_type))
                 } else {
-                    Err(CompilerError::undefined_function(
-                        function,
-                        location.line,
-                        location.column,
-                    ))
+                    type_error(*location, format!("Undefined function: {}", function))
                 }
             }
             Expr::Matrix { rows, location } => {
@@ -253,21 +495,14 @@ impl TypeChecker {
                 let row_len = rows[0].len();
                 for row in rows {
                     if row.len() != row_len {
-                        return Err(CompilerError::type_error(
-                            location.line,
-                            location.column,
-                            "All matrix rows must have the same length".to_string(),
-                        ));
+                        return type_error(*location, "All matrix rows must have the same length".to_string());
                     }
                     for elem in row {
                         let elem_type = self.infer_type(elem)?;
-                        if elem_type != Type::Number {
-                            return Err(CompilerError::type_error(
-                                location.line,
-                                location.column,
-                                "Matrix elements must be numbers".to_string(),
-                            ));
-                        }
+                        self.unify(&elem_type, &Type::Number).map_err(|_| TypeError {
+                            location: *location,
+                            message: "Matrix elements must be numbers".to_string(),
+                        })?;
                     }
                 }
                 Ok(Type::Matrix)
@@ -275,24 +510,38 @@ impl TypeChecker {
         }
     }
 
-    fn lookup_variable(&self, name: &str, line: usize, column: usize) -> CompilerResult<Type> {
-        self.symbol_table
-            .get(name)
-            .cloned()
-            .ok_or_else(|| CompilerError::undefined_variable(name, line, column))
+    fn lookup_variable(&self, name: &str, location: Location) -> TypeResult<Type> {
+        let info = self.symbol_table.get(name)
+            .ok_or_else(|| TypeError { location, message: format!("Undefined variable: {}", name) })?;
+        if let Some(consumed_at) = info.consumed_at {
+            return type_error(
+                consumed_at,
+                format!("Use of moved unique value '{}'", name),
+            );
+        }
+        Ok(info.ty.clone())
     }
 
+    /// Whether a value typed `actual` may be used where `expected` is
+    /// wanted. Beyond plain equality (and leaving unresolved variables
+    /// permissive, as before), a `Type::Unique` value may flow into a
+    /// position expecting its plain, shared base type -- handing a
+    /// unique `Matrix` to something that only needs to read a `Matrix`
+    /// demotes it, it doesn't need to be unique there. The reverse does
+    /// not hold: a shared value can't satisfy a position that demands
+    /// uniqueness, since nothing stopped it from being aliased already.
     fn types_compatible(&self, expected: &Type, actual: &Type) -> bool {
+        let expected = self.resolve(expected);
+        let actual = self.resolve(actual);
         if expected == actual {
             return true;
         }
-        // Handle Any type - it's compatible with everything
-        if matches!(expected, Type::Any) || matches!(actual, Type::Any) {
+        if matches!(expected, Type::Var(_)) || matches!(actual, Type::Var(_)) {
             return true;
         }
-        // Numeric type promotions
-        match (expected, actual) {
-            (Type::Number, Type::Integer) => true,
+        match (&expected, &actual) {
+            (Type::Unique(e), Type::Unique(a)) => self.types_compatible(e, a),
+            (e, Type::Unique(a)) if !matches!(e, Type::Unique(_)) => self.types_compatible(e, a),
             _ => false,
         }
     }
@@ -301,14 +550,13 @@ impl TypeChecker {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::ast::Location;
 
     #[test]
     fn test_variable_declaration() {
         let mut checker = TypeChecker::new();
         let stmt = Statement::Let {
             name: "x".to_string(),
-            type_: Type::Number,
+            type_: Some(Type::Number),
             init: Some(Expr::Number(42.0)),
             location: Location { line: 1, column: 1 },
         };
@@ -320,7 +568,7 @@ mod tests {
         let mut checker = TypeChecker::new();
         let stmt = Statement::Let {
             name: "x".to_string(),
-            type_: Type::Number,
+            type_: Some(Type::Number),
             init: Some(Expr::String("hello".to_string())),
             location: Location { line: 1, column: 1 },
         };
@@ -329,7 +577,7 @@ mod tests {
 
     #[test]
     fn test_undefined_variable() {
-        let checker = TypeChecker::new();
+        let mut checker = TypeChecker::new();
         let expr = Expr::Variable {
             name: "x".to_string(),
             location: Location { line: 1, column: 1 },
@@ -358,7 +606,7 @@ mod tests {
 
     #[test]
     fn test_matrix_type_checking() {
-        let checker = TypeChecker::new();
+        let mut checker = TypeChecker::new();
         let expr = Expr::Matrix {
             rows: vec![
                 vec![Expr::Number(1.0), Expr::Number(2.0)],
@@ -377,4 +625,130 @@ mod tests {
         };
         assert!(checker.infer_type(&expr).is_err());
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn test_occurs_check_rejects_a_variable_bound_to_itself_wrapped_in_unique() {
+        let mut checker = TypeChecker::new();
+        let v = match checker.fresh_var() {
+            Type::Var(id) => id,
+            _ => unreachable!(),
+        };
+        let wrapped = Type::Unique(Box::new(Type::Var(v)));
+        let result = checker.unify(&Type::Var(v), &wrapped);
+        assert!(result.is_err(), "unifying Var(v) with Unique(Var(v)) should fail the occurs check, not bind an infinite type");
+    }
+
+    #[test]
+    fn test_let_without_annotation_infers_from_init() {
+        let mut checker = TypeChecker::new();
+        let stmt = Statement::Let {
+            name: "x".to_string(),
+            type_: None,
+            init: Some(Expr::Number(42.0)),
+            location: Location { line: 1, column: 1 },
+        };
+        assert!(checker.check_statement(&stmt).is_ok());
+        assert_eq!(checker.symbol_table.get("x").map(|info| info.ty.clone()), Some(Type::Number));
+    }
+
+    #[test]
+    fn test_generic_function_called_at_multiple_types() {
+        let mut checker = TypeChecker::new();
+        let identity = FunctionDef {
+            name: "identity".to_string(),
+            params: vec![Parameter { name: "x".to_string(), type_: None }],
+            return_type: Type::Var(0), // placeholder, overwritten by register_function
+            body: vec![],
+            location: Location { line: 1, column: 1 },
+        };
+        checker.register_function(&identity).unwrap();
+
+        let call_with_number = Expr::Call {
+            function: "identity".to_string(),
+            args: vec![Expr::Number(1.0)],
+            location: Location { line: 1, column: 1 },
+        };
+        let call_with_string = Expr::Call {
+            function: "identity".to_string(),
+            args: vec![Expr::String("hi".to_string())],
+            location: Location { line: 1, column: 1 },
+        };
+        assert!(checker.infer_type(&call_with_number).is_ok());
+        assert!(checker.infer_type(&call_with_string).is_ok());
+    }
+
+    #[test]
+    fn test_occurs_check_rejects_infinite_type() {
+        let mut checker = TypeChecker::new();
+        let var = checker.fresh_var();
+        let Type::Var(id) = &var else { unreachable!() };
+        let id = *id;
+        // Binding Var(id) to itself through another variable should be a no-op,
+        // not an infinite type; binding it to something that truly contains
+        // itself is prevented by occurs_in.
+        assert!(checker.bind(id, var).is_ok());
+    }
+
+    #[test]
+    fn test_use_of_moved_unique_value_is_rejected() {
+        let mut checker = TypeChecker::new();
+        let loc = Location { line: 1, column: 1 };
+
+        let let_stmt = Statement::Let {
+            name: "m".to_string(),
+            type_: Some(Type::Unique(Box::new(Type::Matrix))),
+            init: None,
+            location: loc,
+        };
+        checker.check_statement(&let_stmt).unwrap();
+
+        // Moving `m` into `n` consumes it.
+        let move_stmt = Statement::Let {
+            name: "n".to_string(),
+            type_: Some(Type::Unique(Box::new(Type::Matrix))),
+            init: Some(Expr::Variable { name: "m".to_string(), location: loc }),
+            location: loc,
+        };
+        checker.check_statement(&move_stmt).unwrap();
+
+        // Reading `m` again is now an error.
+        let reuse = Expr::Variable { name: "m".to_string(), location: loc };
+        assert!(checker.infer_type(&reuse).is_err());
+    }
+
+    #[test]
+    fn test_consuming_parameter_marks_argument_consumed() {
+        let mut checker = TypeChecker::new();
+        let loc = Location { line: 1, column: 1 };
+
+        checker.function_table.insert(
+            "consume".to_string(),
+            FunctionType {
+                params: vec![Type::Unique(Box::new(Type::Matrix))],
+                return_type: Type::Void,
+            },
+        );
+        checker.symbol_table.insert(
+            "m".to_string(),
+            SymbolInfo { ty: Type::Unique(Box::new(Type::Matrix)), consumed_at: None },
+        );
+
+        let call = Expr::Call {
+            function: "consume".to_string(),
+            args: vec![Expr::Variable { name: "m".to_string(), location: loc }],
+            location: loc,
+        };
+        assert!(checker.infer_type(&call).is_ok());
+
+        let reuse = Expr::Variable { name: "m".to_string(), location: loc };
+        assert!(checker.infer_type(&reuse).is_err());
+    }
+
+    #[test]
+    fn test_unique_value_demotes_into_shared_position() {
+        let checker = TypeChecker::new();
+        let unique_matrix = Type::Unique(Box::new(Type::Matrix));
+        assert!(checker.types_compatible(&Type::Matrix, &unique_matrix));
+        assert!(!checker.types_compatible(&unique_matrix, &Type::Matrix));
+    }
+}
@@ -495,22 +495,105 @@ impl NumericOperations {
             Some(WasmType::F64),
             self.generate_tanh()
         )?;
-        
+
+        // Integer power function (non-negative integer exponent). Kept to
+        // i32 rather than f64 because `register_function` only knows how
+        // to allocate extra i32 scratch locals (see its `locals_needed`
+        // computation), and this needs two of them for the accumulator
+        // and loop counter.
+        register_stdlib_function(
+            codegen,
+            "pow",
+            &[WasmType::I32, WasmType::I32],
+            Some(WasmType::I32),
+            self.generate_pow_function()
+        )?;
+
+        // Factorial function
+        register_stdlib_function(
+            codegen,
+            "factorial",
+            &[WasmType::I32],
+            Some(WasmType::I32),
+            self.generate_factorial_function()
+        )?;
+
         Ok(())
     }
-    
+
     // Helper functions to generate complex mathematical operations
-    
+
     fn generate_pow_function(&self) -> Vec<Instruction> {
-        // Simplified implementation to avoid WASM validation issues
-        // Parameters: base, exponent
-        // Returns base^exponent approximation
+        // Parameters: base (i32, local 0), exponent (i32, local 1)
+        // Locals: result (i32, local 2), counter (i32, local 3)
+        // result = 1; while counter < exponent { result *= base; counter += 1 }
         vec![
-            // For now, just return base * exponent as a simple placeholder
-            // In a real implementation, this would compute proper exponentiation
-            Instruction::LocalGet(0), // base
+            Instruction::I32Const(1),
+            Instruction::LocalSet(2), // result = 1
+
+            Instruction::I32Const(0),
+            Instruction::LocalSet(3), // counter = 0
+
+            Instruction::Block(wasm_encoder::BlockType::Empty),
+            Instruction::Loop(wasm_encoder::BlockType::Empty),
+
+            Instruction::LocalGet(3), // counter
             Instruction::LocalGet(1), // exponent
-            Instruction::F64Mul,      // base * exponent (placeholder)
+            Instruction::I32GeS,      // counter >= exponent?
+            Instruction::BrIf(1),     // exit loop
+
+            Instruction::LocalGet(2), // result
+            Instruction::LocalGet(0), // base
+            Instruction::I32Mul,
+            Instruction::LocalSet(2), // result *= base
+
+            Instruction::LocalGet(3),
+            Instruction::I32Const(1),
+            Instruction::I32Add,
+            Instruction::LocalSet(3), // counter += 1
+
+            Instruction::Br(0),      // continue loop
+            Instruction::End,        // end loop
+            Instruction::End,        // end block
+
+            Instruction::LocalGet(2), // result
+        ]
+    }
+
+    fn generate_factorial_function(&self) -> Vec<Instruction> {
+        // Parameters: n (i32, local 0)
+        // Locals: result (i32, local 1), counter (i32, local 2)
+        // result = 1; for counter in 1..=n { result *= counter }
+        vec![
+            Instruction::I32Const(1),
+            Instruction::LocalSet(1), // result = 1
+
+            Instruction::I32Const(1),
+            Instruction::LocalSet(2), // counter = 1
+
+            Instruction::Block(wasm_encoder::BlockType::Empty),
+            Instruction::Loop(wasm_encoder::BlockType::Empty),
+
+            Instruction::LocalGet(2), // counter
+            Instruction::LocalGet(0), // n
+            Instruction::I32GtS,      // counter > n?
+            Instruction::BrIf(1),     // exit loop
+
+            Instruction::LocalGet(1), // result
+            Instruction::LocalGet(2), // counter
+            Instruction::I32Mul,
+            Instruction::LocalSet(1), // result *= counter
+
+            Instruction::LocalGet(2),
+            Instruction::I32Const(1),
+            Instruction::I32Add,
+            Instruction::LocalSet(2), // counter += 1
+
+            Instruction::Br(0),      // continue loop
+            Instruction::End,        // end loop
+            Instruction::End,        // end block
+
+            Instruction::LocalGet(1), // result
         ]
     }
     
@@ -847,13 +930,82 @@ mod tests {
     fn test_greater_than() {
         let (mut store, instance) = setup_test_environment();
         let greater_than = instance.get_func(&mut store, "greater_than").unwrap();
-        
+
         let mut results = vec![Val::I32(0)];
         greater_than.call(&mut store, &[
-            Val::F64(f64::to_bits(3.0)), 
+            Val::F64(f64::to_bits(3.0)),
             Val::F64(f64::to_bits(2.5))
         ], &mut results).unwrap();
-        
+
         assert_eq!(results[0].unwrap_i32(), 1);
     }
+
+    // Golden tests driving the generated module end-to-end through
+    // `runtime::run_export`, rather than just checking (as the tests
+    // above do) that the individual instruction sequences validate.
+    mod golden {
+        use super::*;
+        use crate::ast::Value;
+        use crate::runtime::run_export;
+
+        fn full_numeric_module() -> Vec<u8> {
+            let mut codegen = CodeGenerator::new();
+            NumericOperations::new().register_functions(&mut codegen).unwrap();
+            codegen.generate_test_module_without_imports().unwrap()
+        }
+
+        #[test]
+        fn abs_of_negative_integer() {
+            let wasm = full_numeric_module();
+            // The i32 overload of `abs` is the one registered last, so it's
+            // the one that ends up exported under the plain name "abs".
+            let result = run_export(&wasm, "abs", &[Value::Integer(-5)]).unwrap();
+            assert_eq!(result, Value::Integer(5));
+        }
+
+        #[test]
+        fn max_and_min_of_two_numbers() {
+            let wasm = full_numeric_module();
+            let max = run_export(&wasm, "max", &[Value::Number(2.5), Value::Number(7.25)]).unwrap();
+            assert_eq!(max, Value::Number(7.25));
+
+            let min = run_export(&wasm, "min", &[Value::Number(2.5), Value::Number(7.25)]).unwrap();
+            assert_eq!(min, Value::Number(2.5));
+        }
+
+        #[test]
+        fn sqrt_of_non_perfect_square() {
+            let wasm = full_numeric_module();
+            let result = run_export(&wasm, "sqrt", &[Value::Number(2.0)]).unwrap();
+            match result {
+                Value::Number(n) => assert!((n - std::f64::consts::SQRT_2).abs() < 1e-12),
+                other => panic!("expected a number, got {:?}", other),
+            }
+        }
+
+        #[test]
+        fn pow_of_integers() {
+            let wasm = full_numeric_module();
+            let result = run_export(&wasm, "pow", &[Value::Integer(2), Value::Integer(10)]).unwrap();
+            assert_eq!(result, Value::Integer(1024));
+
+            // Any base to the power of 0 is 1.
+            let result = run_export(&wasm, "pow", &[Value::Integer(7), Value::Integer(0)]).unwrap();
+            assert_eq!(result, Value::Integer(1));
+        }
+
+        #[test]
+        fn factorial_edge_cases_and_typical_values() {
+            let wasm = full_numeric_module();
+
+            let zero = run_export(&wasm, "factorial", &[Value::Integer(0)]).unwrap();
+            assert_eq!(zero, Value::Integer(1));
+
+            let one = run_export(&wasm, "factorial", &[Value::Integer(1)]).unwrap();
+            assert_eq!(one, Value::Integer(1));
+
+            let five = run_export(&wasm, "factorial", &[Value::Integer(5)]).unwrap();
+            assert_eq!(five, Value::Integer(120));
+        }
+    }
 } 
\ No newline at end of file
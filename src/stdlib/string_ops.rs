@@ -10,6 +10,29 @@ use crate::stdlib::register_stdlib_function;
 
 pub const STRING_TYPE_ID: u32 = 1;
 
+/// Numeric error codes that generated string bounds/type checks trap with.
+/// These mirror the situations `StringManager`'s Rust-side helpers
+/// (`get_char`, `set_char`, `get_string`, ...) already report as
+/// `CompilerError`s - keeping the mapping here lets a host runtime render
+/// the same message for a WASM-side trap as the compiler would have for the
+/// equivalent host-side call.
+pub mod string_errors {
+    pub const INVALID_POINTER: i32 = 1;
+    pub const INDEX_OUT_OF_BOUNDS: i32 = 2;
+    pub const BUFFER_OVERFLOW: i32 = 3;
+    pub const MALFORMED_UTF8: i32 = 4;
+
+    pub fn describe(code: i32) -> &'static str {
+        match code {
+            INVALID_POINTER => "Invalid string pointer",
+            INDEX_OUT_OF_BOUNDS => "String index out of bounds",
+            BUFFER_OVERFLOW => "String buffer overflow",
+            MALFORMED_UTF8 => "Malformed UTF-8 sequence",
+            _ => "Unknown string error",
+        }
+    }
+}
+
 pub struct StringManager {
     memory_manager: MemoryManager,
 }
@@ -60,43 +83,138 @@ impl StringManager {
         instructions
     }
 
+    /// Parameters: string_ptr (0), index (1). Locals: 2 = length.
+    /// Traps via `unreachable` on an out-of-range index instead of reading
+    /// whatever happens to live past the string's data.
     fn generate_string_get(&self) -> Vec<Instruction> {
-        let mut instructions = Vec::new();
-        instructions.push(Instruction::LocalGet(0)); // string pointer
-        instructions.push(Instruction::LocalGet(1)); // index
-        instructions.push(Instruction::I32Add); // Add pointer and index
-        instructions.push(Instruction::I32Load8U(MemArg {
-            offset: 0,
-            align: 0,
-            memory_index: 0,
-        })); // Load byte
-        instructions
+        vec![
+            Instruction::LocalGet(0),
+            Instruction::I32Load(MemArg { offset: 0, align: 2, memory_index: 0 }),
+            Instruction::LocalSet(2), // length
+
+            Instruction::LocalGet(1),
+            Instruction::I32Const(0),
+            Instruction::I32LtS,
+            Instruction::LocalGet(1),
+            Instruction::LocalGet(2),
+            Instruction::I32GeS,
+            Instruction::I32Or,
+            Instruction::If(wasm_encoder::BlockType::Empty),
+                Instruction::Unreachable, // index out of bounds
+            Instruction::End,
+
+            Instruction::LocalGet(0),
+            Instruction::I32Const(16),
+            Instruction::I32Add,
+            Instruction::LocalGet(1),
+            Instruction::I32Add,
+            Instruction::I32Load8U(MemArg { offset: 0, align: 0, memory_index: 0 }),
+        ]
     }
 
+    /// Parameters: string_ptr (0), index (1), character (2). Locals: 2 is
+    /// reused for length here since the character argument only needs
+    /// reading once. Traps via `unreachable` on an out-of-range index.
     fn generate_string_set(&self) -> Vec<Instruction> {
-        let mut instructions = Vec::new();
-        instructions.push(Instruction::LocalGet(0)); // string pointer
-        instructions.push(Instruction::LocalGet(1)); // index
-        instructions.push(Instruction::I32Add); // Add pointer and index
-        instructions.push(Instruction::LocalGet(2)); // character to store
-        instructions.push(Instruction::I32Store8(MemArg {
-            offset: 0,
-            align: 0,
-            memory_index: 0,
-        })); // Store byte
-        instructions.push(Instruction::LocalGet(0)); // Return string pointer
-        instructions
+        vec![
+            Instruction::LocalGet(0),
+            Instruction::I32Load(MemArg { offset: 0, align: 2, memory_index: 0 }),
+            Instruction::LocalSet(3), // length
+
+            Instruction::LocalGet(1),
+            Instruction::I32Const(0),
+            Instruction::I32LtS,
+            Instruction::LocalGet(1),
+            Instruction::LocalGet(3),
+            Instruction::I32GeS,
+            Instruction::I32Or,
+            Instruction::If(wasm_encoder::BlockType::Empty),
+                Instruction::Unreachable, // index out of bounds
+            Instruction::End,
+
+            Instruction::LocalGet(0),
+            Instruction::I32Const(16),
+            Instruction::I32Add,
+            Instruction::LocalGet(1),
+            Instruction::I32Add,
+            Instruction::LocalGet(2),
+            Instruction::I32Store8(MemArg { offset: 0, align: 0, memory_index: 0 }),
+
+            Instruction::LocalGet(0), // Return string pointer
+        ]
     }
 
     pub fn allocate_string(&mut self, length: usize) -> Result<usize, CompilerError> {
-        let ptr = self.memory_manager.allocate(length + 16, STRING_TYPE_ID)?;
-        
-        // Store length in header
+        self.allocate_string_with_capacity(length, length)
+    }
+
+    /// Like `allocate_string`, but reserves `capacity` bytes of data space
+    /// (capacity is widened to at least `length`) so later `set_string`
+    /// calls can grow the string in place up to that capacity instead of
+    /// failing.
+    ///
+    /// Header layout (16 bytes): offset 0 = length, offset 4 = capacity,
+    /// offset 8 = cached FNV-1a hash (0 = not yet computed), offset 12
+    /// reserved. Data starts at offset 16.
+    pub fn allocate_string_with_capacity(&mut self, length: usize, capacity: usize) -> Result<usize, CompilerError> {
+        let capacity = capacity.max(length);
+        let ptr = self.memory_manager.allocate(capacity + 16, STRING_TYPE_ID)?;
+
         self.memory_manager.store_i32(ptr, length as i32)?;
-        
+        self.memory_manager.store_i32(ptr + 4, capacity as i32)?;
+        self.memory_manager.store_i32(ptr + 8, 0)?; // hash not yet computed
+
         Ok(ptr)
     }
 
+    fn capacity_of(&self, string_ptr: usize) -> Result<usize, CompilerError> {
+        Ok(i32::from_le_bytes([
+            self.memory_manager.data[string_ptr + 4],
+            self.memory_manager.data[string_ptr + 5],
+            self.memory_manager.data[string_ptr + 6],
+            self.memory_manager.data[string_ptr + 7],
+        ]) as usize)
+    }
+
+    /// Lazily computes and caches the FNV-1a hash of a string's current
+    /// contents (offset 8 of the header), returning the cached value if one
+    /// is already present.
+    pub fn string_hash(&mut self, string_ptr: usize) -> Result<u32, CompilerError> {
+        if self.memory_manager.get_type_id(string_ptr)? != STRING_TYPE_ID {
+            return Err(CompilerError::type_error(
+                "Invalid string pointer",
+                Some("Ensure the string pointer is valid".to_string()),
+                None
+            ));
+        }
+
+        let cached = i32::from_le_bytes([
+            self.memory_manager.data[string_ptr + 8],
+            self.memory_manager.data[string_ptr + 9],
+            self.memory_manager.data[string_ptr + 10],
+            self.memory_manager.data[string_ptr + 11],
+        ]) as u32;
+        if cached != 0 {
+            return Ok(cached);
+        }
+
+        let length = i32::from_le_bytes([
+            self.memory_manager.data[string_ptr],
+            self.memory_manager.data[string_ptr + 1],
+            self.memory_manager.data[string_ptr + 2],
+            self.memory_manager.data[string_ptr + 3],
+        ]) as usize;
+
+        let mut hash: u32 = 2166136261;
+        for &byte in &self.memory_manager.data[string_ptr + 16..string_ptr + 16 + length] {
+            hash ^= byte as u32;
+            hash = hash.wrapping_mul(16777619);
+        }
+
+        self.memory_manager.store_i32(string_ptr + 8, hash as i32)?;
+        Ok(hash)
+    }
+
     pub fn get_string(&self, string_ptr: usize) -> Result<String, CompilerError> {
         // Check type
         if self.memory_manager.get_type_id(string_ptr)? != STRING_TYPE_ID {
@@ -135,28 +253,27 @@ impl StringManager {
                 None
             ));
         }
-        
-        // Get length from header
-        let length = i32::from_le_bytes([
-            self.memory_manager.data[string_ptr],
-            self.memory_manager.data[string_ptr + 1],
-            self.memory_manager.data[string_ptr + 2],
-            self.memory_manager.data[string_ptr + 3],
-        ]) as usize;
-        
-        // Check length
-        if value.len() > length {
+
+        // Growing in place is bounded by capacity, not by the current
+        // length - that's the whole point of tracking it separately.
+        let capacity = self.capacity_of(string_ptr)?;
+
+        if value.len() > capacity {
             return Err(CompilerError::type_error(
-                format!("String too long: {} > {}", value.len(), length),
-                Some("Ensure the string fits within allocated space".to_string()),
+                format!("String too long: {} > {}", value.len(), capacity),
+                Some("Ensure the string fits within the allocated capacity".to_string()),
                 None
             ));
         }
-        
-        // Copy string data
+
+        // Copy string data and record the new length.
         self.memory_manager.data[string_ptr + 16..string_ptr + 16 + value.len()]
             .copy_from_slice(value.as_bytes());
-        
+        self.memory_manager.store_i32(string_ptr, value.len() as i32)?;
+
+        // Content changed - the cached hash no longer reflects it.
+        self.memory_manager.store_i32(string_ptr + 8, 0)?;
+
         Ok(())
     }
 
@@ -218,6 +335,10 @@ impl StringManager {
         }
         
         self.memory_manager.data[string_ptr + 16 + index] = value;
+
+        // Content changed - the cached hash no longer reflects it.
+        self.memory_manager.store_i32(string_ptr + 8, 0)?;
+
         Ok(())
     }
 }
@@ -230,13 +351,26 @@ impl StringOperations {
     }
 
     pub fn register_functions(&self, codegen: &mut CodeGenerator) -> Result<(), CompilerError> {
+        // Every generated string function that produces a new string pulls
+        // its output buffer from the same host allocator guest code shares
+        // with the runtime (see chunk101-1/chunk102-4's `HostHeap`), rather
+        // than writing to a fixed scratch address. Registered once, up
+        // front, so every generator below can bake the resulting import
+        // index into its `Call`.
+        let malloc_idx = codegen.register_import_function(
+            "env",
+            "malloc",
+            &[WasmType::I32], // requested size in bytes
+            Some(WasmType::I32), // allocated pointer
+        )?;
+
         // Register string operations
         register_stdlib_function(
             codegen,
             "string.concat",
             &[WasmType::I32, WasmType::I32], // string1, string2
             Some(WasmType::I32), // result
-            self.generate_string_concat()
+            self.generate_string_concat(malloc_idx)
         )?;
 
         // Register string comparison function
@@ -248,13 +382,22 @@ impl StringOperations {
             self.generate_string_compare()
         )?;
 
+        // Register string hash function - lazily computed, cached in the header
+        register_stdlib_function(
+            codegen,
+            "string_hash",
+            &[WasmType::I32], // string
+            Some(WasmType::I32), // FNV-1a hash
+            self.generate_string_hash()
+        )?;
+
         // Register string replace function
         register_stdlib_function(
             codegen,
             "string.replace",
             &[WasmType::I32, WasmType::I32, WasmType::I32], // string, old, new
             Some(WasmType::I32), // new string
-            self.generate_string_replace()
+            self.generate_string_replace(malloc_idx)
         )?;
 
         // Register string length function
@@ -289,7 +432,7 @@ impl StringOperations {
             "string.trim",
             &[WasmType::I32], // string pointer
             Some(WasmType::I32), // trimmed string
-            self.generate_string_trim()
+            self.generate_string_trim(malloc_idx)
         )?;
 
         register_stdlib_function(
@@ -297,7 +440,7 @@ impl StringOperations {
             "string.replaceAll",
             &[WasmType::I32, WasmType::I32, WasmType::I32], // string, old, new
             Some(WasmType::I32), // new string
-            self.generate_string_replace_all()
+            self.generate_string_replace_all(malloc_idx)
         )?;
 
         register_stdlib_function(
@@ -305,7 +448,7 @@ impl StringOperations {
             "string.split",
             &[WasmType::I32, WasmType::I32], // string, delimiter
             Some(WasmType::I32), // list pointer
-            self.generate_string_split()
+            self.generate_string_split(malloc_idx)
         )?;
 
         // Register new string functions
@@ -330,7 +473,7 @@ impl StringOperations {
             "string_last_index_of",
             &[WasmType::I32, WasmType::I32], // string, search
             Some(WasmType::I32), // index (-1 if not found)
-            vec![Instruction::I32Const(-1)] // SIMPLIFIED STUB - return -1
+            self.generate_string_last_index_of()
         )?;
 
         register_stdlib_function(
@@ -354,7 +497,7 @@ impl StringOperations {
             "string_to_upper",
             &[WasmType::I32], // string
             Some(WasmType::I32), // new string
-            self.generate_string_to_upper()
+            self.generate_string_to_upper(malloc_idx)
         )?;
 
         register_stdlib_function(
@@ -362,7 +505,7 @@ impl StringOperations {
             "string_to_lower",
             &[WasmType::I32], // string
             Some(WasmType::I32), // new string
-            self.generate_string_to_lower()
+            self.generate_string_to_lower(malloc_idx)
         )?;
 
         register_stdlib_function(
@@ -370,7 +513,7 @@ impl StringOperations {
             "string_trim",
             &[WasmType::I32], // string
             Some(WasmType::I32), // new string
-            self.generate_string_trim()
+            self.generate_string_trim(malloc_idx)
         )?;
 
         register_stdlib_function(
@@ -378,7 +521,7 @@ impl StringOperations {
             "string_to_upper_case",
             &[WasmType::I32], // string
             Some(WasmType::I32), // new string
-            self.generate_string_to_upper()
+            self.generate_string_to_upper(malloc_idx)
         )?;
 
         register_stdlib_function(
@@ -386,7 +529,7 @@ impl StringOperations {
             "string_to_lower_case",
             &[WasmType::I32], // string
             Some(WasmType::I32), // new string
-            self.generate_string_to_lower()
+            self.generate_string_to_lower(malloc_idx)
         )?;
 
         register_stdlib_function(
@@ -394,7 +537,7 @@ impl StringOperations {
             "string_trim_start",
             &[WasmType::I32], // string
             Some(WasmType::I32), // new string
-            vec![Instruction::LocalGet(0)] // SIMPLIFIED STUB
+            self.generate_string_trim_start(malloc_idx)
         )?;
 
         register_stdlib_function(
@@ -402,7 +545,7 @@ impl StringOperations {
             "string_trim_end",
             &[WasmType::I32], // string
             Some(WasmType::I32), // new string
-            vec![Instruction::LocalGet(0)] // SIMPLIFIED STUB
+            self.generate_string_trim_end()
         )?;
 
         register_stdlib_function(
@@ -410,7 +553,7 @@ impl StringOperations {
             "string_substring",
             &[WasmType::I32, WasmType::I32, WasmType::I32], // string, start, end
             Some(WasmType::I32), // new string
-            self.generate_string_substring()
+            self.generate_string_substring(malloc_idx)
         )?;
 
         register_stdlib_function(
@@ -418,7 +561,7 @@ impl StringOperations {
             "string_replace",
             &[WasmType::I32, WasmType::I32, WasmType::I32], // string, old, new
             Some(WasmType::I32), // new string
-            self.generate_string_replace()
+            self.generate_string_replace(malloc_idx)
         )?;
 
         register_stdlib_function(
@@ -426,7 +569,7 @@ impl StringOperations {
             "string_replace_all",
             &[WasmType::I32, WasmType::I32, WasmType::I32], // string, old, new
             Some(WasmType::I32), // new string
-            self.generate_string_replace_all()
+            self.generate_string_replace_all(malloc_idx)
         )?;
 
         register_stdlib_function(
@@ -440,11 +583,19 @@ impl StringOperations {
         register_stdlib_function(
             codegen,
             "string_char_code_at",
-            &[WasmType::I32, WasmType::I32], // string, index
-            Some(WasmType::I32), // character code
+            &[WasmType::I32, WasmType::I32], // string, character index (codepoint-aware)
+            Some(WasmType::I32), // full Unicode scalar value, or -1 if out of range/malformed
             self.generate_string_char_code_at()
         )?;
 
+        register_stdlib_function(
+            codegen,
+            "string_char_count",
+            &[WasmType::I32], // string
+            Some(WasmType::I32), // codepoint count
+            self.generate_string_char_count()
+        )?;
+
         register_stdlib_function(
             codegen,
             "string_is_empty",
@@ -483,7 +634,7 @@ impl StringOperations {
             "string_trim_start_impl",
             &[WasmType::I32], // string
             Some(WasmType::I32), // trimmed string
-            vec![Instruction::LocalGet(0)] // SIMPLIFIED STUB
+            self.generate_string_trim_start(malloc_idx)
         )?;
 
         // Register string_trim_end_impl for compatibility with codegen
@@ -492,7 +643,7 @@ impl StringOperations {
             "string_trim_end_impl",
             &[WasmType::I32], // string
             Some(WasmType::I32), // trimmed string
-            vec![Instruction::LocalGet(0)] // SIMPLIFIED STUB
+            self.generate_string_trim_end()
         )?;
 
         // Register string_last_index_of_impl for compatibility with codegen
@@ -501,7 +652,7 @@ impl StringOperations {
             "string_last_index_of_impl",
             &[WasmType::I32, WasmType::I32], // string, search
             Some(WasmType::I32), // index (-1 if not found)
-            vec![Instruction::I32Const(-1)] // SIMPLIFIED STUB - return -1
+            self.generate_string_last_index_of()
         )?;
 
         // Register string_substring_impl for compatibility with codegen
@@ -511,7 +662,7 @@ impl StringOperations {
             "string_substring_impl",
             &[WasmType::I32, WasmType::I32, WasmType::I32], // string, start, end
             Some(WasmType::I32), // new string
-            self.generate_simple_substring()
+            self.generate_simple_substring(malloc_idx)
         )?;
 
         // Register string_replace_impl for compatibility with codegen
@@ -521,7 +672,7 @@ impl StringOperations {
             "string_replace_impl",
             &[WasmType::I32, WasmType::I32, WasmType::I32], // string, old, new
             Some(WasmType::I32), // new string
-            self.generate_simple_replace()
+            self.generate_simple_replace(malloc_idx)
         )?;
 
         // Register string_pad_start_impl for compatibility with codegen
@@ -540,7 +691,7 @@ impl StringOperations {
             "string_trim_impl",
             &[WasmType::I32], // string
             Some(WasmType::I32), // trimmed string
-            self.generate_string_trim()
+            self.generate_string_trim(malloc_idx)
         )?;
 
         // Register string_to_lower_case_impl for compatibility with codegen
@@ -549,7 +700,7 @@ impl StringOperations {
             "string_to_lower_case_impl",
             &[WasmType::I32], // string
             Some(WasmType::I32), // new string
-            self.generate_string_to_lower()
+            self.generate_string_to_lower(malloc_idx)
         )?;
 
         // Register string_to_upper_case_impl for compatibility with codegen
@@ -558,7 +709,7 @@ impl StringOperations {
             "string_to_upper_case_impl",
             &[WasmType::I32], // string
             Some(WasmType::I32), // new string
-            self.generate_string_to_upper()
+            self.generate_string_to_upper(malloc_idx)
         )?;
 
         // Register string_starts_with_impl for compatibility with codegen
@@ -579,22 +730,329 @@ impl StringOperations {
             self.generate_string_ends_with()
         )?;
 
+        // Register the UTF-16 host interop bridge.
+        register_stdlib_function(
+            codegen,
+            "string_utf16_len",
+            &[WasmType::I32], // string
+            Some(WasmType::I32), // code unit count
+            self.generate_string_utf16_len()
+        )?;
+
+        register_stdlib_function(
+            codegen,
+            "string_to_utf16",
+            &[WasmType::I32], // string
+            Some(WasmType::I32), // UTF-16 buffer pointer
+            self.generate_string_to_utf16(malloc_idx)
+        )?;
+
+        register_stdlib_function(
+            codegen,
+            "string_from_utf16",
+            &[WasmType::I32], // UTF-16 buffer pointer
+            Some(WasmType::I32), // new string
+            self.generate_string_from_utf16(malloc_idx)
+        )?;
+
         Ok(())
     }
 
-    fn generate_string_concat(&self) -> Vec<Instruction> {
-        // Simplified version for testing - just return the first string pointer
-        // In a real implementation, this would allocate memory and concatenate strings
+    fn generate_string_concat(&self, malloc_idx: u32) -> Vec<Instruction> {
+        // Parameters: str1_ptr (0), str2_ptr (1). Returns a new string
+        // pointer whose data is str1's bytes followed by str2's bytes.
+        //
+        // Locals: 2 = len1, 3 = len2, 4 = copy index, 5 = byte being copied,
+        // 6 = dest pointer, freshly allocated via the shared `env.malloc`
+        // import (size = len1 + len2 + 16, matching the 16-byte header) so
+        // each call gets its own buffer instead of aliasing a fixed address
+        // - chained concatenation (`a + b + c`) no longer overwrites the
+        // first result on the second call.
         vec![
-            Instruction::LocalGet(0), // Return first string pointer
+            Instruction::LocalGet(0),
+            Instruction::I32Load(MemArg { offset: 0, align: 2, memory_index: 0 }),
+            Instruction::LocalSet(2), // len1
+
+            Instruction::LocalGet(1),
+            Instruction::I32Load(MemArg { offset: 0, align: 2, memory_index: 0 }),
+            Instruction::LocalSet(3), // len2
+
+            Instruction::LocalGet(2),
+            Instruction::LocalGet(3),
+            Instruction::I32Add,
+            Instruction::I32Const(16),
+            Instruction::I32Add,
+            Instruction::Call(malloc_idx),
+            Instruction::LocalSet(6), // dest = malloc(len1 + len2 + 16)
+
+            // Write the combined length and capacity into the new header;
+            // hash (offset 8) defaults to 0, i.e. "not yet computed".
+            Instruction::LocalGet(6),
+            Instruction::LocalGet(2),
+            Instruction::LocalGet(3),
+            Instruction::I32Add,
+            Instruction::I32Store(MemArg { offset: 0, align: 2, memory_index: 0 }),
+            Instruction::LocalGet(6),
+            Instruction::LocalGet(2),
+            Instruction::LocalGet(3),
+            Instruction::I32Add,
+            Instruction::I32Store(MemArg { offset: 4, align: 2, memory_index: 0 }),
+
+            // Copy str1[16..16+len1] to dest[16..16+len1].
+            Instruction::I32Const(0),
+            Instruction::LocalSet(4), // i = 0
+            Instruction::Block(wasm_encoder::BlockType::Empty),
+                Instruction::Loop(wasm_encoder::BlockType::Empty),
+                    Instruction::LocalGet(4),
+                    Instruction::LocalGet(2),
+                    Instruction::I32GeU,
+                    Instruction::BrIf(1),
+
+                    Instruction::LocalGet(6),
+                    Instruction::I32Const(16),
+                    Instruction::I32Add,
+                    Instruction::LocalGet(4),
+                    Instruction::I32Add,
+
+                    Instruction::LocalGet(0),
+                    Instruction::I32Const(16),
+                    Instruction::I32Add,
+                    Instruction::LocalGet(4),
+                    Instruction::I32Add,
+                    Instruction::I32Load8U(MemArg { offset: 0, align: 0, memory_index: 0 }),
+                    Instruction::LocalSet(5),
+                    Instruction::LocalGet(5),
+                    Instruction::I32Store8(MemArg { offset: 0, align: 0, memory_index: 0 }),
+
+                    Instruction::LocalGet(4),
+                    Instruction::I32Const(1),
+                    Instruction::I32Add,
+                    Instruction::LocalSet(4),
+                    Instruction::Br(0),
+                Instruction::End,
+            Instruction::End,
+
+            // Copy str2[16..16+len2] to dest[16+len1..16+len1+len2].
+            Instruction::I32Const(0),
+            Instruction::LocalSet(4), // i = 0 (reused)
+            Instruction::Block(wasm_encoder::BlockType::Empty),
+                Instruction::Loop(wasm_encoder::BlockType::Empty),
+                    Instruction::LocalGet(4),
+                    Instruction::LocalGet(3),
+                    Instruction::I32GeU,
+                    Instruction::BrIf(1),
+
+                    Instruction::LocalGet(6),
+                    Instruction::I32Const(16),
+                    Instruction::I32Add,
+                    Instruction::LocalGet(2),
+                    Instruction::I32Add,
+                    Instruction::LocalGet(4),
+                    Instruction::I32Add,
+
+                    Instruction::LocalGet(1),
+                    Instruction::I32Const(16),
+                    Instruction::I32Add,
+                    Instruction::LocalGet(4),
+                    Instruction::I32Add,
+                    Instruction::I32Load8U(MemArg { offset: 0, align: 0, memory_index: 0 }),
+                    Instruction::LocalSet(5),
+                    Instruction::LocalGet(5),
+                    Instruction::I32Store8(MemArg { offset: 0, align: 0, memory_index: 0 }),
+
+                    Instruction::LocalGet(4),
+                    Instruction::I32Const(1),
+                    Instruction::I32Add,
+                    Instruction::LocalSet(4),
+                    Instruction::Br(0),
+                Instruction::End,
+            Instruction::End,
+
+            Instruction::LocalGet(6),
         ]
     }
 
+    /// Lexicographically compares two strings, returning -1, 0, or 1.
+    ///
+    /// Parameters: string_ptr1 (0), string_ptr2 (1).
+    ///
+    /// Locals: 2 = len1, 3 = len2, 4 = hash1, 5 = hash2, 6 = byte index,
+    /// 7 = byte from string1, 8 = byte from string2, 9 = result.
+    ///
+    /// When the two strings are the same length and both already have a
+    /// cached hash (offset 8 of the header - see `generate_string_hash`),
+    /// a hash mismatch proves inequality without scanning a single byte.
+    /// A cache of 0 means "not yet computed", so that case always falls
+    /// through to the real scan.
     fn generate_string_compare(&self) -> Vec<Instruction> {
-        // Simplified string compare that just compares first byte for testing
         vec![
-            // Just return 0 for now (strings are equal)
+            Instruction::LocalGet(0),
+            Instruction::I32Load(MemArg { offset: 0, align: 2, memory_index: 0 }),
+            Instruction::LocalSet(2), // len1
+            Instruction::LocalGet(1),
+            Instruction::I32Load(MemArg { offset: 0, align: 2, memory_index: 0 }),
+            Instruction::LocalSet(3), // len2
+
+            Instruction::I32Const(0),
+            Instruction::LocalSet(9), // result = 0
+
+            Instruction::LocalGet(2),
+            Instruction::LocalGet(3),
+            Instruction::I32Ne,
+            Instruction::If(wasm_encoder::BlockType::Empty),
+                // Different lengths: shorter sorts first.
+                Instruction::LocalGet(2),
+                Instruction::LocalGet(3),
+                Instruction::I32LtU,
+                Instruction::If(wasm_encoder::BlockType::Empty),
+                    Instruction::I32Const(-1),
+                    Instruction::LocalSet(9),
+                Instruction::Else,
+                    Instruction::I32Const(1),
+                    Instruction::LocalSet(9),
+                Instruction::End,
+            Instruction::Else,
+                Instruction::LocalGet(0),
+                Instruction::I32Load(MemArg { offset: 8, align: 2, memory_index: 0 }),
+                Instruction::LocalSet(4), // hash1
+                Instruction::LocalGet(1),
+                Instruction::I32Load(MemArg { offset: 8, align: 2, memory_index: 0 }),
+                Instruction::LocalSet(5), // hash2
+
+                Instruction::LocalGet(4),
+                Instruction::I32Const(0),
+                Instruction::I32Ne,
+                Instruction::LocalGet(5),
+                Instruction::I32Const(0),
+                Instruction::I32Ne,
+                Instruction::I32And,
+                Instruction::LocalGet(4),
+                Instruction::LocalGet(5),
+                Instruction::I32Ne,
+                Instruction::I32And,
+                Instruction::If(wasm_encoder::BlockType::Empty),
+                    // Both hashes cached and they differ - unequal, no scan needed.
+                    Instruction::I32Const(1),
+                    Instruction::LocalSet(9),
+                Instruction::Else,
+                    Instruction::I32Const(0),
+                    Instruction::LocalSet(6), // i = 0
+                    Instruction::Block(wasm_encoder::BlockType::Empty),
+                        Instruction::Loop(wasm_encoder::BlockType::Empty),
+                            Instruction::LocalGet(6),
+                            Instruction::LocalGet(2),
+                            Instruction::I32GeU,
+                            Instruction::BrIf(1), // scanned the whole (equal-length) string: equal
+
+                            Instruction::LocalGet(0),
+                            Instruction::I32Const(16),
+                            Instruction::I32Add,
+                            Instruction::LocalGet(6),
+                            Instruction::I32Add,
+                            Instruction::I32Load8U(MemArg { offset: 0, align: 0, memory_index: 0 }),
+                            Instruction::LocalSet(7),
+                            Instruction::LocalGet(1),
+                            Instruction::I32Const(16),
+                            Instruction::I32Add,
+                            Instruction::LocalGet(6),
+                            Instruction::I32Add,
+                            Instruction::I32Load8U(MemArg { offset: 0, align: 0, memory_index: 0 }),
+                            Instruction::LocalSet(8),
+
+                            Instruction::LocalGet(7),
+                            Instruction::LocalGet(8),
+                            Instruction::I32Ne,
+                            Instruction::If(wasm_encoder::BlockType::Empty),
+                                Instruction::LocalGet(7),
+                                Instruction::LocalGet(8),
+                                Instruction::I32LtU,
+                                Instruction::If(wasm_encoder::BlockType::Empty),
+                                    Instruction::I32Const(-1),
+                                    Instruction::LocalSet(9),
+                                Instruction::Else,
+                                    Instruction::I32Const(1),
+                                    Instruction::LocalSet(9),
+                                Instruction::End,
+                                Instruction::Br(2), // mismatch found, stop scanning
+                            Instruction::End,
+
+                            Instruction::LocalGet(6),
+                            Instruction::I32Const(1),
+                            Instruction::I32Add,
+                            Instruction::LocalSet(6),
+                            Instruction::Br(0),
+                        Instruction::End, // loop
+                    Instruction::End, // block
+                Instruction::End, // if hash mismatch
+            Instruction::End, // if len1 != len2
+
+            Instruction::LocalGet(9),
+        ]
+    }
+
+    /// Lazily computes and caches an FNV-1a hash of the string's bytes into
+    /// the header at offset 8 (0 means "not yet computed" - see the header
+    /// layout note on `generate_string_concat`'s neighbours). Backs the
+    /// fast-path equality checks in `generate_string_compare` and
+    /// `generate_string_contains`, and is suitable for keying hashed
+    /// collections by string content.
+    ///
+    /// Parameters: string_ptr (0). Locals: 2 = cached hash, 3 = length,
+    /// 4 = byte index, 5 = running hash.
+    pub fn generate_string_hash(&self) -> Vec<Instruction> {
+        vec![
+            Instruction::LocalGet(0),
+            Instruction::I32Load(MemArg { offset: 8, align: 2, memory_index: 0 }),
+            Instruction::LocalSet(2), // cached
+
+            Instruction::LocalGet(2),
             Instruction::I32Const(0),
+            Instruction::I32Ne,
+            Instruction::If(wasm_encoder::BlockType::Result(wasm_encoder::ValType::I32)),
+                Instruction::LocalGet(2),
+            Instruction::Else,
+                Instruction::LocalGet(0),
+                Instruction::I32Load(MemArg { offset: 0, align: 2, memory_index: 0 }),
+                Instruction::LocalSet(3), // length
+
+                Instruction::I32Const(-2128831035), // 2166136261 as i32 (FNV offset basis)
+                Instruction::LocalSet(5), // hash
+
+                Instruction::I32Const(0),
+                Instruction::LocalSet(4), // i = 0
+
+                Instruction::Block(wasm_encoder::BlockType::Empty),
+                    Instruction::Loop(wasm_encoder::BlockType::Empty),
+                        Instruction::LocalGet(4),
+                        Instruction::LocalGet(3),
+                        Instruction::I32GeU,
+                        Instruction::BrIf(1),
+
+                        Instruction::LocalGet(5),
+                        Instruction::LocalGet(0),
+                        Instruction::I32Const(16),
+                        Instruction::I32Add,
+                        Instruction::LocalGet(4),
+                        Instruction::I32Add,
+                        Instruction::I32Load8U(MemArg { offset: 0, align: 0, memory_index: 0 }),
+                        Instruction::I32Xor,
+                        Instruction::I32Const(16777619), // FNV prime
+                        Instruction::I32Mul,
+                        Instruction::LocalSet(5),
+
+                        Instruction::LocalGet(4),
+                        Instruction::I32Const(1),
+                        Instruction::I32Add,
+                        Instruction::LocalSet(4),
+                        Instruction::Br(0),
+                    Instruction::End,
+                Instruction::End,
+
+                Instruction::LocalGet(0),
+                Instruction::LocalGet(5),
+                Instruction::I32Store(MemArg { offset: 8, align: 2, memory_index: 0 }),
+                Instruction::LocalGet(5),
+            Instruction::End,
         ]
     }
 
@@ -615,31 +1073,383 @@ impl StringOperations {
 
     // NEW STRING FUNCTIONS
 
-    fn generate_string_contains(&self) -> Vec<Instruction> {
-        // Simplified string contains implementation - just return true for now
-        // This will help isolate the stack balance issue
+    /// Fixed scratch region for `generate_forward_search`'s 256-entry
+    /// bad-character shift table (256 i32 slots = 1024 bytes). Unlike the
+    /// `*_SCRATCH` constants the earlier allocator fixes removed, this
+    /// table never escapes the function that builds it - it's written and
+    /// consumed entirely within one call to `generate_forward_search`, so
+    /// a fixed address is safe: there's nothing for a later call to alias.
+    const FORWARD_SEARCH_SHIFT_TABLE_SCRATCH: i32 = 11264;
+
+    /// Shared forward byte-scanning search used by `contains`/`indexOf`,
+    /// implemented as Boyer-Moore-Horspool: build a 256-entry bad-character
+    /// shift table from the needle, then scan comparing each candidate
+    /// window back-to-front, skipping ahead by the shift table's entry for
+    /// the haystack byte aligned with the needle's last position whenever a
+    /// window doesn't match outright.
+    ///
+    /// Locals (beyond the two string-pointer parameters 0 and 1):
+    ///   2 = haystack length, 3 = needle length, 4 = candidate start position
+    ///   `i`, 5 = inner comparison index `j` (counts down from `nlen - 1`),
+    ///   6 = match flag for the current `i`, 7 = result index (-1 until a
+    ///   match is found), 8 = shift-table build index, 9 = shift amount for
+    ///   the current mismatch.
+    fn generate_forward_search(&self) -> Vec<Instruction> {
         vec![
-            Instruction::I32Const(1), // Always return true for testing
+            // hlen = load(string_ptr), nlen = load(search_ptr)
+            Instruction::LocalGet(0),
+            Instruction::I32Load(MemArg { offset: 0, align: 2, memory_index: 0 }),
+            Instruction::LocalSet(2),
+            Instruction::LocalGet(1),
+            Instruction::I32Load(MemArg { offset: 0, align: 2, memory_index: 0 }),
+            Instruction::LocalSet(3),
+
+            Instruction::I32Const(-1),
+            Instruction::LocalSet(7), // result = -1
+
+            // An empty needle matches at position 0.
+            Instruction::LocalGet(3),
+            Instruction::I32Eqz,
+            Instruction::If(wasm_encoder::BlockType::Empty),
+                Instruction::I32Const(0),
+                Instruction::LocalSet(7),
+            Instruction::Else,
+                // A needle longer than the haystack can never match.
+                Instruction::LocalGet(3),
+                Instruction::LocalGet(2),
+                Instruction::I32GtU,
+                Instruction::If(wasm_encoder::BlockType::Empty),
+                Instruction::Else,
+                    // Build the bad-character shift table: default every
+                    // entry to nlen (a full skip past the needle), then
+                    // give each byte that actually occurs in the needle
+                    // (except its last position) the distance from that
+                    // occurrence to the needle's end.
+                    Instruction::I32Const(0),
+                    Instruction::LocalSet(8), // fill index = 0
+                    Instruction::Block(wasm_encoder::BlockType::Empty),
+                        Instruction::Loop(wasm_encoder::BlockType::Empty),
+                            Instruction::LocalGet(8),
+                            Instruction::I32Const(256),
+                            Instruction::I32GeU,
+                            Instruction::BrIf(1),
+
+                            Instruction::I32Const(Self::FORWARD_SEARCH_SHIFT_TABLE_SCRATCH),
+                            Instruction::LocalGet(8),
+                            Instruction::I32Const(4),
+                            Instruction::I32Mul,
+                            Instruction::I32Add,
+                            Instruction::LocalGet(3),
+                            Instruction::I32Store(MemArg { offset: 0, align: 2, memory_index: 0 }),
+
+                            Instruction::LocalGet(8),
+                            Instruction::I32Const(1),
+                            Instruction::I32Add,
+                            Instruction::LocalSet(8),
+                            Instruction::Br(0),
+                        Instruction::End,
+                    Instruction::End,
+
+                    Instruction::I32Const(0),
+                    Instruction::LocalSet(8), // reused as the needle-scan index
+                    Instruction::Block(wasm_encoder::BlockType::Empty),
+                        Instruction::Loop(wasm_encoder::BlockType::Empty),
+                            Instruction::LocalGet(8),
+                            Instruction::LocalGet(3),
+                            Instruction::I32Const(1),
+                            Instruction::I32Sub,
+                            Instruction::I32GeU,
+                            Instruction::BrIf(1),
+
+                            Instruction::I32Const(Self::FORWARD_SEARCH_SHIFT_TABLE_SCRATCH),
+                            Instruction::LocalGet(1),
+                            Instruction::I32Const(16),
+                            Instruction::I32Add,
+                            Instruction::LocalGet(8),
+                            Instruction::I32Add,
+                            Instruction::I32Load8U(MemArg { offset: 0, align: 0, memory_index: 0 }),
+                            Instruction::I32Const(4),
+                            Instruction::I32Mul,
+                            Instruction::I32Add,
+                            Instruction::LocalGet(3),
+                            Instruction::I32Const(1),
+                            Instruction::I32Sub,
+                            Instruction::LocalGet(8),
+                            Instruction::I32Sub,
+                            Instruction::I32Store(MemArg { offset: 0, align: 2, memory_index: 0 }),
+
+                            Instruction::LocalGet(8),
+                            Instruction::I32Const(1),
+                            Instruction::I32Add,
+                            Instruction::LocalSet(8),
+                            Instruction::Br(0),
+                        Instruction::End,
+                    Instruction::End,
+
+                    Instruction::I32Const(0),
+                    Instruction::LocalSet(4), // i = 0
+
+                    Instruction::Block(wasm_encoder::BlockType::Empty),
+                        Instruction::Loop(wasm_encoder::BlockType::Empty),
+                            // Stop once the needle no longer fits, or once we've found a match.
+                            Instruction::LocalGet(4),
+                            Instruction::LocalGet(3),
+                            Instruction::I32Add,
+                            Instruction::LocalGet(2),
+                            Instruction::I32GtU,
+                            Instruction::BrIf(1),
+                            Instruction::LocalGet(7),
+                            Instruction::I32Const(0),
+                            Instruction::I32GeS,
+                            Instruction::BrIf(1),
+
+                            Instruction::I32Const(1),
+                            Instruction::LocalSet(6), // match = true
+                            Instruction::LocalGet(3),
+                            Instruction::I32Const(1),
+                            Instruction::I32Sub,
+                            Instruction::LocalSet(5), // j = nlen - 1
+
+                            Instruction::Block(wasm_encoder::BlockType::Empty),
+                                Instruction::Loop(wasm_encoder::BlockType::Empty),
+                                    // Compare haystack[i + j] with needle[j] (data starts at offset 16).
+                                    Instruction::LocalGet(0),
+                                    Instruction::I32Const(16),
+                                    Instruction::I32Add,
+                                    Instruction::LocalGet(4),
+                                    Instruction::I32Add,
+                                    Instruction::LocalGet(5),
+                                    Instruction::I32Add,
+                                    Instruction::I32Load8U(MemArg { offset: 0, align: 0, memory_index: 0 }),
+                                    Instruction::LocalGet(1),
+                                    Instruction::I32Const(16),
+                                    Instruction::I32Add,
+                                    Instruction::LocalGet(5),
+                                    Instruction::I32Add,
+                                    Instruction::I32Load8U(MemArg { offset: 0, align: 0, memory_index: 0 }),
+                                    Instruction::I32Ne,
+                                    Instruction::If(wasm_encoder::BlockType::Empty),
+                                        Instruction::I32Const(0),
+                                        Instruction::LocalSet(6), // mismatch -> match = false
+                                        Instruction::Br(1), // stop comparing this window
+                                    Instruction::End,
+
+                                    Instruction::LocalGet(5),
+                                    Instruction::I32Eqz,
+                                    Instruction::BrIf(1), // compared position 0, window fully matched
+
+                                    Instruction::LocalGet(5),
+                                    Instruction::I32Const(1),
+                                    Instruction::I32Sub,
+                                    Instruction::LocalSet(5), // j -= 1
+                                    Instruction::Br(0),
+                                Instruction::End, // inner loop
+                            Instruction::End, // inner block
+
+                            Instruction::LocalGet(6),
+                            Instruction::If(wasm_encoder::BlockType::Empty),
+                                Instruction::LocalGet(4),
+                                Instruction::LocalSet(7), // result = i
+                            Instruction::Else,
+                                // Skip ahead by the shift table's entry for
+                                // the haystack byte aligned with the
+                                // needle's last position.
+                                Instruction::I32Const(Self::FORWARD_SEARCH_SHIFT_TABLE_SCRATCH),
+                                Instruction::LocalGet(0),
+                                Instruction::I32Const(16),
+                                Instruction::I32Add,
+                                Instruction::LocalGet(4),
+                                Instruction::I32Add,
+                                Instruction::LocalGet(3),
+                                Instruction::I32Const(1),
+                                Instruction::I32Sub,
+                                Instruction::I32Add,
+                                Instruction::I32Load8U(MemArg { offset: 0, align: 0, memory_index: 0 }),
+                                Instruction::I32Const(4),
+                                Instruction::I32Mul,
+                                Instruction::I32Add,
+                                Instruction::I32Load(MemArg { offset: 0, align: 2, memory_index: 0 }),
+                                Instruction::LocalSet(9), // shift
+
+                                Instruction::LocalGet(4),
+                                Instruction::LocalGet(9),
+                                Instruction::I32Add,
+                                Instruction::LocalSet(4), // i += shift
+                            Instruction::End,
+                            Instruction::Br(0),
+                        Instruction::End, // outer loop
+                    Instruction::End, // outer block
+                Instruction::End, // if nlen > hlen
+            Instruction::End, // if nlen == 0
         ]
     }
 
+    fn generate_string_contains(&self) -> Vec<Instruction> {
+        // Parameters: string_ptr, search_ptr. Returns 1 if search is found, 0 otherwise.
+        //
+        // A haystack can only equal-contain a needle of the *same* length by
+        // being equal to it, so in that one case a cached-hash mismatch
+        // (locals 8, 9) rules out containment without a scan. Any other
+        // length pair, or an uncached (zero) hash, falls through to the
+        // real search.
+        let mut instructions = vec![
+            Instruction::LocalGet(0),
+            Instruction::I32Load(MemArg { offset: 0, align: 2, memory_index: 0 }),
+            Instruction::LocalGet(1),
+            Instruction::I32Load(MemArg { offset: 0, align: 2, memory_index: 0 }),
+            Instruction::I32Eq,
+            Instruction::If(wasm_encoder::BlockType::Empty),
+                Instruction::LocalGet(0),
+                Instruction::I32Load(MemArg { offset: 8, align: 2, memory_index: 0 }),
+                Instruction::LocalSet(8), // hash1
+                Instruction::LocalGet(1),
+                Instruction::I32Load(MemArg { offset: 8, align: 2, memory_index: 0 }),
+                Instruction::LocalSet(9), // hash2
+            Instruction::End,
+        ];
+
+        instructions.push(Instruction::LocalGet(0));
+        instructions.push(Instruction::I32Load(MemArg { offset: 0, align: 2, memory_index: 0 }));
+        instructions.push(Instruction::LocalGet(1));
+        instructions.push(Instruction::I32Load(MemArg { offset: 0, align: 2, memory_index: 0 }));
+        instructions.push(Instruction::I32Eq);
+        instructions.push(Instruction::LocalGet(8));
+        instructions.push(Instruction::I32Const(0));
+        instructions.push(Instruction::I32Ne);
+        instructions.push(Instruction::I32And);
+        instructions.push(Instruction::LocalGet(9));
+        instructions.push(Instruction::I32Const(0));
+        instructions.push(Instruction::I32Ne);
+        instructions.push(Instruction::I32And);
+        instructions.push(Instruction::LocalGet(8));
+        instructions.push(Instruction::LocalGet(9));
+        instructions.push(Instruction::I32Ne);
+        instructions.push(Instruction::I32And);
+        instructions.push(Instruction::If(wasm_encoder::BlockType::Result(wasm_encoder::ValType::I32)));
+        instructions.push(Instruction::I32Const(0)); // same length, hashes cached and differ: not contained
+        instructions.push(Instruction::Else);
+        instructions.extend(self.generate_forward_search());
+        instructions.push(Instruction::LocalGet(7));
+        instructions.push(Instruction::I32Const(0));
+        instructions.push(Instruction::I32GeS); // result >= 0
+        instructions.push(Instruction::End);
+        instructions
+    }
+
     pub fn generate_string_index_of(&self) -> Vec<Instruction> {
-        // Proper indexOf implementation using Boyer-Moore-like algorithm
-        // Parameters: string_ptr, search_ptr 
-        vec![
-            // Simplified version for testing - just return 1 (true)
-            Instruction::I32Const(1), // Return true
-        ]
+        // Parameters: string_ptr, search_ptr. Returns the first index where
+        // search occurs in string, or -1 if it isn't found.
+        let mut instructions = self.generate_forward_search();
+        instructions.push(Instruction::LocalGet(7));
+        instructions
     }
 
     pub fn generate_string_last_index_of(&self) -> Vec<Instruction> {
-        // Simplified implementation to avoid WASM validation issues
-        // Parameters: string_ptr, search_ptr
-        // Returns the last index where search_ptr is found in string_ptr, or -1 if not found
+        // Parameters: string_ptr, search_ptr. Returns the last index where
+        // search occurs in string, or -1 if it isn't found, by scanning
+        // candidate start positions from the end of the string backwards and
+        // taking the first match found.
+        //
+        // Locals: 2 = haystack length, 3 = needle length, 4 = candidate start
+        // position `i`, 5 = inner comparison index `j`, 6 = match flag,
+        // 7 = result index (-1 until a match is found).
         vec![
-            // For now, return a constant value to avoid complex local variable usage
-            // In a real implementation, this would search backwards through the string
-            Instruction::I32Const(5), // Placeholder: return index 5
+            Instruction::LocalGet(0),
+            Instruction::I32Load(MemArg { offset: 0, align: 2, memory_index: 0 }),
+            Instruction::LocalSet(2),
+            Instruction::LocalGet(1),
+            Instruction::I32Load(MemArg { offset: 0, align: 2, memory_index: 0 }),
+            Instruction::LocalSet(3),
+
+            Instruction::I32Const(-1),
+            Instruction::LocalSet(7), // result = -1
+
+            // An empty needle matches at the end of the string.
+            Instruction::LocalGet(3),
+            Instruction::I32Eqz,
+            Instruction::If(wasm_encoder::BlockType::Empty),
+                Instruction::LocalGet(2),
+                Instruction::LocalSet(7),
+            Instruction::Else,
+                // Only search if the needle can possibly fit.
+                Instruction::LocalGet(3),
+                Instruction::LocalGet(2),
+                Instruction::I32GtU,
+                Instruction::I32Eqz,
+                Instruction::If(wasm_encoder::BlockType::Empty),
+                    Instruction::LocalGet(2),
+                    Instruction::LocalGet(3),
+                    Instruction::I32Sub,
+                    Instruction::LocalSet(4), // i = hlen - nlen
+
+                    Instruction::Block(wasm_encoder::BlockType::Empty),
+                        Instruction::Loop(wasm_encoder::BlockType::Empty),
+                            Instruction::LocalGet(4),
+                            Instruction::I32Const(0),
+                            Instruction::I32LtS,
+                            Instruction::BrIf(1),
+                            Instruction::LocalGet(7),
+                            Instruction::I32Const(0),
+                            Instruction::I32GeS,
+                            Instruction::BrIf(1),
+
+                            Instruction::I32Const(1),
+                            Instruction::LocalSet(6), // match = true
+                            Instruction::I32Const(0),
+                            Instruction::LocalSet(5), // j = 0
+
+                            Instruction::Block(wasm_encoder::BlockType::Empty),
+                                Instruction::Loop(wasm_encoder::BlockType::Empty),
+                                    Instruction::LocalGet(5),
+                                    Instruction::LocalGet(3),
+                                    Instruction::I32GeU,
+                                    Instruction::BrIf(1),
+
+                                    Instruction::LocalGet(0),
+                                    Instruction::I32Const(16),
+                                    Instruction::I32Add,
+                                    Instruction::LocalGet(4),
+                                    Instruction::I32Add,
+                                    Instruction::LocalGet(5),
+                                    Instruction::I32Add,
+                                    Instruction::I32Load8U(MemArg { offset: 0, align: 0, memory_index: 0 }),
+                                    Instruction::LocalGet(1),
+                                    Instruction::I32Const(16),
+                                    Instruction::I32Add,
+                                    Instruction::LocalGet(5),
+                                    Instruction::I32Add,
+                                    Instruction::I32Load8U(MemArg { offset: 0, align: 0, memory_index: 0 }),
+                                    Instruction::I32Ne,
+                                    Instruction::If(wasm_encoder::BlockType::Empty),
+                                        Instruction::I32Const(0),
+                                        Instruction::LocalSet(6),
+                                    Instruction::End,
+
+                                    Instruction::LocalGet(5),
+                                    Instruction::I32Const(1),
+                                    Instruction::I32Add,
+                                    Instruction::LocalSet(5),
+                                    Instruction::Br(0),
+                                Instruction::End, // inner loop
+                            Instruction::End, // inner block
+
+                            Instruction::LocalGet(6),
+                            Instruction::If(wasm_encoder::BlockType::Empty),
+                                Instruction::LocalGet(4),
+                                Instruction::LocalSet(7), // result = i
+                            Instruction::End,
+
+                            Instruction::LocalGet(4),
+                            Instruction::I32Const(1),
+                            Instruction::I32Sub,
+                            Instruction::LocalSet(4), // i -= 1
+                            Instruction::Br(0),
+                        Instruction::End, // outer loop
+                    Instruction::End, // outer block
+                Instruction::End, // if nlen <= hlen
+            Instruction::End, // if nlen == 0
+
+            Instruction::LocalGet(7),
         ]
     }
 
@@ -665,129 +1475,1677 @@ impl StringOperations {
         ]
     }
 
-    pub fn generate_string_to_upper(&self) -> Vec<Instruction> {
-        // Simplified implementation to avoid WASM validation issues
-        // Parameters: string_ptr
-        // Returns a new string pointer with uppercase characters
-        vec![
-            // For now, return the original string pointer to avoid complex local variable usage
-            // In a real implementation, this would create a new string with uppercase characters
-            Instruction::LocalGet(0), // Return original string_ptr
-        ]
-    }
+    /// Maps the continuation byte of a 2-byte Latin-1 Supplement sequence
+    /// (lead byte 0xC3, codepoints U+00C0-U+00FF) between its upper- and
+    /// lowercase halves. The 0x20 codepoint shift used by ASCII case
+    /// folding lands entirely inside the continuation byte here too, since
+    /// both halves share the same lead byte - only 0xD7 (multiplication
+    /// sign) and 0xF7 (division sign) interrupt the otherwise-contiguous
+    /// ranges, so they're excluded rather than mapped.
+    ///
+    /// Expects local 6 to hold the continuation byte; pushes the mapped
+    /// byte (or the original, unchanged, outside the mapped ranges).
+    fn generate_latin1_supplement_cont_map(&self, lower_to_upper: bool) -> Vec<Instruction> {
+        let (lo1, hi1, lo2, hi2, shift): (i32, i32, i32, i32, Instruction) = if lower_to_upper {
+            (0xA0, 0xB6, 0xB8, 0xBE, Instruction::I32Sub)
+        } else {
+            (0x80, 0x96, 0x98, 0x9E, Instruction::I32Add)
+        };
 
-    pub fn generate_string_to_lower(&self) -> Vec<Instruction> {
-        // Simplified implementation to avoid WASM validation issues
-        // Parameters: string_ptr
-        // Returns a new string pointer with lowercase characters
         vec![
-            // For now, return the original string pointer to avoid complex local variable usage
-            // In a real implementation, this would create a new string with lowercase characters
-            Instruction::LocalGet(0), // Return original string_ptr
+            Instruction::LocalGet(6),
+            Instruction::I32Const(lo1),
+            Instruction::I32GeU,
+            Instruction::LocalGet(6),
+            Instruction::I32Const(hi1),
+            Instruction::I32LeU,
+            Instruction::I32And,
+            Instruction::LocalGet(6),
+            Instruction::I32Const(lo2),
+            Instruction::I32GeU,
+            Instruction::LocalGet(6),
+            Instruction::I32Const(hi2),
+            Instruction::I32LeU,
+            Instruction::I32And,
+            Instruction::I32Or,
+            Instruction::If(wasm_encoder::BlockType::Result(wasm_encoder::ValType::I32)),
+                Instruction::LocalGet(6),
+                Instruction::I32Const(0x20),
+                shift,
+            Instruction::Else,
+                Instruction::LocalGet(6),
+            Instruction::End,
         ]
     }
 
-    pub fn generate_string_trim(&self) -> Vec<Instruction> {
-        // SIMPLIFIED: String trim - just return the original string for now
-        // Parameters: string_ptr (0)
-        // Returns: original string pointer (no trimming performed)
-        vec![
-            Instruction::LocalGet(0), // Return the original string
-        ]
-    }
+    /// Maps the continuation byte of a 2-byte Latin Extended-A sequence
+    /// (lead byte 0xC4 or 0xC5, codepoints U+0100-U+017F) between upper-
+    /// and lowercase. Most of this block pairs an even codepoint (upper)
+    /// with the next odd codepoint (lower), so toggling case is just
+    /// `cont ^ 1` restricted to the parity that matches the requested
+    /// direction. A handful of codepoints don't follow that pairing at all
+    /// (e.g. U+0138, U+0149, U+0178, U+017F are case-singletons or pair
+    /// outside this block) - `exceptions` lists their continuation bytes so
+    /// they pass through unchanged instead of being toggled incorrectly.
+    ///
+    /// Expects local 6 to hold the continuation byte; pushes the mapped
+    /// byte (or the original, unchanged, for exceptions and the opposite
+    /// parity).
+    fn generate_extended_a_cont_map(&self, lower_to_upper: bool, exceptions: &[i32]) -> Vec<Instruction> {
+        let parity_target = if lower_to_upper { 1 } else { 0 };
+        let adjust: Instruction = if lower_to_upper { Instruction::I32Sub } else { Instruction::I32Add };
 
-    pub fn generate_string_trim_start(&self) -> Vec<Instruction> {
-        // Simplified implementation to avoid WASM validation issues
-        // Parameters: string_ptr
-        // Returns a new string pointer with leading whitespace removed
-        vec![
-            // For now, return the original string pointer to avoid complex local variable usage
-            // In a real implementation, this would create a new string with leading whitespace removed
-            Instruction::LocalGet(0), // Return original string_ptr
-        ]
-    }
+        let mut cond = vec![
+            Instruction::LocalGet(6),
+            Instruction::I32Const(1),
+            Instruction::I32And,
+            Instruction::I32Const(parity_target),
+            Instruction::I32Eq,
+        ];
+        for except in exceptions {
+            cond.push(Instruction::LocalGet(6));
+            cond.push(Instruction::I32Const(*except));
+            cond.push(Instruction::I32Ne);
+            cond.push(Instruction::I32And);
+        }
 
-    pub fn generate_string_trim_end(&self) -> Vec<Instruction> {
-        // Simplified implementation to avoid WASM validation issues
-        // Parameters: string_ptr
-        // Returns a new string pointer with trailing whitespace removed
-        vec![
-            // For now, return the original string pointer to avoid complex local variable usage
-            // In a real implementation, this would create a new string with trailing whitespace removed
-            Instruction::LocalGet(0), // Return original string_ptr
-        ]
+        let mut instructions = cond;
+        instructions.extend(vec![
+            Instruction::If(wasm_encoder::BlockType::Result(wasm_encoder::ValType::I32)),
+                Instruction::LocalGet(6),
+                Instruction::I32Const(1),
+                adjust,
+            Instruction::Else,
+                Instruction::LocalGet(6),
+            Instruction::End,
+        ]);
+        instructions
     }
 
-    pub fn generate_string_substring(&self) -> Vec<Instruction> {
-        // Simplified implementation to avoid WASM validation issues
-        // Parameters: string_ptr, start, end
-        // Returns a new string pointer with the substring
-        vec![
-            // For now, return the original string pointer to avoid complex local variable usage
-            // In a real implementation, this would create a new string with the specified substring
-            Instruction::LocalGet(0), // Return original string_ptr
-        ]
-    }
+    /// Case conversion shared by `to_upper`/`to_lower`. Copies every byte
+    /// unchanged except: ASCII letters in the relevant range, shifted by
+    /// 0x20 as before; and the continuation byte of a Latin-1 Supplement
+    /// or Latin Extended-A 2-byte sequence, case-folded via
+    /// `generate_latin1_supplement_cont_map`/`generate_extended_a_cont_map`.
+    /// All mapped ranges happen to re-encode at the same byte length as
+    /// their input, so - unlike `string_replace` - this can allocate
+    /// `src_len` up front and transform in place during a single copy
+    /// pass; a codepoint whose case mapping changes encoded length (e.g.
+    /// German ß expanding to "SS") would need a counting pass first, the
+    /// same way `string_replace` sizes its output before copying.
+    ///
+    /// Any other multibyte lead byte, or a continuation byte encountered
+    /// out of sequence, is copied through unchanged - this is ASCII and
+    /// Latin-1/Extended-A case folding, not full Unicode case folding.
+    ///
+    /// Parameters: string_ptr (0). Locals: 2 = length, 3 = copy index,
+    /// 4 = current (lead) byte, 5 = bytes consumed this step, 6 = next
+    /// byte (continuation), 7 = mapped continuation byte, 8 = dest pointer
+    /// (freshly allocated via the shared `env.malloc` import so each call
+    /// gets its own buffer instead of aliasing a fixed scratch address).
+    fn generate_case_convert(&self, malloc_idx: u32, lower_to_upper: bool) -> Vec<Instruction> {
+        let (range_lo, range_hi, shift): (i32, i32, Instruction) = if lower_to_upper {
+            (0x61, 0x7A, Instruction::I32Sub)
+        } else {
+            (0x41, 0x5A, Instruction::I32Add)
+        };
 
-    pub fn generate_string_replace(&self) -> Vec<Instruction> {
-        // SIMPLIFIED: String replace - just return the original string for now
-        // Parameters: string_ptr (0), old_ptr (1), new_ptr (2)
-        // Returns: original string pointer (no replacement performed)
-        vec![
-            Instruction::LocalGet(0), // Return the original string
-        ]
-    }
+        // Copies `lead` (local 4, already loaded) through to `dest[16+i]`
+        // unchanged, and sets advance = 1. Used by both the "not a 2-byte
+        // lead byte we fold" fallback and, inline, nowhere else - kept as a
+        // closure-shaped block of instructions purely to avoid repeating it.
+        let copy_lead_unchanged_advance_1 = vec![
+            Instruction::LocalGet(8),
+            Instruction::I32Const(16),
+            Instruction::I32Add,
+            Instruction::LocalGet(3),
+            Instruction::I32Add,
+            Instruction::LocalGet(4),
+            Instruction::I32Store8(MemArg { offset: 0, align: 0, memory_index: 0 }),
+            Instruction::I32Const(1),
+            Instruction::LocalSet(5), // advance = 1
+        ];
 
-    pub fn generate_string_replace_all(&self) -> Vec<Instruction> {
-        // Simplified implementation to avoid WASM validation issues
-        // Parameters: string_ptr, old_ptr, new_ptr
-        // Returns a new string pointer with all replacements
-        vec![
-            // For now, return the original string pointer to avoid complex local variable usage
-            // In a real implementation, this would create a new string with all replacements
-            Instruction::LocalGet(0), // Return original string_ptr
-        ]
-    }
+        // Handles a 2-byte sequence whose lead byte is `lead_byte`: loads the
+        // continuation byte into local 6, maps it via `cont_map`, writes both
+        // bytes to the destination, and sets advance = 2.
+        let fold_2byte = |lead_byte: i32, cont_map: Vec<Instruction>| -> Vec<Instruction> {
+            let mut instructions = vec![
+                Instruction::LocalGet(0),
+                Instruction::I32Const(16),
+                Instruction::I32Add,
+                Instruction::LocalGet(3),
+                Instruction::I32Add,
+                Instruction::I32Const(1),
+                Instruction::I32Add,
+                Instruction::I32Load8U(MemArg { offset: 0, align: 0, memory_index: 0 }),
+                Instruction::LocalSet(6), // next (continuation byte)
+            ];
+            instructions.extend(cont_map);
+            instructions.push(Instruction::LocalSet(7)); // mapped continuation byte
 
-    pub fn generate_string_pad_start(&self) -> Vec<Instruction> {
-        // Extremely simplified implementation to avoid WASM validation issues
-        // Parameters: string_ptr, target_length, pad_char
-        // Returns the original string pointer (no actual padding)
-        vec![
-            Instruction::LocalGet(0), // Return original string_ptr
-        ]
+            instructions.extend(vec![
+                Instruction::LocalGet(8),
+                Instruction::I32Const(16),
+                Instruction::I32Add,
+                Instruction::LocalGet(3),
+                Instruction::I32Add,
+                Instruction::I32Const(lead_byte),
+                Instruction::I32Store8(MemArg { offset: 0, align: 0, memory_index: 0 }),
+
+                Instruction::LocalGet(8),
+                Instruction::I32Const(16),
+                Instruction::I32Add,
+                Instruction::LocalGet(3),
+                Instruction::I32Add,
+                Instruction::I32Const(1),
+                Instruction::I32Add,
+                Instruction::LocalGet(7),
+                Instruction::I32Store8(MemArg { offset: 0, align: 0, memory_index: 0 }),
+
+                Instruction::I32Const(2),
+                Instruction::LocalSet(5), // advance = 2
+            ]);
+            instructions
+        };
+
+        // byte == lead_byte && i+1 < len
+        let is_lead_with_next = |lead_byte: i32| -> Vec<Instruction> {
+            vec![
+                Instruction::LocalGet(4),
+                Instruction::I32Const(lead_byte),
+                Instruction::I32Eq,
+                Instruction::LocalGet(3),
+                Instruction::I32Const(1),
+                Instruction::I32Add,
+                Instruction::LocalGet(2),
+                Instruction::I32LtU,
+                Instruction::I32And,
+            ]
+        };
+
+        let mut instructions = vec![
+            Instruction::LocalGet(0),
+            Instruction::I32Load(MemArg { offset: 0, align: 2, memory_index: 0 }),
+            Instruction::LocalSet(2), // length
+
+            Instruction::LocalGet(2),
+            Instruction::I32Const(16),
+            Instruction::I32Add,
+            Instruction::Call(malloc_idx),
+            Instruction::LocalSet(8), // dest = malloc(length + 16)
+
+            Instruction::LocalGet(8),
+            Instruction::LocalGet(2),
+            Instruction::I32Store(MemArg { offset: 0, align: 2, memory_index: 0 }), // length
+            Instruction::LocalGet(8),
+            Instruction::LocalGet(2),
+            Instruction::I32Store(MemArg { offset: 4, align: 2, memory_index: 0 }), // capacity = length
+
+            Instruction::I32Const(0),
+            Instruction::LocalSet(3), // i = 0
+            Instruction::Block(wasm_encoder::BlockType::Empty),
+                Instruction::Loop(wasm_encoder::BlockType::Empty),
+                    Instruction::LocalGet(3),
+                    Instruction::LocalGet(2),
+                    Instruction::I32GeU,
+                    Instruction::BrIf(1),
+
+                    Instruction::LocalGet(0),
+                    Instruction::I32Const(16),
+                    Instruction::I32Add,
+                    Instruction::LocalGet(3),
+                    Instruction::I32Add,
+                    Instruction::I32Load8U(MemArg { offset: 0, align: 0, memory_index: 0 }),
+                    Instruction::LocalSet(4), // lead byte
+
+                    Instruction::LocalGet(4),
+                    Instruction::I32Const(0x80),
+                    Instruction::I32LtU,
+                    Instruction::If(wasm_encoder::BlockType::Empty),
+        ];
+
+        // ASCII: shift in range, copy 1 byte.
+        instructions.extend(vec![
+                        Instruction::LocalGet(4),
+                        Instruction::I32Const(range_lo),
+                        Instruction::I32GeU,
+                        Instruction::LocalGet(4),
+                        Instruction::I32Const(range_hi),
+                        Instruction::I32LeU,
+                        Instruction::I32And,
+                        Instruction::If(wasm_encoder::BlockType::Empty),
+                            Instruction::LocalGet(4),
+                            Instruction::I32Const(0x20),
+                            shift,
+                            Instruction::LocalSet(4),
+                        Instruction::End,
+        ]);
+        instructions.extend(copy_lead_unchanged_advance_1.clone());
+        instructions.push(Instruction::Else);
+
+        // Latin-1 Supplement (lead 0xC3).
+        instructions.extend(is_lead_with_next(0xC3));
+        instructions.push(Instruction::If(wasm_encoder::BlockType::Empty));
+        instructions.extend(fold_2byte(0xC3, self.generate_latin1_supplement_cont_map(lower_to_upper)));
+        instructions.push(Instruction::Else);
+
+        // Latin Extended-A, first half (lead 0xC4).
+        instructions.extend(is_lead_with_next(0xC4));
+        instructions.push(Instruction::If(wasm_encoder::BlockType::Empty));
+        instructions.extend(fold_2byte(0xC4, self.generate_extended_a_cont_map(lower_to_upper, &[0xB8])));
+        instructions.push(Instruction::Else);
+
+        // Latin Extended-A, second half (lead 0xC5).
+        instructions.extend(is_lead_with_next(0xC5));
+        instructions.push(Instruction::If(wasm_encoder::BlockType::Empty));
+        instructions.extend(fold_2byte(0xC5, self.generate_extended_a_cont_map(lower_to_upper, &[0x89, 0xB8, 0xBF])));
+        instructions.push(Instruction::Else);
+
+        // Anything else: pass the lead byte through unchanged.
+        instructions.extend(copy_lead_unchanged_advance_1);
+
+        instructions.extend(vec![
+                        Instruction::End, // if 0xC5
+                    Instruction::End, // if 0xC4
+                Instruction::End, // if 0xC3
+            Instruction::End, // if ASCII
+
+                    Instruction::LocalGet(3),
+                    Instruction::LocalGet(5),
+                    Instruction::I32Add,
+                    Instruction::LocalSet(3),
+                    Instruction::Br(0),
+                Instruction::End,
+            Instruction::End,
+
+            Instruction::LocalGet(8),
+        ]);
+        instructions
     }
 
-    pub fn generate_string_pad_end(&self) -> Vec<Instruction> {
-        // Simplified implementation to avoid WASM validation issues
-        // Parameters: string_ptr, target_length, pad_char
-        // Returns a new string pointer with padding at end
-        vec![
-            // For now, return the original string pointer to avoid complex local variable usage
-            // In a real implementation, this would create a new string with padding at the end
-            Instruction::LocalGet(0), // Return original string_ptr
-        ]
+    pub fn generate_string_to_upper(&self, malloc_idx: u32) -> Vec<Instruction> {
+        // Parameters: string_ptr. Returns a new string pointer with a-z shifted to A-Z.
+        self.generate_case_convert(malloc_idx, true)
     }
 
-    pub fn generate_string_char_at(&self) -> Vec<Instruction> {
-        // Simplified implementation to avoid WASM validation issues
-        // Parameters: string_ptr, index
-        // Returns the character at the specified index
-        vec![
-            // For now, return a constant character code to avoid complex local variable usage
-            // In a real implementation, this would load the character at the specified index
-            Instruction::I32Const(65), // Return 'A' character code
-        ]
+    pub fn generate_string_to_lower(&self, malloc_idx: u32) -> Vec<Instruction> {
+        // Parameters: string_ptr. Returns a new string pointer with A-Z shifted to a-z.
+        self.generate_case_convert(malloc_idx, false)
     }
 
-    pub fn generate_string_char_code_at(&self) -> Vec<Instruction> {
-        // Simplified implementation to avoid WASM validation issues
-        // Parameters: string_ptr, index
-        // Returns the character code at the specified index
+    /// Pushes `1` if the i32 in `local_idx` is one of the ASCII whitespace
+    /// bytes recognised by the trim family (space, tab, LF, VT, FF, CR), `0`
+    /// otherwise. Leaves exactly one value on the stack.
+    fn push_is_whitespace(&self, local_idx: u32) -> Vec<Instruction> {
         vec![
-            // For now, return a constant character code to avoid complex local variable usage
-            // In a real implementation, this would load the character code at the specified index
-            Instruction::I32Const(65), // Return 'A' character code
+            Instruction::LocalGet(local_idx),
+            Instruction::I32Const(0x20),
+            Instruction::I32Eq,
+            Instruction::LocalGet(local_idx),
+            Instruction::I32Const(0x09),
+            Instruction::I32Eq,
+            Instruction::I32Or,
+            Instruction::LocalGet(local_idx),
+            Instruction::I32Const(0x0A),
+            Instruction::I32Eq,
+            Instruction::I32Or,
+            Instruction::LocalGet(local_idx),
+            Instruction::I32Const(0x0B),
+            Instruction::I32Eq,
+            Instruction::I32Or,
+            Instruction::LocalGet(local_idx),
+            Instruction::I32Const(0x0C),
+            Instruction::I32Eq,
+            Instruction::I32Or,
+            Instruction::LocalGet(local_idx),
+            Instruction::I32Const(0x0D),
+            Instruction::I32Eq,
+            Instruction::I32Or,
+        ]
+    }
+
+    /// Trims both leading and trailing ASCII whitespace, allocating a new
+    /// string for the surviving byte span (trimming either end can move
+    /// where the data starts, so unlike `trim_end` this can't reuse the
+    /// input's storage).
+    ///
+    /// Parameters: string_ptr (0). Locals: 2 = length, 3 = start, 4 = end,
+    /// 5 = byte under inspection, 6 = new length, 7 = copy index, 8 = dest
+    /// pointer (freshly allocated via the shared `env.malloc` import so each
+    /// call gets its own buffer instead of aliasing a fixed scratch address).
+    pub fn generate_string_trim(&self, malloc_idx: u32) -> Vec<Instruction> {
+        let mut instructions = vec![
+            Instruction::LocalGet(0),
+            Instruction::I32Load(MemArg { offset: 0, align: 2, memory_index: 0 }),
+            Instruction::LocalSet(2), // length
+
+            Instruction::I32Const(0),
+            Instruction::LocalSet(3), // start = 0
+            Instruction::Block(wasm_encoder::BlockType::Empty),
+                Instruction::Loop(wasm_encoder::BlockType::Empty),
+                    Instruction::LocalGet(3),
+                    Instruction::LocalGet(2),
+                    Instruction::I32GeU,
+                    Instruction::BrIf(1),
+
+                    Instruction::LocalGet(0),
+                    Instruction::I32Const(16),
+                    Instruction::I32Add,
+                    Instruction::LocalGet(3),
+                    Instruction::I32Add,
+                    Instruction::I32Load8U(MemArg { offset: 0, align: 0, memory_index: 0 }),
+                    Instruction::LocalSet(5),
+        ];
+        instructions.extend(self.push_is_whitespace(5));
+        instructions.push(Instruction::I32Eqz);
+        instructions.push(Instruction::BrIf(1));
+        instructions.extend(vec![
+                    Instruction::LocalGet(3),
+                    Instruction::I32Const(1),
+                    Instruction::I32Add,
+                    Instruction::LocalSet(3),
+                    Instruction::Br(0),
+                Instruction::End,
+            Instruction::End,
+
+            Instruction::LocalGet(2),
+            Instruction::LocalSet(4), // end = length
+            Instruction::Block(wasm_encoder::BlockType::Empty),
+                Instruction::Loop(wasm_encoder::BlockType::Empty),
+                    Instruction::LocalGet(4),
+                    Instruction::LocalGet(3),
+                    Instruction::I32LeU,
+                    Instruction::BrIf(1),
+
+                    Instruction::LocalGet(0),
+                    Instruction::I32Const(16),
+                    Instruction::I32Add,
+                    Instruction::LocalGet(4),
+                    Instruction::I32Const(1),
+                    Instruction::I32Sub,
+                    Instruction::I32Add,
+                    Instruction::I32Load8U(MemArg { offset: 0, align: 0, memory_index: 0 }),
+                    Instruction::LocalSet(5),
+        ]);
+        instructions.extend(self.push_is_whitespace(5));
+        instructions.push(Instruction::I32Eqz);
+        instructions.push(Instruction::BrIf(1));
+        instructions.extend(vec![
+                    Instruction::LocalGet(4),
+                    Instruction::I32Const(1),
+                    Instruction::I32Sub,
+                    Instruction::LocalSet(4),
+                    Instruction::Br(0),
+                Instruction::End,
+            Instruction::End,
+
+            Instruction::LocalGet(4),
+            Instruction::LocalGet(3),
+            Instruction::I32Sub,
+            Instruction::LocalSet(6), // new_len = end - start
+
+            Instruction::LocalGet(6),
+            Instruction::I32Const(16),
+            Instruction::I32Add,
+            Instruction::Call(malloc_idx),
+            Instruction::LocalSet(8), // dest = malloc(new_len + 16)
+
+            Instruction::LocalGet(8),
+            Instruction::LocalGet(6),
+            Instruction::I32Store(MemArg { offset: 0, align: 2, memory_index: 0 }), // length
+            Instruction::LocalGet(8),
+            Instruction::LocalGet(6),
+            Instruction::I32Store(MemArg { offset: 4, align: 2, memory_index: 0 }), // capacity = length
+
+            Instruction::I32Const(0),
+            Instruction::LocalSet(7), // i = 0
+            Instruction::Block(wasm_encoder::BlockType::Empty),
+                Instruction::Loop(wasm_encoder::BlockType::Empty),
+                    Instruction::LocalGet(7),
+                    Instruction::LocalGet(6),
+                    Instruction::I32GeU,
+                    Instruction::BrIf(1),
+
+                    Instruction::LocalGet(8),
+                    Instruction::I32Const(16),
+                    Instruction::I32Add,
+                    Instruction::LocalGet(7),
+                    Instruction::I32Add,
+
+                    Instruction::LocalGet(0),
+                    Instruction::I32Const(16),
+                    Instruction::I32Add,
+                    Instruction::LocalGet(3),
+                    Instruction::I32Add,
+                    Instruction::LocalGet(7),
+                    Instruction::I32Add,
+                    Instruction::I32Load8U(MemArg { offset: 0, align: 0, memory_index: 0 }),
+                    Instruction::I32Store8(MemArg { offset: 0, align: 0, memory_index: 0 }),
+
+                    Instruction::LocalGet(7),
+                    Instruction::I32Const(1),
+                    Instruction::I32Add,
+                    Instruction::LocalSet(7),
+                    Instruction::Br(0),
+                Instruction::End,
+            Instruction::End,
+
+            Instruction::LocalGet(8),
+        ]);
+        instructions
+    }
+
+    /// Trims leading ASCII whitespace, allocating a new string for the
+    /// surviving byte span - leading trim shifts where the data starts, so
+    /// it can't reuse the input's storage the way `trim_end` can.
+    ///
+    /// Parameters: string_ptr (0). Locals: 2 = length, 3 = start,
+    /// 4 = byte under inspection, 5 = new length, 6 = copy index, 7 = dest
+    /// pointer (freshly allocated via the shared `env.malloc` import).
+    pub fn generate_string_trim_start(&self, malloc_idx: u32) -> Vec<Instruction> {
+        let mut instructions = vec![
+            Instruction::LocalGet(0),
+            Instruction::I32Load(MemArg { offset: 0, align: 2, memory_index: 0 }),
+            Instruction::LocalSet(2), // length
+
+            Instruction::I32Const(0),
+            Instruction::LocalSet(3), // start = 0
+            Instruction::Block(wasm_encoder::BlockType::Empty),
+                Instruction::Loop(wasm_encoder::BlockType::Empty),
+                    Instruction::LocalGet(3),
+                    Instruction::LocalGet(2),
+                    Instruction::I32GeU,
+                    Instruction::BrIf(1),
+
+                    Instruction::LocalGet(0),
+                    Instruction::I32Const(16),
+                    Instruction::I32Add,
+                    Instruction::LocalGet(3),
+                    Instruction::I32Add,
+                    Instruction::I32Load8U(MemArg { offset: 0, align: 0, memory_index: 0 }),
+                    Instruction::LocalSet(4),
+        ];
+        instructions.extend(self.push_is_whitespace(4));
+        instructions.push(Instruction::I32Eqz);
+        instructions.push(Instruction::BrIf(1));
+        instructions.extend(vec![
+                    Instruction::LocalGet(3),
+                    Instruction::I32Const(1),
+                    Instruction::I32Add,
+                    Instruction::LocalSet(3),
+                    Instruction::Br(0),
+                Instruction::End,
+            Instruction::End,
+
+            Instruction::LocalGet(2),
+            Instruction::LocalGet(3),
+            Instruction::I32Sub,
+            Instruction::LocalSet(5), // new_len = length - start
+
+            Instruction::LocalGet(5),
+            Instruction::I32Const(16),
+            Instruction::I32Add,
+            Instruction::Call(malloc_idx),
+            Instruction::LocalSet(7), // dest = malloc(new_len + 16)
+
+            Instruction::LocalGet(7),
+            Instruction::LocalGet(5),
+            Instruction::I32Store(MemArg { offset: 0, align: 2, memory_index: 0 }), // length
+            Instruction::LocalGet(7),
+            Instruction::LocalGet(5),
+            Instruction::I32Store(MemArg { offset: 4, align: 2, memory_index: 0 }), // capacity = length
+
+            Instruction::I32Const(0),
+            Instruction::LocalSet(6), // i = 0
+            Instruction::Block(wasm_encoder::BlockType::Empty),
+                Instruction::Loop(wasm_encoder::BlockType::Empty),
+                    Instruction::LocalGet(6),
+                    Instruction::LocalGet(5),
+                    Instruction::I32GeU,
+                    Instruction::BrIf(1),
+
+                    Instruction::LocalGet(7),
+                    Instruction::I32Const(16),
+                    Instruction::I32Add,
+                    Instruction::LocalGet(6),
+                    Instruction::I32Add,
+
+                    Instruction::LocalGet(0),
+                    Instruction::I32Const(16),
+                    Instruction::I32Add,
+                    Instruction::LocalGet(3),
+                    Instruction::I32Add,
+                    Instruction::LocalGet(6),
+                    Instruction::I32Add,
+                    Instruction::I32Load8U(MemArg { offset: 0, align: 0, memory_index: 0 }),
+                    Instruction::I32Store8(MemArg { offset: 0, align: 0, memory_index: 0 }),
+
+                    Instruction::LocalGet(6),
+                    Instruction::I32Const(1),
+                    Instruction::I32Add,
+                    Instruction::LocalSet(6),
+                    Instruction::Br(0),
+                Instruction::End,
+            Instruction::End,
+
+            Instruction::LocalGet(7),
+        ]);
+        instructions
+    }
+
+    /// Trims trailing ASCII whitespace in place: since trailing trim never
+    /// moves surviving bytes, this just finds the shorter length and writes
+    /// it back into the existing header, returning the same pointer rather
+    /// than paying for a second allocation and copy.
+    ///
+    /// Parameters: string_ptr (0). Locals: 2 = length, 3 = end (shrinking),
+    /// 4 = byte under inspection.
+    pub fn generate_string_trim_end(&self) -> Vec<Instruction> {
+        let mut instructions = vec![
+            Instruction::LocalGet(0),
+            Instruction::I32Load(MemArg { offset: 0, align: 2, memory_index: 0 }),
+            Instruction::LocalSet(2), // length
+
+            Instruction::LocalGet(2),
+            Instruction::LocalSet(3), // end = length
+            Instruction::Block(wasm_encoder::BlockType::Empty),
+                Instruction::Loop(wasm_encoder::BlockType::Empty),
+                    Instruction::LocalGet(3),
+                    Instruction::I32Eqz,
+                    Instruction::BrIf(1),
+
+                    Instruction::LocalGet(0),
+                    Instruction::I32Const(16),
+                    Instruction::I32Add,
+                    Instruction::LocalGet(3),
+                    Instruction::I32Const(1),
+                    Instruction::I32Sub,
+                    Instruction::I32Add,
+                    Instruction::I32Load8U(MemArg { offset: 0, align: 0, memory_index: 0 }),
+                    Instruction::LocalSet(4),
+        ];
+        instructions.extend(self.push_is_whitespace(4));
+        instructions.push(Instruction::I32Eqz);
+        instructions.push(Instruction::BrIf(1));
+        instructions.extend(vec![
+                    Instruction::LocalGet(3),
+                    Instruction::I32Const(1),
+                    Instruction::I32Sub,
+                    Instruction::LocalSet(3),
+                    Instruction::Br(0),
+                Instruction::End,
+            Instruction::End,
+
+            Instruction::LocalGet(0),
+            Instruction::LocalGet(3),
+            Instruction::I32Store(MemArg { offset: 0, align: 2, memory_index: 0 }),
+
+            Instruction::LocalGet(0), // same pointer, shorter length
+        ]);
+        instructions
+    }
+
+    /// Scans forward from the start of `str_local`'s data counting UTF-8
+    /// scalars until `target_local` codepoints have been consumed (or the
+    /// data runs out), leaving the resulting byte offset in `out_local`.
+    /// Malformed leading bytes are defensively treated as single-byte
+    /// sequences rather than aborting, since this is only ever used to find
+    /// a byte offset for character-indexed APIs like substring - the string
+    /// itself was already validated when it was created.
+    fn generate_char_offset_scan(
+        &self,
+        str_local: u32,
+        len_local: u32,
+        target_local: u32,
+        out_local: u32,
+        pos_local: u32,
+        cp_local: u32,
+        lead_local: u32,
+        seqlen_local: u32,
+    ) -> Vec<Instruction> {
+        vec![
+            Instruction::I32Const(0),
+            Instruction::LocalSet(pos_local),
+            Instruction::I32Const(0),
+            Instruction::LocalSet(cp_local),
+
+            Instruction::Block(wasm_encoder::BlockType::Empty),
+                Instruction::Loop(wasm_encoder::BlockType::Empty),
+                    Instruction::LocalGet(pos_local),
+                    Instruction::LocalGet(len_local),
+                    Instruction::I32GeU,
+                    Instruction::LocalGet(cp_local),
+                    Instruction::LocalGet(target_local),
+                    Instruction::I32GeU,
+                    Instruction::I32Or,
+                    Instruction::BrIf(1),
+
+                    Instruction::LocalGet(str_local),
+                    Instruction::I32Const(16),
+                    Instruction::I32Add,
+                    Instruction::LocalGet(pos_local),
+                    Instruction::I32Add,
+                    Instruction::I32Load8U(MemArg { offset: 0, align: 0, memory_index: 0 }),
+                    Instruction::LocalSet(lead_local),
+
+                    Instruction::LocalGet(lead_local),
+                    Instruction::I32Const(0x80),
+                    Instruction::I32And,
+                    Instruction::I32Eqz,
+                    Instruction::If(wasm_encoder::BlockType::Empty),
+                        Instruction::I32Const(1),
+                        Instruction::LocalSet(seqlen_local),
+                    Instruction::Else,
+                        Instruction::LocalGet(lead_local),
+                        Instruction::I32Const(0xE0),
+                        Instruction::I32And,
+                        Instruction::I32Const(0xC0),
+                        Instruction::I32Eq,
+                        Instruction::If(wasm_encoder::BlockType::Empty),
+                            Instruction::I32Const(2),
+                            Instruction::LocalSet(seqlen_local),
+                        Instruction::Else,
+                            Instruction::LocalGet(lead_local),
+                            Instruction::I32Const(0xF0),
+                            Instruction::I32And,
+                            Instruction::I32Const(0xE0),
+                            Instruction::I32Eq,
+                            Instruction::If(wasm_encoder::BlockType::Empty),
+                                Instruction::I32Const(3),
+                                Instruction::LocalSet(seqlen_local),
+                            Instruction::Else,
+                                Instruction::LocalGet(lead_local),
+                                Instruction::I32Const(0xF8),
+                                Instruction::I32And,
+                                Instruction::I32Const(0xF0),
+                                Instruction::I32Eq,
+                                Instruction::If(wasm_encoder::BlockType::Empty),
+                                    Instruction::I32Const(4),
+                                    Instruction::LocalSet(seqlen_local),
+                                Instruction::Else,
+                                    Instruction::I32Const(1), // malformed: advance defensively
+                                    Instruction::LocalSet(seqlen_local),
+                                Instruction::End,
+                            Instruction::End,
+                        Instruction::End,
+                    Instruction::End,
+
+                    // Clamp so a truncated trailing sequence can't push pos past len.
+                    Instruction::LocalGet(pos_local),
+                    Instruction::LocalGet(seqlen_local),
+                    Instruction::I32Add,
+                    Instruction::LocalGet(len_local),
+                    Instruction::I32GtU,
+                    Instruction::If(wasm_encoder::BlockType::Empty),
+                        Instruction::LocalGet(len_local),
+                        Instruction::LocalGet(pos_local),
+                        Instruction::I32Sub,
+                        Instruction::LocalSet(seqlen_local),
+                    Instruction::End,
+
+                    Instruction::LocalGet(pos_local),
+                    Instruction::LocalGet(seqlen_local),
+                    Instruction::I32Add,
+                    Instruction::LocalSet(pos_local),
+                    Instruction::LocalGet(cp_local),
+                    Instruction::I32Const(1),
+                    Instruction::I32Add,
+                    Instruction::LocalSet(cp_local),
+                    Instruction::Br(0),
+                Instruction::End,
+            Instruction::End,
+
+            Instruction::LocalGet(pos_local),
+            Instruction::LocalSet(out_local),
+        ]
+    }
+
+    /// Extracts the character range `[start, end)` (character, not byte,
+    /// offsets) as a new string. Negative bounds clamp to 0, `start > end`
+    /// (after clamping) yields an empty string, and both bounds naturally
+    /// clamp to the string's length since `generate_char_offset_scan` stops
+    /// at the end of the data.
+    ///
+    /// Parameters: string_ptr (0), start (1), end (2). Locals: 3 = byte
+    /// length, 4 = clamped start char index, 5 = clamped end char index,
+    /// 6 = start byte offset, 7 = end byte offset, 8 = new byte length,
+    /// 9 = copy index, 10-13 = scratch locals for `generate_char_offset_scan`,
+    /// 14 = dest pointer (freshly allocated via the shared `env.malloc`
+    /// import so each call gets its own buffer instead of aliasing a fixed
+    /// scratch address).
+    pub fn generate_string_substring(&self, malloc_idx: u32) -> Vec<Instruction> {
+        let mut instructions = vec![
+            Instruction::LocalGet(0),
+            Instruction::I32Load(MemArg { offset: 0, align: 2, memory_index: 0 }),
+            Instruction::LocalSet(3), // byte length
+
+            Instruction::LocalGet(1),
+            Instruction::I32Const(0),
+            Instruction::I32LtS,
+            Instruction::If(wasm_encoder::BlockType::Empty),
+                Instruction::I32Const(0),
+                Instruction::LocalSet(1),
+            Instruction::End,
+            Instruction::LocalGet(1),
+            Instruction::LocalSet(4), // start_idx = max(start, 0)
+
+            Instruction::LocalGet(2),
+            Instruction::I32Const(0),
+            Instruction::I32LtS,
+            Instruction::If(wasm_encoder::BlockType::Empty),
+                Instruction::I32Const(0),
+                Instruction::LocalSet(2),
+            Instruction::End,
+            Instruction::LocalGet(2),
+            Instruction::LocalSet(5), // end_idx = max(end, 0)
+
+            Instruction::LocalGet(4),
+            Instruction::LocalGet(5),
+            Instruction::I32GtS,
+            Instruction::If(wasm_encoder::BlockType::Empty),
+                Instruction::LocalGet(4),
+                Instruction::LocalSet(5), // start > end: empty range
+            Instruction::End,
+        ];
+
+        instructions.extend(self.generate_char_offset_scan(0, 3, 4, 6, 10, 11, 12, 13));
+        instructions.extend(self.generate_char_offset_scan(0, 3, 5, 7, 10, 11, 12, 13));
+
+        instructions.extend(vec![
+            Instruction::LocalGet(7),
+            Instruction::LocalGet(6),
+            Instruction::I32Sub,
+            Instruction::LocalSet(8), // new_len = end_byte - start_byte
+
+            Instruction::LocalGet(8),
+            Instruction::I32Const(16),
+            Instruction::I32Add,
+            Instruction::Call(malloc_idx),
+            Instruction::LocalSet(14), // dest = malloc(new_len + 16)
+
+            Instruction::LocalGet(14),
+            Instruction::LocalGet(8),
+            Instruction::I32Store(MemArg { offset: 0, align: 2, memory_index: 0 }), // length
+            Instruction::LocalGet(14),
+            Instruction::LocalGet(8),
+            Instruction::I32Store(MemArg { offset: 4, align: 2, memory_index: 0 }), // capacity = length
+
+            Instruction::I32Const(0),
+            Instruction::LocalSet(9), // i = 0
+            Instruction::Block(wasm_encoder::BlockType::Empty),
+                Instruction::Loop(wasm_encoder::BlockType::Empty),
+                    Instruction::LocalGet(9),
+                    Instruction::LocalGet(8),
+                    Instruction::I32GeU,
+                    Instruction::BrIf(1),
+
+                    Instruction::LocalGet(14),
+                    Instruction::I32Const(16),
+                    Instruction::I32Add,
+                    Instruction::LocalGet(9),
+                    Instruction::I32Add,
+
+                    Instruction::LocalGet(0),
+                    Instruction::I32Const(16),
+                    Instruction::I32Add,
+                    Instruction::LocalGet(6),
+                    Instruction::I32Add,
+                    Instruction::LocalGet(9),
+                    Instruction::I32Add,
+                    Instruction::I32Load8U(MemArg { offset: 0, align: 0, memory_index: 0 }),
+                    Instruction::I32Store8(MemArg { offset: 0, align: 0, memory_index: 0 }),
+
+                    Instruction::LocalGet(9),
+                    Instruction::I32Const(1),
+                    Instruction::I32Add,
+                    Instruction::LocalSet(9),
+                    Instruction::Br(0),
+                Instruction::End,
+            Instruction::End,
+
+            Instruction::LocalGet(14),
+        ]);
+        instructions
+    }
+
+    /// Two-pass substitution shared by `replace` (stop after the first
+    /// match) and `replace_all` (every non-overlapping match). Pass one
+    /// counts matches to size the exact output buffer
+    /// (`hlen + count * (newlen - oldlen)`), pass two re-walks the haystack
+    /// copying bytes and splicing in `new` at each matched site. An empty
+    /// `old` returns the haystack unchanged rather than looping forever.
+    ///
+    /// Parameters: haystack_ptr (0), old_ptr (1), new_ptr (2). Locals:
+    /// 3 = haystack length, 4 = old length, 5 = new length, 6 = pass-one
+    /// scan position, 7 = comparison index, 8 = match flag, 9 = match
+    /// count, 10 = output length, 11 = pass-two write position, 12 =
+    /// pass-two scan position, 13 = matched-here flag, 14 = byte-copy
+    /// index, 15 = replacements done so far (caps `replace` at one), 16 =
+    /// dest pointer (freshly allocated via the shared `env.malloc` import
+    /// once the exact output length is known, so each call gets its own
+    /// buffer instead of aliasing a fixed scratch address).
+    fn generate_string_replace_impl(&self, malloc_idx: u32, replace_all: bool) -> Vec<Instruction> {
+        let mut instructions = vec![
+            Instruction::LocalGet(0),
+            Instruction::I32Load(MemArg { offset: 0, align: 2, memory_index: 0 }),
+            Instruction::LocalSet(3), // hlen
+            Instruction::LocalGet(1),
+            Instruction::I32Load(MemArg { offset: 0, align: 2, memory_index: 0 }),
+            Instruction::LocalSet(4), // oldlen
+            Instruction::LocalGet(2),
+            Instruction::I32Load(MemArg { offset: 0, align: 2, memory_index: 0 }),
+            Instruction::LocalSet(5), // newlen
+
+            Instruction::LocalGet(4),
+            Instruction::I32Eqz,
+            Instruction::If(wasm_encoder::BlockType::Result(wasm_encoder::ValType::I32)),
+                Instruction::LocalGet(0), // empty old: avoid an infinite loop, return unchanged
+            Instruction::Else,
+                // Pass one: count non-overlapping matches.
+                Instruction::I32Const(0),
+                Instruction::LocalSet(6), // i = 0
+                Instruction::I32Const(0),
+                Instruction::LocalSet(9), // count = 0
+                Instruction::Block(wasm_encoder::BlockType::Empty),
+                    Instruction::Loop(wasm_encoder::BlockType::Empty),
+                        Instruction::LocalGet(6),
+                        Instruction::LocalGet(4),
+                        Instruction::I32Add,
+                        Instruction::LocalGet(3),
+                        Instruction::I32GtU,
+                        Instruction::BrIf(1),
+        ];
+        if !replace_all {
+            instructions.push(Instruction::LocalGet(9));
+            instructions.push(Instruction::I32Const(1));
+            instructions.push(Instruction::I32GeS);
+            instructions.push(Instruction::BrIf(1));
+        }
+        instructions.extend(vec![
+                        Instruction::I32Const(1),
+                        Instruction::LocalSet(8), // match = true
+                        Instruction::I32Const(0),
+                        Instruction::LocalSet(7), // j = 0
+                        Instruction::Block(wasm_encoder::BlockType::Empty),
+                            Instruction::Loop(wasm_encoder::BlockType::Empty),
+                                Instruction::LocalGet(7),
+                                Instruction::LocalGet(4),
+                                Instruction::I32GeU,
+                                Instruction::BrIf(1),
+
+                                Instruction::LocalGet(0),
+                                Instruction::I32Const(16),
+                                Instruction::I32Add,
+                                Instruction::LocalGet(6),
+                                Instruction::I32Add,
+                                Instruction::LocalGet(7),
+                                Instruction::I32Add,
+                                Instruction::I32Load8U(MemArg { offset: 0, align: 0, memory_index: 0 }),
+                                Instruction::LocalGet(1),
+                                Instruction::I32Const(16),
+                                Instruction::I32Add,
+                                Instruction::LocalGet(7),
+                                Instruction::I32Add,
+                                Instruction::I32Load8U(MemArg { offset: 0, align: 0, memory_index: 0 }),
+                                Instruction::I32Ne,
+                                Instruction::If(wasm_encoder::BlockType::Empty),
+                                    Instruction::I32Const(0),
+                                    Instruction::LocalSet(8),
+                                Instruction::End,
+
+                                Instruction::LocalGet(7),
+                                Instruction::I32Const(1),
+                                Instruction::I32Add,
+                                Instruction::LocalSet(7),
+                                Instruction::Br(0),
+                            Instruction::End,
+                        Instruction::End,
+
+                        Instruction::LocalGet(8),
+                        Instruction::If(wasm_encoder::BlockType::Empty),
+                            Instruction::LocalGet(9),
+                            Instruction::I32Const(1),
+                            Instruction::I32Add,
+                            Instruction::LocalSet(9), // count += 1
+                            Instruction::LocalGet(6),
+                            Instruction::LocalGet(4),
+                            Instruction::I32Add,
+                            Instruction::LocalSet(6), // i += oldlen (skip past the match)
+                        Instruction::Else,
+                            Instruction::LocalGet(6),
+                            Instruction::I32Const(1),
+                            Instruction::I32Add,
+                            Instruction::LocalSet(6), // i += 1
+                        Instruction::End,
+                        Instruction::Br(0),
+                    Instruction::End,
+                Instruction::End,
+
+                // output_len = hlen + count * (newlen - oldlen)
+                Instruction::LocalGet(3),
+                Instruction::LocalGet(9),
+                Instruction::LocalGet(5),
+                Instruction::LocalGet(4),
+                Instruction::I32Sub,
+                Instruction::I32Mul,
+                Instruction::I32Add,
+                Instruction::LocalSet(10),
+
+                Instruction::LocalGet(10),
+                Instruction::I32Const(16),
+                Instruction::I32Add,
+                Instruction::Call(malloc_idx),
+                Instruction::LocalSet(16), // dest = malloc(output_len + 16)
+
+                Instruction::LocalGet(16),
+                Instruction::LocalGet(10),
+                Instruction::I32Store(MemArg { offset: 0, align: 2, memory_index: 0 }), // length
+                Instruction::LocalGet(16),
+                Instruction::LocalGet(10),
+                Instruction::I32Store(MemArg { offset: 4, align: 2, memory_index: 0 }), // capacity = length
+
+                // Pass two: re-walk the haystack, splicing in `new` at matches.
+                Instruction::I32Const(0),
+                Instruction::LocalSet(12), // i2 = 0
+                Instruction::I32Const(0),
+                Instruction::LocalSet(11), // out = 0
+                Instruction::I32Const(0),
+                Instruction::LocalSet(15), // replaced = 0
+                Instruction::Block(wasm_encoder::BlockType::Empty),
+                    Instruction::Loop(wasm_encoder::BlockType::Empty),
+                        Instruction::LocalGet(12),
+                        Instruction::LocalGet(3),
+                        Instruction::I32GeU,
+                        Instruction::BrIf(1),
+
+                        Instruction::I32Const(0),
+                        Instruction::LocalSet(13), // matched = false
+
+                        Instruction::LocalGet(12),
+                        Instruction::LocalGet(4),
+                        Instruction::I32Add,
+                        Instruction::LocalGet(3),
+                        Instruction::I32LeU,
+        ]);
+        if !replace_all {
+            instructions.push(Instruction::LocalGet(15));
+            instructions.push(Instruction::I32Const(1));
+            instructions.push(Instruction::I32LtS);
+            instructions.push(Instruction::I32And);
+        }
+        instructions.extend(vec![
+                        Instruction::If(wasm_encoder::BlockType::Empty),
+                            Instruction::I32Const(1),
+                            Instruction::LocalSet(8),
+                            Instruction::I32Const(0),
+                            Instruction::LocalSet(7), // j = 0
+                            Instruction::Block(wasm_encoder::BlockType::Empty),
+                                Instruction::Loop(wasm_encoder::BlockType::Empty),
+                                    Instruction::LocalGet(7),
+                                    Instruction::LocalGet(4),
+                                    Instruction::I32GeU,
+                                    Instruction::BrIf(1),
+
+                                    Instruction::LocalGet(0),
+                                    Instruction::I32Const(16),
+                                    Instruction::I32Add,
+                                    Instruction::LocalGet(12),
+                                    Instruction::I32Add,
+                                    Instruction::LocalGet(7),
+                                    Instruction::I32Add,
+                                    Instruction::I32Load8U(MemArg { offset: 0, align: 0, memory_index: 0 }),
+                                    Instruction::LocalGet(1),
+                                    Instruction::I32Const(16),
+                                    Instruction::I32Add,
+                                    Instruction::LocalGet(7),
+                                    Instruction::I32Add,
+                                    Instruction::I32Load8U(MemArg { offset: 0, align: 0, memory_index: 0 }),
+                                    Instruction::I32Ne,
+                                    Instruction::If(wasm_encoder::BlockType::Empty),
+                                        Instruction::I32Const(0),
+                                        Instruction::LocalSet(8),
+                                    Instruction::End,
+
+                                    Instruction::LocalGet(7),
+                                    Instruction::I32Const(1),
+                                    Instruction::I32Add,
+                                    Instruction::LocalSet(7),
+                                    Instruction::Br(0),
+                                Instruction::End,
+                            Instruction::End,
+                            Instruction::LocalGet(8),
+                            Instruction::LocalSet(13), // matched = comparison result
+                        Instruction::End,
+
+                        Instruction::LocalGet(13),
+                        Instruction::If(wasm_encoder::BlockType::Empty),
+                            // Splice in `new`.
+                            Instruction::I32Const(0),
+                            Instruction::LocalSet(14), // k = 0
+                            Instruction::Block(wasm_encoder::BlockType::Empty),
+                                Instruction::Loop(wasm_encoder::BlockType::Empty),
+                                    Instruction::LocalGet(14),
+                                    Instruction::LocalGet(5),
+                                    Instruction::I32GeU,
+                                    Instruction::BrIf(1),
+
+                                    Instruction::LocalGet(16),
+                                    Instruction::I32Const(16),
+                                    Instruction::I32Add,
+                                    Instruction::LocalGet(11),
+                                    Instruction::I32Add,
+                                    Instruction::LocalGet(14),
+                                    Instruction::I32Add,
+
+                                    Instruction::LocalGet(2),
+                                    Instruction::I32Const(16),
+                                    Instruction::I32Add,
+                                    Instruction::LocalGet(14),
+                                    Instruction::I32Add,
+                                    Instruction::I32Load8U(MemArg { offset: 0, align: 0, memory_index: 0 }),
+                                    Instruction::I32Store8(MemArg { offset: 0, align: 0, memory_index: 0 }),
+
+                                    Instruction::LocalGet(14),
+                                    Instruction::I32Const(1),
+                                    Instruction::I32Add,
+                                    Instruction::LocalSet(14),
+                                    Instruction::Br(0),
+                                Instruction::End,
+                            Instruction::End,
+
+                            Instruction::LocalGet(11),
+                            Instruction::LocalGet(5),
+                            Instruction::I32Add,
+                            Instruction::LocalSet(11), // out += newlen
+                            Instruction::LocalGet(12),
+                            Instruction::LocalGet(4),
+                            Instruction::I32Add,
+                            Instruction::LocalSet(12), // i2 += oldlen
+                            Instruction::LocalGet(15),
+                            Instruction::I32Const(1),
+                            Instruction::I32Add,
+                            Instruction::LocalSet(15), // replaced += 1
+                        Instruction::Else,
+                            Instruction::LocalGet(16),
+                            Instruction::I32Const(16),
+                            Instruction::I32Add,
+                            Instruction::LocalGet(11),
+                            Instruction::I32Add,
+
+                            Instruction::LocalGet(0),
+                            Instruction::I32Const(16),
+                            Instruction::I32Add,
+                            Instruction::LocalGet(12),
+                            Instruction::I32Add,
+                            Instruction::I32Load8U(MemArg { offset: 0, align: 0, memory_index: 0 }),
+                            Instruction::I32Store8(MemArg { offset: 0, align: 0, memory_index: 0 }),
+
+                            Instruction::LocalGet(11),
+                            Instruction::I32Const(1),
+                            Instruction::I32Add,
+                            Instruction::LocalSet(11), // out += 1
+                            Instruction::LocalGet(12),
+                            Instruction::I32Const(1),
+                            Instruction::I32Add,
+                            Instruction::LocalSet(12), // i2 += 1
+                        Instruction::End,
+                        Instruction::Br(0),
+                    Instruction::End,
+                Instruction::End,
+
+                Instruction::LocalGet(16),
+            Instruction::End,
+        ]);
+        instructions
+    }
+
+    pub fn generate_string_replace(&self, malloc_idx: u32) -> Vec<Instruction> {
+        // Parameters: string_ptr (0), old_ptr (1), new_ptr (2). Replaces
+        // only the first match - see `generate_string_replace_impl`.
+        self.generate_string_replace_impl(malloc_idx, false)
+    }
+
+    pub fn generate_string_replace_all(&self, malloc_idx: u32) -> Vec<Instruction> {
+        // Parameters: string_ptr (0), old_ptr (1), new_ptr (2). Replaces
+        // every non-overlapping match - see `generate_string_replace_impl`.
+        self.generate_string_replace_impl(malloc_idx, true)
+    }
+
+    pub fn generate_string_pad_start(&self) -> Vec<Instruction> {
+        // Extremely simplified implementation to avoid WASM validation issues
+        // Parameters: string_ptr, target_length, pad_char
+        // Returns the original string pointer (no actual padding)
+        vec![
+            Instruction::LocalGet(0), // Return original string_ptr
+        ]
+    }
+
+    pub fn generate_string_pad_end(&self) -> Vec<Instruction> {
+        // Simplified implementation to avoid WASM validation issues
+        // Parameters: string_ptr, target_length, pad_char
+        // Returns a new string pointer with padding at end
+        vec![
+            // For now, return the original string pointer to avoid complex local variable usage
+            // In a real implementation, this would create a new string with padding at the end
+            Instruction::LocalGet(0), // Return original string_ptr
+        ]
+    }
+
+    /// Scratch region for `char_at`'s single-character result string. Same
+    /// fixed-address, non-reentrant convention as `STRING_CONCAT_SCRATCH` -
+    /// see that constant's doc comment. A UTF-8 scalar is at most 4 bytes, so
+    /// 16 (header) + 4 is ample.
+    const STRING_CHAR_AT_SCRATCH: i32 = 6656;
+
+    /// Returns a freshly-written one-character string containing the UTF-8
+    /// scalar at the given *character* (not byte) index, or a null (0)
+    /// pointer if the index is out of range or the sequence is malformed.
+    ///
+    /// This walks the same leading-byte decode as
+    /// `generate_string_char_code_at`, but only needs the byte span of the
+    /// matched scalar (not its numeric value) so it can copy it verbatim
+    /// into the result.
+    ///
+    /// Parameters: string_ptr (0), char_index (1). Locals: 2 = byte length,
+    /// 3 = byte offset of the current codepoint, 4 = codepoints seen so far,
+    /// 5 = leading byte, 6 = sequence length for the current codepoint,
+    /// 8 = continuation-byte loop index, 9 = continuation byte value,
+    /// 10 = malformed flag, 11 = found flag, 12 = byte offset of the match,
+    /// 13 = byte copy index used once a match is found.
+    pub fn generate_string_char_at(&self) -> Vec<Instruction> {
+        vec![
+            Instruction::LocalGet(0),
+            Instruction::I32Load(MemArg { offset: 0, align: 2, memory_index: 0 }),
+            Instruction::LocalSet(2), // len = byte length
+
+            Instruction::I32Const(0),
+            Instruction::LocalSet(3), // pos = 0
+            Instruction::I32Const(0),
+            Instruction::LocalSet(4), // cp_count = 0
+            Instruction::I32Const(0),
+            Instruction::LocalSet(11), // found = false
+
+            Instruction::Block(wasm_encoder::BlockType::Empty),
+                Instruction::Loop(wasm_encoder::BlockType::Empty),
+                    Instruction::LocalGet(3),
+                    Instruction::LocalGet(2),
+                    Instruction::I32GeU,
+                    Instruction::BrIf(1), // ran off the end without finding the index
+
+                    Instruction::LocalGet(0),
+                    Instruction::I32Const(16),
+                    Instruction::I32Add,
+                    Instruction::LocalGet(3),
+                    Instruction::I32Add,
+                    Instruction::I32Load8U(MemArg { offset: 0, align: 0, memory_index: 0 }),
+                    Instruction::LocalSet(5), // lead
+                    Instruction::I32Const(0),
+                    Instruction::LocalSet(10), // malformed = false
+
+                    Instruction::LocalGet(5),
+                    Instruction::I32Const(0x80),
+                    Instruction::I32And,
+                    Instruction::I32Eqz,
+                    Instruction::If(wasm_encoder::BlockType::Empty),
+                        Instruction::I32Const(1),
+                        Instruction::LocalSet(6), // seqlen = 1
+                    Instruction::Else,
+                        Instruction::LocalGet(5),
+                        Instruction::I32Const(0xE0),
+                        Instruction::I32And,
+                        Instruction::I32Const(0xC0),
+                        Instruction::I32Eq,
+                        Instruction::If(wasm_encoder::BlockType::Empty),
+                            Instruction::I32Const(2),
+                            Instruction::LocalSet(6),
+                        Instruction::Else,
+                            Instruction::LocalGet(5),
+                            Instruction::I32Const(0xF0),
+                            Instruction::I32And,
+                            Instruction::I32Const(0xE0),
+                            Instruction::I32Eq,
+                            Instruction::If(wasm_encoder::BlockType::Empty),
+                                Instruction::I32Const(3),
+                                Instruction::LocalSet(6),
+                            Instruction::Else,
+                                Instruction::LocalGet(5),
+                                Instruction::I32Const(0xF8),
+                                Instruction::I32And,
+                                Instruction::I32Const(0xF0),
+                                Instruction::I32Eq,
+                                Instruction::If(wasm_encoder::BlockType::Empty),
+                                    Instruction::I32Const(4),
+                                    Instruction::LocalSet(6),
+                                Instruction::Else,
+                                    Instruction::I32Const(1),
+                                    Instruction::LocalSet(10), // malformed = true
+                                Instruction::End,
+                            Instruction::End,
+                        Instruction::End,
+                    Instruction::End,
+
+                    Instruction::LocalGet(10),
+                    Instruction::If(wasm_encoder::BlockType::Empty),
+                        Instruction::Br(1), // malformed lead byte: give up
+                    Instruction::End,
+                    Instruction::LocalGet(3),
+                    Instruction::LocalGet(6),
+                    Instruction::I32Add,
+                    Instruction::LocalGet(2),
+                    Instruction::I32GtU,
+                    Instruction::If(wasm_encoder::BlockType::Empty),
+                        Instruction::Br(1), // sequence runs past the end
+                    Instruction::End,
+
+                    Instruction::I32Const(1),
+                    Instruction::LocalSet(8), // k = 1
+                    Instruction::Block(wasm_encoder::BlockType::Empty),
+                        Instruction::Loop(wasm_encoder::BlockType::Empty),
+                            Instruction::LocalGet(8),
+                            Instruction::LocalGet(6),
+                            Instruction::I32GeU,
+                            Instruction::BrIf(1),
+
+                            Instruction::LocalGet(0),
+                            Instruction::I32Const(16),
+                            Instruction::I32Add,
+                            Instruction::LocalGet(3),
+                            Instruction::I32Add,
+                            Instruction::LocalGet(8),
+                            Instruction::I32Add,
+                            Instruction::I32Load8U(MemArg { offset: 0, align: 0, memory_index: 0 }),
+                            Instruction::LocalSet(9), // cont
+                            Instruction::LocalGet(9),
+                            Instruction::I32Const(0xC0),
+                            Instruction::I32And,
+                            Instruction::I32Const(0x80),
+                            Instruction::I32Ne,
+                            Instruction::If(wasm_encoder::BlockType::Empty),
+                                Instruction::I32Const(1),
+                                Instruction::LocalSet(10),
+                                Instruction::Br(1),
+                            Instruction::End,
+
+                            Instruction::LocalGet(8),
+                            Instruction::I32Const(1),
+                            Instruction::I32Add,
+                            Instruction::LocalSet(8),
+                            Instruction::Br(0),
+                        Instruction::End,
+                    Instruction::End,
+
+                    Instruction::LocalGet(10),
+                    Instruction::If(wasm_encoder::BlockType::Empty),
+                        Instruction::Br(1), // a continuation byte was bad
+                    Instruction::End,
+
+                    Instruction::LocalGet(4),
+                    Instruction::LocalGet(1),
+                    Instruction::I32Eq,
+                    Instruction::If(wasm_encoder::BlockType::Empty),
+                        Instruction::I32Const(1),
+                        Instruction::LocalSet(11), // found = true
+                        Instruction::LocalGet(3),
+                        Instruction::LocalSet(12), // found_pos = pos
+                        Instruction::Br(1),
+                    Instruction::End,
+
+                    Instruction::LocalGet(4),
+                    Instruction::I32Const(1),
+                    Instruction::I32Add,
+                    Instruction::LocalSet(4), // cp_count += 1
+                    Instruction::LocalGet(3),
+                    Instruction::LocalGet(6),
+                    Instruction::I32Add,
+                    Instruction::LocalSet(3), // pos += seqlen
+                    Instruction::Br(0),
+                Instruction::End, // loop
+            Instruction::End, // block
+
+            Instruction::LocalGet(11),
+            Instruction::If(wasm_encoder::BlockType::Result(wasm_encoder::ValType::I32)),
+                // Re-decode the lead byte at found_pos just to recover seqlen
+                // for the copy below (cheap: at most one extra byte load).
+                Instruction::LocalGet(0),
+                Instruction::I32Const(16),
+                Instruction::I32Add,
+                Instruction::LocalGet(12),
+                Instruction::I32Add,
+                Instruction::I32Load8U(MemArg { offset: 0, align: 0, memory_index: 0 }),
+                Instruction::LocalSet(5), // lead
+
+                Instruction::LocalGet(5),
+                Instruction::I32Const(0x80),
+                Instruction::I32And,
+                Instruction::I32Eqz,
+                Instruction::If(wasm_encoder::BlockType::Empty),
+                    Instruction::I32Const(1),
+                    Instruction::LocalSet(6),
+                Instruction::Else,
+                    Instruction::LocalGet(5),
+                    Instruction::I32Const(0xE0),
+                    Instruction::I32And,
+                    Instruction::I32Const(0xC0),
+                    Instruction::I32Eq,
+                    Instruction::If(wasm_encoder::BlockType::Empty),
+                        Instruction::I32Const(2),
+                        Instruction::LocalSet(6),
+                    Instruction::Else,
+                        Instruction::LocalGet(5),
+                        Instruction::I32Const(0xF0),
+                        Instruction::I32And,
+                        Instruction::I32Const(0xE0),
+                        Instruction::I32Eq,
+                        Instruction::If(wasm_encoder::BlockType::Empty),
+                            Instruction::I32Const(3),
+                            Instruction::LocalSet(6),
+                        Instruction::Else,
+                            Instruction::I32Const(4),
+                            Instruction::LocalSet(6),
+                        Instruction::End,
+                    Instruction::End,
+                Instruction::End,
+
+                Instruction::I32Const(Self::STRING_CHAR_AT_SCRATCH),
+                Instruction::LocalGet(6),
+                Instruction::I32Store(MemArg { offset: 0, align: 2, memory_index: 0 }),
+
+                Instruction::I32Const(0),
+                Instruction::LocalSet(13), // copy index
+                Instruction::Block(wasm_encoder::BlockType::Empty),
+                    Instruction::Loop(wasm_encoder::BlockType::Empty),
+                        Instruction::LocalGet(13),
+                        Instruction::LocalGet(6),
+                        Instruction::I32GeU,
+                        Instruction::BrIf(1),
+
+                        Instruction::I32Const(Self::STRING_CHAR_AT_SCRATCH),
+                        Instruction::I32Const(16),
+                        Instruction::I32Add,
+                        Instruction::LocalGet(13),
+                        Instruction::I32Add,
+                        Instruction::LocalGet(0),
+                        Instruction::I32Const(16),
+                        Instruction::I32Add,
+                        Instruction::LocalGet(12),
+                        Instruction::I32Add,
+                        Instruction::LocalGet(13),
+                        Instruction::I32Add,
+                        Instruction::I32Load8U(MemArg { offset: 0, align: 0, memory_index: 0 }),
+                        Instruction::I32Store8(MemArg { offset: 0, align: 0, memory_index: 0 }),
+
+                        Instruction::LocalGet(13),
+                        Instruction::I32Const(1),
+                        Instruction::I32Add,
+                        Instruction::LocalSet(13),
+                        Instruction::Br(0),
+                    Instruction::End,
+                Instruction::End,
+
+                Instruction::I32Const(Self::STRING_CHAR_AT_SCRATCH),
+            Instruction::Else,
+                Instruction::I32Const(0), // not found / malformed: null
+            Instruction::End,
+        ]
+    }
+
+    /// Decodes the UTF-8 codepoint at the given *character* index (not byte
+    /// index) and returns its full Unicode scalar value.
+    ///
+    /// Parameters: string_ptr (0), char_index (1).
+    ///
+    /// Locals: 2 = byte length, 3 = byte offset of the current codepoint
+    /// (relative to the data region at `string_ptr + 16`), 4 = codepoints
+    /// seen so far, 5 = leading byte, 6 = sequence length for the current
+    /// codepoint (1-4), 7 = result (-1 until a match is found, or on a
+    /// malformed/out-of-range index), 8 = continuation-byte loop index,
+    /// 9 = continuation byte value, 10 = malformed flag, 11 = scalar
+    /// accumulator for the codepoint currently being decoded.
+    pub fn generate_string_char_code_at(&self) -> Vec<Instruction> {
+        vec![
+            Instruction::LocalGet(0),
+            Instruction::I32Load(MemArg { offset: 0, align: 2, memory_index: 0 }),
+            Instruction::LocalSet(2), // len = byte length
+
+            Instruction::I32Const(0),
+            Instruction::LocalSet(3), // pos = 0
+            Instruction::I32Const(0),
+            Instruction::LocalSet(4), // cp_count = 0
+            Instruction::I32Const(-1),
+            Instruction::LocalSet(7), // result = -1
+
+            Instruction::Block(wasm_encoder::BlockType::Empty),
+                Instruction::Loop(wasm_encoder::BlockType::Empty),
+                    // Ran off the end of the string without finding the index.
+                    Instruction::LocalGet(3),
+                    Instruction::LocalGet(2),
+                    Instruction::I32GeU,
+                    Instruction::BrIf(1),
+
+                    // lead = string[16 + pos]
+                    Instruction::LocalGet(0),
+                    Instruction::I32Const(16),
+                    Instruction::I32Add,
+                    Instruction::LocalGet(3),
+                    Instruction::I32Add,
+                    Instruction::I32Load8U(MemArg { offset: 0, align: 0, memory_index: 0 }),
+                    Instruction::LocalSet(5),
+                    Instruction::I32Const(0),
+                    Instruction::LocalSet(10), // malformed = false
+
+                    // seqlen/scalar from the leading byte's high bits.
+                    Instruction::LocalGet(5),
+                    Instruction::I32Const(0x80),
+                    Instruction::I32And,
+                    Instruction::I32Eqz,
+                    Instruction::If(wasm_encoder::BlockType::Empty),
+                        Instruction::I32Const(1),
+                        Instruction::LocalSet(6), // seqlen = 1
+                        Instruction::LocalGet(5),
+                        Instruction::LocalSet(11), // scalar = lead
+                    Instruction::Else,
+                        Instruction::LocalGet(5),
+                        Instruction::I32Const(0xE0),
+                        Instruction::I32And,
+                        Instruction::I32Const(0xC0),
+                        Instruction::I32Eq,
+                        Instruction::If(wasm_encoder::BlockType::Empty),
+                            Instruction::I32Const(2),
+                            Instruction::LocalSet(6),
+                            Instruction::LocalGet(5),
+                            Instruction::I32Const(0x1F),
+                            Instruction::I32And,
+                            Instruction::LocalSet(11),
+                        Instruction::Else,
+                            Instruction::LocalGet(5),
+                            Instruction::I32Const(0xF0),
+                            Instruction::I32And,
+                            Instruction::I32Const(0xE0),
+                            Instruction::I32Eq,
+                            Instruction::If(wasm_encoder::BlockType::Empty),
+                                Instruction::I32Const(3),
+                                Instruction::LocalSet(6),
+                                Instruction::LocalGet(5),
+                                Instruction::I32Const(0x0F),
+                                Instruction::I32And,
+                                Instruction::LocalSet(11),
+                            Instruction::Else,
+                                Instruction::LocalGet(5),
+                                Instruction::I32Const(0xF8),
+                                Instruction::I32And,
+                                Instruction::I32Const(0xF0),
+                                Instruction::I32Eq,
+                                Instruction::If(wasm_encoder::BlockType::Empty),
+                                    Instruction::I32Const(4),
+                                    Instruction::LocalSet(6),
+                                    Instruction::LocalGet(5),
+                                    Instruction::I32Const(0x07),
+                                    Instruction::I32And,
+                                    Instruction::LocalSet(11),
+                                Instruction::Else,
+                                    // Not a valid UTF-8 leading byte.
+                                    Instruction::I32Const(1),
+                                    Instruction::LocalSet(10),
+                                Instruction::End,
+                            Instruction::End,
+                        Instruction::End,
+                    Instruction::End,
+
+                    // Malformed lead byte, or the sequence runs past the end of the string.
+                    Instruction::LocalGet(10),
+                    Instruction::If(wasm_encoder::BlockType::Empty),
+                        Instruction::I32Const(-1),
+                        Instruction::LocalSet(7),
+                        Instruction::Br(1),
+                    Instruction::End,
+                    Instruction::LocalGet(3),
+                    Instruction::LocalGet(6),
+                    Instruction::I32Add,
+                    Instruction::LocalGet(2),
+                    Instruction::I32GtU,
+                    Instruction::If(wasm_encoder::BlockType::Empty),
+                        Instruction::I32Const(-1),
+                        Instruction::LocalSet(7),
+                        Instruction::Br(1),
+                    Instruction::End,
+
+                    // Fold in the continuation bytes, checking each is `10xxxxxx`.
+                    Instruction::I32Const(1),
+                    Instruction::LocalSet(8), // k = 1
+                    Instruction::Block(wasm_encoder::BlockType::Empty),
+                        Instruction::Loop(wasm_encoder::BlockType::Empty),
+                            Instruction::LocalGet(8),
+                            Instruction::LocalGet(6),
+                            Instruction::I32GeU,
+                            Instruction::BrIf(1),
+
+                            Instruction::LocalGet(0),
+                            Instruction::I32Const(16),
+                            Instruction::I32Add,
+                            Instruction::LocalGet(3),
+                            Instruction::I32Add,
+                            Instruction::LocalGet(8),
+                            Instruction::I32Add,
+                            Instruction::I32Load8U(MemArg { offset: 0, align: 0, memory_index: 0 }),
+                            Instruction::LocalSet(9), // cont = string[16 + pos + k]
+
+                            Instruction::LocalGet(9),
+                            Instruction::I32Const(0xC0),
+                            Instruction::I32And,
+                            Instruction::I32Const(0x80),
+                            Instruction::I32Ne,
+                            Instruction::If(wasm_encoder::BlockType::Empty),
+                                Instruction::I32Const(1),
+                                Instruction::LocalSet(10), // malformed = true
+                                Instruction::Br(1), // stop folding continuation bytes
+                            Instruction::End,
+
+                            Instruction::LocalGet(11),
+                            Instruction::I32Const(6),
+                            Instruction::I32Shl,
+                            Instruction::LocalGet(9),
+                            Instruction::I32Const(0x3F),
+                            Instruction::I32And,
+                            Instruction::I32Or,
+                            Instruction::LocalSet(11), // scalar = (scalar << 6) | (cont & 0x3F)
+
+                            Instruction::LocalGet(8),
+                            Instruction::I32Const(1),
+                            Instruction::I32Add,
+                            Instruction::LocalSet(8), // k += 1
+                            Instruction::Br(0),
+                        Instruction::End, // inner loop
+                    Instruction::End, // inner block
+
+                    Instruction::LocalGet(10),
+                    Instruction::If(wasm_encoder::BlockType::Empty),
+                        Instruction::I32Const(-1),
+                        Instruction::LocalSet(7),
+                        Instruction::Br(1),
+                    Instruction::End,
+
+                    // Is this the requested codepoint?
+                    Instruction::LocalGet(4),
+                    Instruction::LocalGet(1),
+                    Instruction::I32Eq,
+                    Instruction::If(wasm_encoder::BlockType::Empty),
+                        Instruction::LocalGet(11),
+                        Instruction::LocalSet(7),
+                        Instruction::Br(1),
+                    Instruction::End,
+
+                    Instruction::LocalGet(4),
+                    Instruction::I32Const(1),
+                    Instruction::I32Add,
+                    Instruction::LocalSet(4), // cp_count += 1
+                    Instruction::LocalGet(3),
+                    Instruction::LocalGet(6),
+                    Instruction::I32Add,
+                    Instruction::LocalSet(3), // pos += seqlen
+                    Instruction::Br(0),
+                Instruction::End, // loop
+            Instruction::End, // block
+
+            Instruction::LocalGet(7),
+        ]
+    }
+
+    /// Counts Unicode codepoints (not bytes) in a string by scanning for
+    /// leading bytes - any byte that isn't a `10xxxxxx` continuation byte
+    /// starts a new codepoint. This is the real length backing `.length`
+    /// wants eventually; `generate_string_length`/the shared `"length"`
+    /// dispatch still report byte count today (changing that shared function
+    /// would also change `List.length()`, which is out of scope here), so
+    /// this is exposed as its own `string_char_count` function for now.
+    ///
+    /// Parameters: string_ptr (0). Locals: 2 = byte length, 3 = byte
+    /// position, 4 = codepoint count.
+    pub fn generate_string_char_count(&self) -> Vec<Instruction> {
+        vec![
+            Instruction::LocalGet(0),
+            Instruction::I32Load(MemArg { offset: 0, align: 2, memory_index: 0 }),
+            Instruction::LocalSet(2), // len = byte length
+
+            Instruction::I32Const(0),
+            Instruction::LocalSet(3), // pos = 0
+            Instruction::I32Const(0),
+            Instruction::LocalSet(4), // count = 0
+
+            Instruction::Block(wasm_encoder::BlockType::Empty),
+                Instruction::Loop(wasm_encoder::BlockType::Empty),
+                    Instruction::LocalGet(3),
+                    Instruction::LocalGet(2),
+                    Instruction::I32GeU,
+                    Instruction::BrIf(1),
+
+                    // Count this byte unless it's a continuation byte (10xxxxxx).
+                    Instruction::LocalGet(0),
+                    Instruction::I32Const(16),
+                    Instruction::I32Add,
+                    Instruction::LocalGet(3),
+                    Instruction::I32Add,
+                    Instruction::I32Load8U(MemArg { offset: 0, align: 0, memory_index: 0 }),
+                    Instruction::I32Const(0xC0),
+                    Instruction::I32And,
+                    Instruction::I32Const(0x80),
+                    Instruction::I32Ne,
+                    Instruction::If(wasm_encoder::BlockType::Empty),
+                        Instruction::LocalGet(4),
+                        Instruction::I32Const(1),
+                        Instruction::I32Add,
+                        Instruction::LocalSet(4),
+                    Instruction::End,
+
+                    Instruction::LocalGet(3),
+                    Instruction::I32Const(1),
+                    Instruction::I32Add,
+                    Instruction::LocalSet(3), // pos += 1
+                    Instruction::Br(0),
+                Instruction::End, // loop
+            Instruction::End, // block
+
+            Instruction::LocalGet(4),
         ]
     }
 
@@ -813,47 +3171,1695 @@ impl StringOperations {
         ]
     }
 
-    pub fn generate_string_split(&self) -> Vec<Instruction> {
-        // SIMPLIFIED: String split - just return a null pointer for now
-        // Parameters: string_ptr (0), delimiter_ptr (1)
-        // Returns: null pointer (no actual list created)
+    /// Appends the current segment (locals 5 = start offset, 9 = byte
+    /// length) as a newly `malloc`'d string to the newly `malloc`'d list at
+    /// `local 14`, at slot `local 6` (the running segment count, which this
+    /// also advances) - used as the sizing pass's segment action is a plain
+    /// counter bump instead.
+    ///
+    /// Expects locals (shared with the caller): 0 = source string pointer,
+    /// 5 = segment start byte offset, 6 = segment index so far, 9 = segment
+    /// byte length, 14 = destination list pointer. Locals 10 = newly
+    /// allocated segment string pointer, 12 = copy index are owned by this
+    /// helper.
+    fn generate_split_alloc_and_store_segment(&self, malloc_idx: u32) -> Vec<Instruction> {
         vec![
-            Instruction::I32Const(0), // Return null pointer
+            Instruction::LocalGet(9),
+            Instruction::I32Const(16),
+            Instruction::I32Add,
+            Instruction::Call(malloc_idx),
+            Instruction::LocalSet(10), // dest = malloc(seg_len + 16)
+
+            Instruction::LocalGet(10),
+            Instruction::LocalGet(9),
+            Instruction::I32Store(MemArg { offset: 0, align: 2, memory_index: 0 }), // length
+            Instruction::LocalGet(10),
+            Instruction::LocalGet(9),
+            Instruction::I32Store(MemArg { offset: 4, align: 2, memory_index: 0 }), // capacity
+
+            Instruction::I32Const(0),
+            Instruction::LocalSet(12), // k = 0
+            Instruction::Block(wasm_encoder::BlockType::Empty),
+                Instruction::Loop(wasm_encoder::BlockType::Empty),
+                    Instruction::LocalGet(12),
+                    Instruction::LocalGet(9),
+                    Instruction::I32GeU,
+                    Instruction::BrIf(1),
+
+                    Instruction::LocalGet(10),
+                    Instruction::I32Const(16),
+                    Instruction::I32Add,
+                    Instruction::LocalGet(12),
+                    Instruction::I32Add,
+
+                    Instruction::LocalGet(0),
+                    Instruction::I32Const(16),
+                    Instruction::I32Add,
+                    Instruction::LocalGet(5),
+                    Instruction::I32Add,
+                    Instruction::LocalGet(12),
+                    Instruction::I32Add,
+                    Instruction::I32Load8U(MemArg { offset: 0, align: 0, memory_index: 0 }),
+                    Instruction::I32Store8(MemArg { offset: 0, align: 0, memory_index: 0 }),
+
+                    Instruction::LocalGet(12),
+                    Instruction::I32Const(1),
+                    Instruction::I32Add,
+                    Instruction::LocalSet(12),
+                    Instruction::Br(0),
+                Instruction::End,
+            Instruction::End,
+
+            Instruction::LocalGet(14),
+            Instruction::I32Const(8),
+            Instruction::I32Add,
+            Instruction::LocalGet(6),
+            Instruction::I32Const(4),
+            Instruction::I32Mul,
+            Instruction::I32Add,
+            Instruction::LocalGet(10),
+            Instruction::I32Store(MemArg { offset: 0, align: 2, memory_index: 0 }),
+
+            Instruction::LocalGet(6),
+            Instruction::I32Const(1),
+            Instruction::I32Add,
+            Instruction::LocalSet(6), // seg_count += 1
         ]
     }
 
-    pub fn generate_simple_substring(&self) -> Vec<Instruction> {
-        // SIMPLIFIED: Substring implementation - basic bounds checking
-        // Parameters: string_ptr (0), start (1), end (2)
-        // Returns: new string pointer
-        
+    /// Bumps the segment counter (local 6) without writing anything -
+    /// `generate_string_split`'s sizing pass over the same scan used by
+    /// `generate_split_alloc_and_store_segment`, so the list can be
+    /// allocated at its exact final size instead of a fixed capacity.
+    fn generate_split_count_segment(&self) -> Vec<Instruction> {
         vec![
-            // For now, just return the original string to avoid complex memory management
-            // In a full implementation, this would:
-            // 1. Validate start/end bounds
-            // 2. Allocate new string with calculated length
-            // 3. Copy substring data
-            // 4. Return new string pointer
-            Instruction::LocalGet(0), // Return original string
+            Instruction::LocalGet(6),
+            Instruction::I32Const(1),
+            Instruction::I32Add,
+            Instruction::LocalSet(6), // seg_count += 1
         ]
     }
 
-    pub fn generate_simple_replace(&self) -> Vec<Instruction> {
-        // SIMPLIFIED: Replace implementation - basic functionality
-        // Parameters: string_ptr (0), old_str (1), new_str (2)
-        // Returns: new string pointer
-        
+    /// Finds every segment boundary in `string_ptr` (0) split by
+    /// `delimiter_ptr` (1) - an empty delimiter splits into individual
+    /// UTF-8 scalars (respecting multibyte sequence boundaries), a
+    /// non-empty one scans for byte-exact matches - invoking
+    /// `on_segment` at each boundary with locals 5 (segment start byte
+    /// offset) and 9 (segment byte length) already set. Shared between
+    /// `generate_string_split`'s sizing pass (`generate_split_count_segment`)
+    /// and its write pass (`generate_split_alloc_and_store_segment`), so
+    /// both passes visit exactly the same segments.
+    ///
+    /// Parameters: string_ptr (0), delimiter_ptr (1). Locals: 2 = haystack
+    /// length, 3 = delimiter length, 4 = scan position, 5 = current segment
+    /// start, 6 = segment count (reset to 0 by the caller before each
+    /// pass), 7 = inner compare index, 8 = match flag / decoded lead byte,
+    /// 9 = segment length, 13 = decoded UTF-8 sequence length
+    /// (empty-delimiter path only).
+    fn generate_string_split_scan(&self, on_segment: &dyn Fn() -> Vec<Instruction>) -> Vec<Instruction> {
+        let mut instructions = vec![
+            Instruction::LocalGet(0),
+            Instruction::I32Load(MemArg { offset: 0, align: 2, memory_index: 0 }),
+            Instruction::LocalSet(2), // hlen
+            Instruction::LocalGet(1),
+            Instruction::I32Load(MemArg { offset: 0, align: 2, memory_index: 0 }),
+            Instruction::LocalSet(3), // dlen
+
+            Instruction::LocalGet(3),
+            Instruction::I32Eqz,
+            Instruction::If(wasm_encoder::BlockType::Empty),
+                // Empty delimiter: split into individual UTF-8 scalars.
+                Instruction::I32Const(0),
+                Instruction::LocalSet(4), // pos = 0
+                Instruction::Block(wasm_encoder::BlockType::Empty),
+                    Instruction::Loop(wasm_encoder::BlockType::Empty),
+                        Instruction::LocalGet(4),
+                        Instruction::LocalGet(2),
+                        Instruction::I32GeU,
+                        Instruction::BrIf(1),
+
+                        Instruction::LocalGet(0),
+                        Instruction::I32Const(16),
+                        Instruction::I32Add,
+                        Instruction::LocalGet(4),
+                        Instruction::I32Add,
+                        Instruction::I32Load8U(MemArg { offset: 0, align: 0, memory_index: 0 }),
+                        Instruction::LocalSet(8), // lead
+
+                        Instruction::LocalGet(8),
+                        Instruction::I32Const(0x80),
+                        Instruction::I32And,
+                        Instruction::I32Eqz,
+                        Instruction::If(wasm_encoder::BlockType::Empty),
+                            Instruction::I32Const(1),
+                            Instruction::LocalSet(13),
+                        Instruction::Else,
+                            Instruction::LocalGet(8),
+                            Instruction::I32Const(0xE0),
+                            Instruction::I32And,
+                            Instruction::I32Const(0xC0),
+                            Instruction::I32Eq,
+                            Instruction::If(wasm_encoder::BlockType::Empty),
+                                Instruction::I32Const(2),
+                                Instruction::LocalSet(13),
+                            Instruction::Else,
+                                Instruction::LocalGet(8),
+                                Instruction::I32Const(0xF0),
+                                Instruction::I32And,
+                                Instruction::I32Const(0xE0),
+                                Instruction::I32Eq,
+                                Instruction::If(wasm_encoder::BlockType::Empty),
+                                    Instruction::I32Const(3),
+                                    Instruction::LocalSet(13),
+                                Instruction::Else,
+                                    Instruction::LocalGet(8),
+                                    Instruction::I32Const(0xF8),
+                                    Instruction::I32And,
+                                    Instruction::I32Const(0xF0),
+                                    Instruction::I32Eq,
+                                    Instruction::If(wasm_encoder::BlockType::Empty),
+                                        Instruction::I32Const(4),
+                                        Instruction::LocalSet(13),
+                                    Instruction::Else,
+                                        Instruction::I32Const(1), // malformed: treat as 1 byte
+                                        Instruction::LocalSet(13),
+                                    Instruction::End,
+                                Instruction::End,
+                            Instruction::End,
+                        Instruction::End,
+
+                        Instruction::LocalGet(4),
+                        Instruction::LocalGet(13),
+                        Instruction::I32Add,
+                        Instruction::LocalGet(2),
+                        Instruction::I32GtU,
+                        Instruction::If(wasm_encoder::BlockType::Empty),
+                            Instruction::LocalGet(2),
+                            Instruction::LocalGet(4),
+                            Instruction::I32Sub,
+                            Instruction::LocalSet(13), // clamp a truncated trailing sequence
+                        Instruction::End,
+
+                        Instruction::LocalGet(4),
+                        Instruction::LocalSet(5), // seg_start = pos
+                        Instruction::LocalGet(13),
+                        Instruction::LocalSet(9), // seg_len = seqlen
+        ];
+        instructions.extend(on_segment());
+        instructions.extend(vec![
+                        Instruction::LocalGet(4),
+                        Instruction::LocalGet(13),
+                        Instruction::I32Add,
+                        Instruction::LocalSet(4), // pos += seqlen
+                        Instruction::Br(0),
+                    Instruction::End,
+                Instruction::End,
+            Instruction::Else,
+                // Non-empty delimiter: scan for byte-exact matches.
+                Instruction::I32Const(0),
+                Instruction::LocalSet(4), // i = 0
+                Instruction::I32Const(0),
+                Instruction::LocalSet(5), // seg_start = 0
+                Instruction::Block(wasm_encoder::BlockType::Empty),
+                    Instruction::Loop(wasm_encoder::BlockType::Empty),
+                        Instruction::LocalGet(4),
+                        Instruction::LocalGet(3),
+                        Instruction::I32Add,
+                        Instruction::LocalGet(2),
+                        Instruction::I32GtU,
+                        Instruction::BrIf(1),
+
+                        Instruction::I32Const(1),
+                        Instruction::LocalSet(8), // match = true
+                        Instruction::I32Const(0),
+                        Instruction::LocalSet(7), // j = 0
+                        Instruction::Block(wasm_encoder::BlockType::Empty),
+                            Instruction::Loop(wasm_encoder::BlockType::Empty),
+                                Instruction::LocalGet(7),
+                                Instruction::LocalGet(3),
+                                Instruction::I32GeU,
+                                Instruction::BrIf(1),
+
+                                Instruction::LocalGet(0),
+                                Instruction::I32Const(16),
+                                Instruction::I32Add,
+                                Instruction::LocalGet(4),
+                                Instruction::I32Add,
+                                Instruction::LocalGet(7),
+                                Instruction::I32Add,
+                                Instruction::I32Load8U(MemArg { offset: 0, align: 0, memory_index: 0 }),
+                                Instruction::LocalGet(1),
+                                Instruction::I32Const(16),
+                                Instruction::I32Add,
+                                Instruction::LocalGet(7),
+                                Instruction::I32Add,
+                                Instruction::I32Load8U(MemArg { offset: 0, align: 0, memory_index: 0 }),
+                                Instruction::I32Ne,
+                                Instruction::If(wasm_encoder::BlockType::Empty),
+                                    Instruction::I32Const(0),
+                                    Instruction::LocalSet(8),
+                                Instruction::End,
+
+                                Instruction::LocalGet(7),
+                                Instruction::I32Const(1),
+                                Instruction::I32Add,
+                                Instruction::LocalSet(7),
+                                Instruction::Br(0),
+                            Instruction::End,
+                        Instruction::End,
+
+                        Instruction::LocalGet(8),
+                        Instruction::If(wasm_encoder::BlockType::Empty),
+                            Instruction::LocalGet(4),
+                            Instruction::LocalGet(5),
+                            Instruction::I32Sub,
+                            Instruction::LocalSet(9), // seg_len = i - seg_start
+        ]);
+        instructions.extend(on_segment());
+        instructions.extend(vec![
+                            Instruction::LocalGet(4),
+                            Instruction::LocalGet(3),
+                            Instruction::I32Add,
+                            Instruction::LocalSet(4), // i += dlen
+                            Instruction::LocalGet(4),
+                            Instruction::LocalSet(5), // seg_start = i
+                        Instruction::Else,
+                            Instruction::LocalGet(4),
+                            Instruction::I32Const(1),
+                            Instruction::I32Add,
+                            Instruction::LocalSet(4), // i += 1
+                        Instruction::End,
+                        Instruction::Br(0),
+                    Instruction::End,
+                Instruction::End,
+
+                // Final segment: everything from seg_start to the end.
+                Instruction::LocalGet(2),
+                Instruction::LocalGet(5),
+                Instruction::I32Sub,
+                Instruction::LocalSet(9),
+        ]);
+        instructions.extend(on_segment());
+        instructions.push(Instruction::End); // if dlen == 0
+        instructions
+    }
+
+    /// Splits a string on every occurrence of `delimiter`, producing the
+    /// array-family list representation (8-byte header: offset 0 = count,
+    /// offset 4 = capacity, elements from offset 8 on - see
+    /// `ArrayOperations::generate_array_push` in `array_ops.rs`) whose
+    /// elements are pointers to newly-allocated segment strings. An empty
+    /// delimiter splits into individual UTF-8 scalars instead (respecting
+    /// multibyte sequence boundaries), and leading/trailing delimiters
+    /// produce empty-string elements the same way a byte-exact scan would.
+    ///
+    /// Runs `generate_string_split_scan` twice: once to count the
+    /// segments so the list can be `malloc`'d at its exact size (no
+    /// fixed segment cap, no silent truncation), once to allocate each
+    /// segment string and fill the list.
+    ///
+    /// Parameters: string_ptr (0), delimiter_ptr (1). Locals: as
+    /// `generate_string_split_scan`, plus 14 = destination list pointer
+    /// (allocated between the two passes). 10/12 are owned by
+    /// `generate_split_alloc_and_store_segment`.
+    pub fn generate_string_split(&self, malloc_idx: u32) -> Vec<Instruction> {
+        let mut instructions = vec![
+            Instruction::I32Const(0),
+            Instruction::LocalSet(6), // seg_count = 0
+        ];
+        instructions.extend(self.generate_string_split_scan(&|| self.generate_split_count_segment()));
+
+        instructions.extend(vec![
+            // list = malloc(8 + seg_count * 4)
+            Instruction::LocalGet(6),
+            Instruction::I32Const(4),
+            Instruction::I32Mul,
+            Instruction::I32Const(8),
+            Instruction::I32Add,
+            Instruction::Call(malloc_idx),
+            Instruction::LocalSet(14), // dest list pointer
+
+            Instruction::LocalGet(14),
+            Instruction::LocalGet(6),
+            Instruction::I32Store(MemArg { offset: 0, align: 2, memory_index: 0 }), // count
+            Instruction::LocalGet(14),
+            Instruction::LocalGet(6),
+            Instruction::I32Store(MemArg { offset: 4, align: 2, memory_index: 0 }), // capacity
+
+            Instruction::I32Const(0),
+            Instruction::LocalSet(6), // seg_count reused as the write index
+        ]);
+        instructions.extend(self.generate_string_split_scan(&|| self.generate_split_alloc_and_store_segment(malloc_idx)));
+        instructions.push(Instruction::LocalGet(14));
+        instructions
+    }
+
+    pub fn generate_simple_substring(&self, malloc_idx: u32) -> Vec<Instruction> {
+        // Parameters: string_ptr (0), start (1), end (2). `_impl` alias kept
+        // for codegen call sites that expect this name; same real behavior
+        // as `generate_string_substring`.
+        self.generate_string_substring(malloc_idx)
+    }
+
+    pub fn generate_simple_replace(&self, malloc_idx: u32) -> Vec<Instruction> {
+        // Parameters: string_ptr (0), old_str (1), new_str (2). `_impl`
+        // alias kept for codegen call sites that expect this name; same
+        // real behavior as `generate_string_replace`.
+        self.generate_string_replace(malloc_idx)
+    }
+
+    /// Header layout for the UTF-16 bridge's buffers.
+    ///
+    /// Unlike the 16-byte string header used elsewhere in this file, a
+    /// UTF-16 buffer uses the same 8-byte header as the array/list family
+    /// (see `array_ops.rs`): offset 0 = code unit count (u32), offset 4 =
+    /// reserved, data (u16 LE units) starts at offset 8. It's a distinct
+    /// host-facing layout, not a Clean string, so it doesn't carry the
+    /// cached-hash/capacity fields a string header does.
+
+    /// Decodes the UTF-8 scalar at byte offset `pos` (local 3) of the
+    /// string at `string_ptr` (local 0), given byte length `len` (local 2)
+    /// and assuming `pos < len`. A malformed lead byte, or a sequence that
+    /// runs past `len`, or a bad continuation byte, is reported via the
+    /// malformed flag rather than aborting, with `seqlen` forced to 1 so
+    /// the caller can still make forward progress one byte at a time.
+    ///
+    /// On return: local 6 = sequence length in bytes actually consumed
+    /// (1-4), local 10 = malformed flag, local 11 = decoded scalar (only
+    /// meaningful when not malformed). Also uses locals 5 (lead byte), 8
+    /// (continuation loop index), 9 (continuation byte).
+    fn generate_utf8_decode_scalar(&self) -> Vec<Instruction> {
+        vec![
+            Instruction::LocalGet(0),
+            Instruction::I32Const(16),
+            Instruction::I32Add,
+            Instruction::LocalGet(3),
+            Instruction::I32Add,
+            Instruction::I32Load8U(MemArg { offset: 0, align: 0, memory_index: 0 }),
+            Instruction::LocalSet(5), // lead
+            Instruction::I32Const(0),
+            Instruction::LocalSet(10), // malformed = false
+
+            Instruction::LocalGet(5),
+            Instruction::I32Const(0x80),
+            Instruction::I32And,
+            Instruction::I32Eqz,
+            Instruction::If(wasm_encoder::BlockType::Empty),
+                Instruction::I32Const(1),
+                Instruction::LocalSet(6),
+                Instruction::LocalGet(5),
+                Instruction::LocalSet(11),
+            Instruction::Else,
+                Instruction::LocalGet(5),
+                Instruction::I32Const(0xE0),
+                Instruction::I32And,
+                Instruction::I32Const(0xC0),
+                Instruction::I32Eq,
+                Instruction::If(wasm_encoder::BlockType::Empty),
+                    Instruction::I32Const(2),
+                    Instruction::LocalSet(6),
+                    Instruction::LocalGet(5),
+                    Instruction::I32Const(0x1F),
+                    Instruction::I32And,
+                    Instruction::LocalSet(11),
+                Instruction::Else,
+                    Instruction::LocalGet(5),
+                    Instruction::I32Const(0xF0),
+                    Instruction::I32And,
+                    Instruction::I32Const(0xE0),
+                    Instruction::I32Eq,
+                    Instruction::If(wasm_encoder::BlockType::Empty),
+                        Instruction::I32Const(3),
+                        Instruction::LocalSet(6),
+                        Instruction::LocalGet(5),
+                        Instruction::I32Const(0x0F),
+                        Instruction::I32And,
+                        Instruction::LocalSet(11),
+                    Instruction::Else,
+                        Instruction::LocalGet(5),
+                        Instruction::I32Const(0xF8),
+                        Instruction::I32And,
+                        Instruction::I32Const(0xF0),
+                        Instruction::I32Eq,
+                        Instruction::If(wasm_encoder::BlockType::Empty),
+                            Instruction::I32Const(4),
+                            Instruction::LocalSet(6),
+                            Instruction::LocalGet(5),
+                            Instruction::I32Const(0x07),
+                            Instruction::I32And,
+                            Instruction::LocalSet(11),
+                        Instruction::Else,
+                            Instruction::I32Const(1),
+                            Instruction::LocalSet(10), // malformed lead byte
+                            Instruction::I32Const(1),
+                            Instruction::LocalSet(6), // advance 1 byte anyway
+                        Instruction::End,
+                    Instruction::End,
+                Instruction::End,
+            Instruction::End,
+
+            // Sequence runs past the end?
+            Instruction::LocalGet(10),
+            Instruction::If(wasm_encoder::BlockType::Empty),
+            Instruction::Else,
+                Instruction::LocalGet(3),
+                Instruction::LocalGet(6),
+                Instruction::I32Add,
+                Instruction::LocalGet(2),
+                Instruction::I32GtU,
+                Instruction::If(wasm_encoder::BlockType::Empty),
+                    Instruction::I32Const(1),
+                    Instruction::LocalSet(10),
+                    Instruction::I32Const(1),
+                    Instruction::LocalSet(6),
+                Instruction::End,
+            Instruction::End,
+
+            // Fold continuation bytes, only if we still think this is well-formed.
+            Instruction::LocalGet(10),
+            Instruction::If(wasm_encoder::BlockType::Empty),
+            Instruction::Else,
+                Instruction::I32Const(1),
+                Instruction::LocalSet(8), // k = 1
+                Instruction::Block(wasm_encoder::BlockType::Empty),
+                    Instruction::Loop(wasm_encoder::BlockType::Empty),
+                        Instruction::LocalGet(8),
+                        Instruction::LocalGet(6),
+                        Instruction::I32GeU,
+                        Instruction::BrIf(1),
+
+                        Instruction::LocalGet(0),
+                        Instruction::I32Const(16),
+                        Instruction::I32Add,
+                        Instruction::LocalGet(3),
+                        Instruction::I32Add,
+                        Instruction::LocalGet(8),
+                        Instruction::I32Add,
+                        Instruction::I32Load8U(MemArg { offset: 0, align: 0, memory_index: 0 }),
+                        Instruction::LocalSet(9), // cont
+
+                        Instruction::LocalGet(9),
+                        Instruction::I32Const(0xC0),
+                        Instruction::I32And,
+                        Instruction::I32Const(0x80),
+                        Instruction::I32Ne,
+                        Instruction::If(wasm_encoder::BlockType::Empty),
+                            Instruction::I32Const(1),
+                            Instruction::LocalSet(10),
+                            Instruction::Br(1),
+                        Instruction::End,
+
+                        Instruction::LocalGet(11),
+                        Instruction::I32Const(6),
+                        Instruction::I32Shl,
+                        Instruction::LocalGet(9),
+                        Instruction::I32Const(0x3F),
+                        Instruction::I32And,
+                        Instruction::I32Or,
+                        Instruction::LocalSet(11),
+
+                        Instruction::LocalGet(8),
+                        Instruction::I32Const(1),
+                        Instruction::I32Add,
+                        Instruction::LocalSet(8),
+                        Instruction::Br(0),
+                    Instruction::End,
+                Instruction::End,
+            Instruction::End,
+        ]
+    }
+
+    /// Computes the UTF-16 buffer length (in code units) that `utf16_len`
+    /// exposes, and the `string_to_utf16`/`string_from_utf16` bridge
+    /// functions share: converts the scalar in local 11 to 1 code unit if
+    /// it fits in the BMP (`<= 0xFFFF`), or 2 (a surrogate pair) otherwise.
+    /// Leaves the result on the stack.
+    fn generate_utf16_units_for_scalar(&self) -> Vec<Instruction> {
+        vec![
+            Instruction::LocalGet(11),
+            Instruction::I32Const(0x10000),
+            Instruction::I32GeU,
+            Instruction::If(wasm_encoder::BlockType::Result(wasm_encoder::ValType::I32)),
+                Instruction::I32Const(2),
+            Instruction::Else,
+                Instruction::I32Const(1),
+            Instruction::End,
+        ]
+    }
+
+    /// Implements `utf16_len`: the number of UTF-16 code units needed to
+    /// represent this UTF-8 string, decoding each scalar and adding 1, or 2
+    /// for a surrogate pair (`generate_utf16_units_for_scalar`).
+    ///
+    /// Parameters: string_ptr (0). Locals: 2 = byte length, 3 = pos,
+    /// 4 = running unit count, plus the decode locals documented on
+    /// `generate_utf8_decode_scalar`.
+    pub fn generate_string_utf16_len(&self) -> Vec<Instruction> {
+        let mut instructions = vec![
+            Instruction::LocalGet(0),
+            Instruction::I32Load(MemArg { offset: 0, align: 2, memory_index: 0 }),
+            Instruction::LocalSet(2), // len
+
+            Instruction::I32Const(0),
+            Instruction::LocalSet(3), // pos = 0
+            Instruction::I32Const(0),
+            Instruction::LocalSet(4), // units = 0
+            Instruction::Block(wasm_encoder::BlockType::Empty),
+                Instruction::Loop(wasm_encoder::BlockType::Empty),
+                    Instruction::LocalGet(3),
+                    Instruction::LocalGet(2),
+                    Instruction::I32GeU,
+                    Instruction::BrIf(1),
+        ];
+        instructions.extend(self.generate_utf8_decode_scalar());
+        instructions.extend(self.generate_utf16_units_for_scalar());
+        instructions.extend(vec![
+                    Instruction::LocalGet(4),
+                    Instruction::I32Add,
+                    Instruction::LocalSet(4), // units += units_for_scalar
+
+                    Instruction::LocalGet(3),
+                    Instruction::LocalGet(6),
+                    Instruction::I32Add,
+                    Instruction::LocalSet(3), // pos += seqlen
+                    Instruction::Br(0),
+                Instruction::End,
+            Instruction::End,
+
+            Instruction::LocalGet(4),
+        ]);
+        instructions
+    }
+
+    /// Converts the UTF-8 string at `string_ptr` (0) to a UTF-16 buffer (see
+    /// the 8-byte buffer header documented above), decoding each
+    /// scalar and emitting either one code unit, or a high/low surrogate
+    /// pair for codepoints above the BMP, per the surrogate formulas in
+    /// Unicode's UTF-16 encoding. Two passes: the first counts units (reusing
+    /// `generate_string_utf16_len`'s approach) to size the buffer; the
+    /// second re-decodes and writes.
+    ///
+    /// Locals: as `generate_utf8_decode_scalar`, plus 4 = unit count (pass
+    /// 1), 7 = write cursor in code units (pass 2), 12/13 = surrogate pair
+    /// scratch, 14 = dest pointer (freshly allocated via the shared
+    /// `env.malloc` import once the exact unit count is known, so each call
+    /// gets its own buffer instead of aliasing a fixed scratch address).
+    pub fn generate_string_to_utf16(&self, malloc_idx: u32) -> Vec<Instruction> {
+        let mut instructions = vec![
+            Instruction::LocalGet(0),
+            Instruction::I32Load(MemArg { offset: 0, align: 2, memory_index: 0 }),
+            Instruction::LocalSet(2), // len
+
+            // Pass 1: count code units.
+            Instruction::I32Const(0),
+            Instruction::LocalSet(3), // pos = 0
+            Instruction::I32Const(0),
+            Instruction::LocalSet(4), // units = 0
+            Instruction::Block(wasm_encoder::BlockType::Empty),
+                Instruction::Loop(wasm_encoder::BlockType::Empty),
+                    Instruction::LocalGet(3),
+                    Instruction::LocalGet(2),
+                    Instruction::I32GeU,
+                    Instruction::BrIf(1),
+        ];
+        instructions.extend(self.generate_utf8_decode_scalar());
+        instructions.extend(self.generate_utf16_units_for_scalar());
+        instructions.extend(vec![
+                    Instruction::LocalGet(4),
+                    Instruction::I32Add,
+                    Instruction::LocalSet(4),
+
+                    Instruction::LocalGet(3),
+                    Instruction::LocalGet(6),
+                    Instruction::I32Add,
+                    Instruction::LocalSet(3),
+                    Instruction::Br(0),
+                Instruction::End,
+            Instruction::End,
+        ]);
+
+        instructions.extend(vec![
+            Instruction::LocalGet(4),
+            Instruction::I32Const(1),
+            Instruction::I32Shl, // units * 2 bytes
+            Instruction::I32Const(8),
+            Instruction::I32Add,
+            Instruction::Call(malloc_idx),
+            Instruction::LocalSet(14), // dest = malloc(units * 2 + 8)
+
+            Instruction::LocalGet(14),
+            Instruction::LocalGet(4),
+            Instruction::I32Store(MemArg { offset: 0, align: 2, memory_index: 0 }),
+            Instruction::LocalGet(14),
+            Instruction::I32Const(0),
+            Instruction::I32Store(MemArg { offset: 4, align: 2, memory_index: 0 }),
+
+            // Pass 2: decode and encode.
+            Instruction::I32Const(0),
+            Instruction::LocalSet(3), // pos = 0
+            Instruction::I32Const(0),
+            Instruction::LocalSet(7), // unit write cursor = 0
+            Instruction::Block(wasm_encoder::BlockType::Empty),
+                Instruction::Loop(wasm_encoder::BlockType::Empty),
+                    Instruction::LocalGet(3),
+                    Instruction::LocalGet(2),
+                    Instruction::I32GeU,
+                    Instruction::BrIf(1),
+        ]);
+        instructions.extend(self.generate_utf8_decode_scalar());
+        instructions.extend(vec![
+                    Instruction::LocalGet(11),
+                    Instruction::I32Const(0x10000),
+                    Instruction::I32GeU,
+                    Instruction::If(wasm_encoder::BlockType::Empty),
+                        // Surrogate pair.
+                        Instruction::LocalGet(11),
+                        Instruction::I32Const(0x10000),
+                        Instruction::I32Sub,
+                        Instruction::LocalSet(12), // v = cp - 0x10000
+
+                        Instruction::I32Const(0xD800),
+                        Instruction::LocalGet(12),
+                        Instruction::I32Const(10),
+                        Instruction::I32ShrU,
+                        Instruction::I32Add,
+                        Instruction::LocalSet(13), // high surrogate
+
+                        Instruction::LocalGet(14),
+                        Instruction::I32Const(8),
+                        Instruction::I32Add,
+                        Instruction::LocalGet(7),
+                        Instruction::I32Const(1),
+                        Instruction::I32Shl,
+                        Instruction::I32Add,
+                        Instruction::LocalGet(13),
+                        Instruction::I32Store16(MemArg { offset: 0, align: 1, memory_index: 0 }),
+
+                        Instruction::I32Const(0xDC00),
+                        Instruction::LocalGet(12),
+                        Instruction::I32Const(0x3FF),
+                        Instruction::I32And,
+                        Instruction::I32Add,
+                        Instruction::LocalSet(13), // low surrogate
+
+                        Instruction::LocalGet(14),
+                        Instruction::I32Const(8),
+                        Instruction::I32Add,
+                        Instruction::LocalGet(7),
+                        Instruction::I32Const(1),
+                        Instruction::I32Add,
+                        Instruction::I32Const(1),
+                        Instruction::I32Shl,
+                        Instruction::I32Add,
+                        Instruction::LocalGet(13),
+                        Instruction::I32Store16(MemArg { offset: 0, align: 1, memory_index: 0 }),
+
+                        Instruction::LocalGet(7),
+                        Instruction::I32Const(2),
+                        Instruction::I32Add,
+                        Instruction::LocalSet(7),
+                    Instruction::Else,
+                        Instruction::LocalGet(14),
+                        Instruction::I32Const(8),
+                        Instruction::I32Add,
+                        Instruction::LocalGet(7),
+                        Instruction::I32Const(1),
+                        Instruction::I32Shl,
+                        Instruction::I32Add,
+                        Instruction::LocalGet(11),
+                        Instruction::I32Store16(MemArg { offset: 0, align: 1, memory_index: 0 }),
+
+                        Instruction::LocalGet(7),
+                        Instruction::I32Const(1),
+                        Instruction::I32Add,
+                        Instruction::LocalSet(7),
+                    Instruction::End,
+
+                    Instruction::LocalGet(3),
+                    Instruction::LocalGet(6),
+                    Instruction::I32Add,
+                    Instruction::LocalSet(3),
+                    Instruction::Br(0),
+                Instruction::End,
+            Instruction::End,
+
+            Instruction::LocalGet(14),
+        ]);
+        instructions
+    }
+
+    /// Decodes one scalar from a UTF-16 buffer (see
+    /// `generate_string_to_utf16` for the header layout) at unit index
+    /// `idx` (local 3), given total unit count `count` (local 2).
+    /// Recombines a high/low surrogate pair into a single scalar; an
+    /// unpaired high or low surrogate decodes as the replacement character
+    /// (U+FFFD) and consumes just the one unit, since a lone half of a
+    /// surrogate pair has no valid Unicode meaning by itself.
+    ///
+    /// On return: local 6 = units consumed (1 or 2), local 11 = decoded
+    /// scalar. Also uses locals 9 (the unit just read) and 10 (a
+    /// surrogate candidate read one unit ahead).
+    fn generate_utf16_decode_unit(&self) -> Vec<Instruction> {
         vec![
-            // For now, just return the original string to avoid complex string manipulation
-            // In a full implementation, this would:
-            // 1. Search for occurrences of old_str in string
-            // 2. Calculate new string length
-            // 3. Allocate new string
-            // 4. Copy parts with replacements
-            // 5. Return new string pointer
-            Instruction::LocalGet(0), // Return original string
+            Instruction::LocalGet(0),
+            Instruction::I32Const(8),
+            Instruction::I32Add,
+            Instruction::LocalGet(3),
+            Instruction::I32Const(1),
+            Instruction::I32Shl,
+            Instruction::I32Add,
+            Instruction::I32Load16U(MemArg { offset: 0, align: 1, memory_index: 0 }),
+            Instruction::LocalSet(9), // unit
+
+            Instruction::LocalGet(9),
+            Instruction::I32Const(0xD800),
+            Instruction::I32GeU,
+            Instruction::LocalGet(9),
+            Instruction::I32Const(0xDBFF),
+            Instruction::I32LeU,
+            Instruction::I32And,
+            Instruction::If(wasm_encoder::BlockType::Empty),
+                // High surrogate: is there a next unit, and is it a low surrogate?
+                Instruction::LocalGet(3),
+                Instruction::I32Const(1),
+                Instruction::I32Add,
+                Instruction::LocalGet(2),
+                Instruction::I32LtU,
+                Instruction::If(wasm_encoder::BlockType::Empty),
+                    Instruction::LocalGet(0),
+                    Instruction::I32Const(8),
+                    Instruction::I32Add,
+                    Instruction::LocalGet(3),
+                    Instruction::I32Const(1),
+                    Instruction::I32Add,
+                    Instruction::I32Const(1),
+                    Instruction::I32Shl,
+                    Instruction::I32Add,
+                    Instruction::I32Load16U(MemArg { offset: 0, align: 1, memory_index: 0 }),
+                    Instruction::LocalSet(10), // candidate low surrogate
+
+                    Instruction::LocalGet(10),
+                    Instruction::I32Const(0xDC00),
+                    Instruction::I32GeU,
+                    Instruction::LocalGet(10),
+                    Instruction::I32Const(0xDFFF),
+                    Instruction::I32LeU,
+                    Instruction::I32And,
+                    Instruction::If(wasm_encoder::BlockType::Empty),
+                        Instruction::LocalGet(9),
+                        Instruction::I32Const(0xD800),
+                        Instruction::I32Sub,
+                        Instruction::I32Const(10),
+                        Instruction::I32Shl,
+                        Instruction::LocalGet(10),
+                        Instruction::I32Const(0xDC00),
+                        Instruction::I32Sub,
+                        Instruction::I32Or,
+                        Instruction::I32Const(0x10000),
+                        Instruction::I32Add,
+                        Instruction::LocalSet(11), // scalar
+                        Instruction::I32Const(2),
+                        Instruction::LocalSet(6), // consumed = 2
+                    Instruction::Else,
+                        Instruction::I32Const(0xFFFD),
+                        Instruction::LocalSet(11),
+                        Instruction::I32Const(1),
+                        Instruction::LocalSet(6),
+                    Instruction::End,
+                Instruction::Else,
+                    Instruction::I32Const(0xFFFD),
+                    Instruction::LocalSet(11),
+                    Instruction::I32Const(1),
+                    Instruction::LocalSet(6),
+                Instruction::End,
+            Instruction::Else,
+                // A lone low surrogate, or an ordinary BMP unit.
+                Instruction::LocalGet(9),
+                Instruction::I32Const(0xDC00),
+                Instruction::I32GeU,
+                Instruction::LocalGet(9),
+                Instruction::I32Const(0xDFFF),
+                Instruction::I32LeU,
+                Instruction::I32And,
+                Instruction::If(wasm_encoder::BlockType::Empty),
+                    Instruction::I32Const(0xFFFD),
+                    Instruction::LocalSet(11),
+                Instruction::Else,
+                    Instruction::LocalGet(9),
+                    Instruction::LocalSet(11),
+                Instruction::End,
+                Instruction::I32Const(1),
+                Instruction::LocalSet(6),
+            Instruction::End,
         ]
     }
 
+    /// Pushes the UTF-8 encoded length (1-4 bytes) of the scalar in local
+    /// 11. Shared by `string_from_utf16`'s sizing pass and its encoding
+    /// pass (via `generate_utf8_encode_scalar`, which uses the same range
+    /// checks while actually writing bytes).
+    fn generate_utf8_encoded_len(&self) -> Vec<Instruction> {
+        vec![
+            Instruction::LocalGet(11),
+            Instruction::I32Const(0x80),
+            Instruction::I32LtU,
+            Instruction::If(wasm_encoder::BlockType::Result(wasm_encoder::ValType::I32)),
+                Instruction::I32Const(1),
+            Instruction::Else,
+                Instruction::LocalGet(11),
+                Instruction::I32Const(0x800),
+                Instruction::I32LtU,
+                Instruction::If(wasm_encoder::BlockType::Result(wasm_encoder::ValType::I32)),
+                    Instruction::I32Const(2),
+                Instruction::Else,
+                    Instruction::LocalGet(11),
+                    Instruction::I32Const(0x10000),
+                    Instruction::I32LtU,
+                    Instruction::If(wasm_encoder::BlockType::Result(wasm_encoder::ValType::I32)),
+                        Instruction::I32Const(3),
+                    Instruction::Else,
+                        Instruction::I32Const(4),
+                    Instruction::End,
+                Instruction::End,
+            Instruction::End,
+        ]
+    }
+
+    /// Encodes the scalar in local 11 as UTF-8 into `dest_local`'s data
+    /// region (past its 16-byte string header) at byte offset
+    /// `dest_pos_local`, advancing `dest_pos_local` by the number of bytes
+    /// written. Uses local 15 as a scratch byte counter.
+    fn generate_utf8_encode_scalar(&self, dest_local: u32, dest_pos_local: u32) -> Vec<Instruction> {
+        let store_byte = |extra_offset: i32, value: Vec<Instruction>| -> Vec<Instruction> {
+            let mut v = vec![
+                Instruction::LocalGet(dest_local),
+                Instruction::I32Const(16),
+                Instruction::I32Add,
+                Instruction::LocalGet(dest_pos_local),
+                Instruction::I32Add,
+            ];
+            if extra_offset != 0 {
+                v.push(Instruction::I32Const(extra_offset));
+                v.push(Instruction::I32Add);
+            }
+            v.extend(value);
+            v.push(Instruction::I32Store8(MemArg { offset: 0, align: 0, memory_index: 0 }));
+            v
+        };
+
+        let mut instructions = vec![
+            Instruction::LocalGet(11),
+            Instruction::I32Const(0x80),
+            Instruction::I32LtU,
+            Instruction::If(wasm_encoder::BlockType::Empty),
+        ];
+        instructions.extend(store_byte(0, vec![Instruction::LocalGet(11)]));
+        instructions.extend(vec![
+                Instruction::I32Const(1),
+                Instruction::LocalSet(15),
+            Instruction::Else,
+                Instruction::LocalGet(11),
+                Instruction::I32Const(0x800),
+                Instruction::I32LtU,
+                Instruction::If(wasm_encoder::BlockType::Empty),
+        ]);
+        instructions.extend(store_byte(0, vec![
+            Instruction::I32Const(0xC0),
+            Instruction::LocalGet(11),
+            Instruction::I32Const(6),
+            Instruction::I32ShrU,
+            Instruction::I32Or,
+        ]));
+        instructions.extend(store_byte(1, vec![
+            Instruction::I32Const(0x80),
+            Instruction::LocalGet(11),
+            Instruction::I32Const(0x3F),
+            Instruction::I32And,
+            Instruction::I32Or,
+        ]));
+        instructions.extend(vec![
+                    Instruction::I32Const(2),
+                    Instruction::LocalSet(15),
+                Instruction::Else,
+                    Instruction::LocalGet(11),
+                    Instruction::I32Const(0x10000),
+                    Instruction::I32LtU,
+                    Instruction::If(wasm_encoder::BlockType::Empty),
+        ]);
+        instructions.extend(store_byte(0, vec![
+            Instruction::I32Const(0xE0),
+            Instruction::LocalGet(11),
+            Instruction::I32Const(12),
+            Instruction::I32ShrU,
+            Instruction::I32Or,
+        ]));
+        instructions.extend(store_byte(1, vec![
+            Instruction::I32Const(0x80),
+            Instruction::LocalGet(11),
+            Instruction::I32Const(6),
+            Instruction::I32ShrU,
+            Instruction::I32Const(0x3F),
+            Instruction::I32And,
+            Instruction::I32Or,
+        ]));
+        instructions.extend(store_byte(2, vec![
+            Instruction::I32Const(0x80),
+            Instruction::LocalGet(11),
+            Instruction::I32Const(0x3F),
+            Instruction::I32And,
+            Instruction::I32Or,
+        ]));
+        instructions.extend(vec![
+                        Instruction::I32Const(3),
+                        Instruction::LocalSet(15),
+                    Instruction::Else,
+        ]);
+        instructions.extend(store_byte(0, vec![
+            Instruction::I32Const(0xF0),
+            Instruction::LocalGet(11),
+            Instruction::I32Const(18),
+            Instruction::I32ShrU,
+            Instruction::I32Or,
+        ]));
+        instructions.extend(store_byte(1, vec![
+            Instruction::I32Const(0x80),
+            Instruction::LocalGet(11),
+            Instruction::I32Const(12),
+            Instruction::I32ShrU,
+            Instruction::I32Const(0x3F),
+            Instruction::I32And,
+            Instruction::I32Or,
+        ]));
+        instructions.extend(store_byte(2, vec![
+            Instruction::I32Const(0x80),
+            Instruction::LocalGet(11),
+            Instruction::I32Const(6),
+            Instruction::I32ShrU,
+            Instruction::I32Const(0x3F),
+            Instruction::I32And,
+            Instruction::I32Or,
+        ]));
+        instructions.extend(store_byte(3, vec![
+            Instruction::I32Const(0x80),
+            Instruction::LocalGet(11),
+            Instruction::I32Const(0x3F),
+            Instruction::I32And,
+            Instruction::I32Or,
+        ]));
+        instructions.extend(vec![
+                        Instruction::I32Const(4),
+                        Instruction::LocalSet(15),
+                    Instruction::End,
+                Instruction::End,
+            Instruction::End,
+
+            Instruction::LocalGet(dest_pos_local),
+            Instruction::LocalGet(15),
+            Instruction::I32Add,
+            Instruction::LocalSet(dest_pos_local),
+        ]);
+        instructions
+    }
+
+    /// Converts a UTF-16 buffer (see `generate_string_to_utf16` for the
+    /// header layout) back to a UTF-8 string, recombining surrogate pairs
+    /// via `generate_utf16_decode_unit` and re-encoding each scalar via
+    /// `generate_utf8_encode_scalar`. Two passes, the same way
+    /// `generate_string_to_utf16` is: the first sizes the output (since a
+    /// 4-byte-UTF-8 scalar came from a 2-unit surrogate pair, byte length
+    /// isn't simply `unit_count * something`), the second writes it.
+    ///
+    /// Parameters: utf16_ptr (0). Locals: 2 = unit count, 3 = idx,
+    /// 4 = byte length total (pass 1), 7 = destination byte cursor (pass
+    /// 2), 16 = destination pointer (freshly allocated via the shared
+    /// `env.malloc` import once pass 1 knows the exact byte length), plus
+    /// the locals documented on `generate_utf16_decode_unit` and
+    /// `generate_utf8_encode_scalar`.
+    pub fn generate_string_from_utf16(&self, malloc_idx: u32) -> Vec<Instruction> {
+        let mut instructions = vec![
+            Instruction::LocalGet(0),
+            Instruction::I32Load(MemArg { offset: 0, align: 2, memory_index: 0 }),
+            Instruction::LocalSet(2), // unit count
+
+            // Pass 1: total UTF-8 byte length.
+            Instruction::I32Const(0),
+            Instruction::LocalSet(3), // idx = 0
+            Instruction::I32Const(0),
+            Instruction::LocalSet(4), // total = 0
+            Instruction::Block(wasm_encoder::BlockType::Empty),
+                Instruction::Loop(wasm_encoder::BlockType::Empty),
+                    Instruction::LocalGet(3),
+                    Instruction::LocalGet(2),
+                    Instruction::I32GeU,
+                    Instruction::BrIf(1),
+        ];
+        instructions.extend(self.generate_utf16_decode_unit());
+        instructions.extend(self.generate_utf8_encoded_len());
+        instructions.extend(vec![
+                    Instruction::LocalGet(4),
+                    Instruction::I32Add,
+                    Instruction::LocalSet(4), // total += encoded_len(scalar)
+
+                    Instruction::LocalGet(3),
+                    Instruction::LocalGet(6),
+                    Instruction::I32Add,
+                    Instruction::LocalSet(3), // idx += consumed
+                    Instruction::Br(0),
+                Instruction::End,
+            Instruction::End,
+        ]);
+
+        instructions.extend(vec![
+            Instruction::LocalGet(4),
+            Instruction::I32Const(16),
+            Instruction::I32Add,
+            Instruction::Call(malloc_idx),
+            Instruction::LocalSet(16), // dest = malloc(total + 16)
+
+            Instruction::LocalGet(16),
+            Instruction::LocalGet(4),
+            Instruction::I32Store(MemArg { offset: 0, align: 2, memory_index: 0 }), // length
+            Instruction::LocalGet(16),
+            Instruction::LocalGet(4),
+            Instruction::I32Store(MemArg { offset: 4, align: 2, memory_index: 0 }), // capacity
+
+            // Pass 2: decode and re-encode.
+            Instruction::I32Const(0),
+            Instruction::LocalSet(3), // idx = 0
+            Instruction::I32Const(0),
+            Instruction::LocalSet(7), // dest byte cursor = 0
+            Instruction::Block(wasm_encoder::BlockType::Empty),
+                Instruction::Loop(wasm_encoder::BlockType::Empty),
+                    Instruction::LocalGet(3),
+                    Instruction::LocalGet(2),
+                    Instruction::I32GeU,
+                    Instruction::BrIf(1),
+        ]);
+        instructions.extend(self.generate_utf16_decode_unit());
+        instructions.extend(self.generate_utf8_encode_scalar(16, 7));
+        instructions.extend(vec![
+                    Instruction::LocalGet(3),
+                    Instruction::LocalGet(6),
+                    Instruction::I32Add,
+                    Instruction::LocalSet(3), // idx += consumed
+                    Instruction::Br(0),
+                Instruction::End,
+            Instruction::End,
+
+            Instruction::LocalGet(16),
+        ]);
+        instructions
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    /// Golden tests that actually execute the hand-emitted WASM these
+    /// generators produce, the same way chunk105-3's numeric golden tests
+    /// do via `run_export` - except these functions now `call` the shared
+    /// `"env"."malloc"` import (chunk102-4), which `run_export`'s bare
+    /// `Linker::new` can't satisfy. `Harness` below links a throwaway
+    /// bump-pointer allocator in its place: good enough to prove a
+    /// generator's own logic is correct without dragging in the real
+    /// `HostHeap`.
+    mod golden {
+        use crate::codegen::CodeGenerator;
+        use crate::stdlib::string_ops::StringOperations;
+        use wasmtime::{Engine, Linker, Module, Store, Caller, Memory, Val};
+
+        fn build_module() -> Vec<u8> {
+            let mut codegen = CodeGenerator::new();
+            StringOperations::new(crate::codegen::HEAP_START)
+                .register_functions(&mut codegen)
+                .expect("string stdlib registration should not fail");
+            codegen
+                .generate_test_module_without_imports()
+                .expect("assembling the golden-test module should not fail")
+        }
+
+        /// Store data is the bump-pointer cursor for the fake `env.malloc`,
+        /// seeded past `HEAP_START` so test-allocated strings never land on
+        /// top of the module's own (empty) data section.
+        struct Harness {
+            store: Store<i32>,
+            instance: wasmtime::Instance,
+            memory: Memory,
+        }
+
+        impl Harness {
+            fn new() -> Self {
+                let engine = Engine::default();
+                let wasm = build_module();
+                let module = Module::new(&engine, &wasm)
+                    .expect("golden-test module should be valid WASM");
+                let mut store = Store::new(&engine, crate::codegen::HEAP_START as i32);
+                let mut linker = Linker::new(&engine);
+                linker
+                    .func_wrap("env", "malloc", |mut caller: Caller<'_, i32>, size: i32| -> i32 {
+                        if size <= 0 {
+                            return 0;
+                        }
+                        let aligned = (size + 7) & !7;
+                        let ptr = *caller.data();
+                        *caller.data_mut() = ptr + aligned;
+                        ptr
+                    })
+                    .expect("linking fake env.malloc should not fail");
+                linker
+                    .func_wrap("env", "free", |_caller: Caller<'_, i32>, _ptr: i32| {})
+                    .expect("linking fake env.free should not fail");
+                let instance = linker
+                    .instantiate(&mut store, &module)
+                    .expect("golden-test module should instantiate");
+                let memory = instance
+                    .get_memory(&mut store, "memory")
+                    .expect("golden-test module should export memory");
+                Harness { store, instance, memory }
+            }
+
+            /// Writes `s` as a string value (16-byte header + UTF-8 bytes,
+            /// matching `StringManager::allocate_string_with_capacity`'s
+            /// layout) directly into guest memory and returns its pointer,
+            /// bumping the same cursor the fake `env.malloc` uses so the two
+            /// allocators never collide.
+            fn alloc_string(&mut self, s: &str) -> i32 {
+                let bytes = s.as_bytes();
+                let len = bytes.len() as i32;
+                let aligned = (len + 16 + 7) & !7;
+                let ptr = *self.store.data();
+                *self.store.data_mut() = ptr + aligned;
+
+                let needed = (ptr + aligned) as u64;
+                let current_pages = self.memory.size(&self.store);
+                if needed > current_pages * 65536 {
+                    let needed_pages = needed.div_ceil(65536);
+                    self.memory
+                        .grow(&mut self.store, needed_pages - current_pages)
+                        .expect("growing golden-test memory should not fail");
+                }
+
+                let data = self.memory.data_mut(&mut self.store);
+                let p = ptr as usize;
+                data[p..p + 4].copy_from_slice(&len.to_le_bytes());
+                data[p + 4..p + 8].copy_from_slice(&len.to_le_bytes()); // capacity = length
+                data[p + 8..p + 12].copy_from_slice(&0i32.to_le_bytes()); // hash: not yet computed
+                data[p + 12..p + 16].copy_from_slice(&0i32.to_le_bytes()); // reserved
+                data[p + 16..p + 16 + bytes.len()].copy_from_slice(bytes);
+                ptr
+            }
+
+            fn read_string(&mut self, ptr: i32) -> String {
+                let data = self.memory.data(&self.store);
+                let p = ptr as usize;
+                let len = i32::from_le_bytes(data[p..p + 4].try_into().unwrap()) as usize;
+                String::from_utf8_lossy(&data[p + 16..p + 16 + len]).into_owned()
+            }
+
+            fn call_i32(&mut self, func: &str, args: &[i32]) -> i32 {
+                let f = self
+                    .instance
+                    .get_func(&mut self.store, func)
+                    .unwrap_or_else(|| panic!("no exported function named '{}'", func));
+                let wasm_args: Vec<Val> = args.iter().map(|a| Val::I32(*a)).collect();
+                let mut results = vec![Val::I32(0)];
+                f.call(&mut self.store, &wasm_args, &mut results)
+                    .unwrap_or_else(|e| panic!("call to '{}' failed: {}", func, e));
+                results[0].unwrap_i32()
+            }
+
+            /// Like `call_i32`, but for bounds-check generators that are
+            /// expected to trap (`unreachable`) rather than return a value -
+            /// asserts the call actually failed instead of panicking on it.
+            fn expect_trap(&mut self, func: &str, args: &[i32]) {
+                let f = self
+                    .instance
+                    .get_func(&mut self.store, func)
+                    .unwrap_or_else(|| panic!("no exported function named '{}'", func));
+                let wasm_args: Vec<Val> = args.iter().map(|a| Val::I32(*a)).collect();
+                let mut results = vec![Val::I32(0)];
+                let outcome = f.call(&mut self.store, &wasm_args, &mut results);
+                assert!(
+                    outcome.is_err(),
+                    "expected call to '{}' to trap, but it returned {:?}",
+                    func,
+                    results
+                );
+            }
+
+            /// Reads an array-family list (8-byte header: count, capacity,
+            /// i32 elements from offset 8 on) into a `Vec<i32>` of its
+            /// elements.
+            fn read_i32_list(&mut self, ptr: i32) -> Vec<i32> {
+                let data = self.memory.data(&self.store);
+                let p = ptr as usize;
+                let count = i32::from_le_bytes(data[p..p + 4].try_into().unwrap()) as usize;
+                (0..count)
+                    .map(|i| {
+                        let off = p + 8 + i * 4;
+                        i32::from_le_bytes(data[off..off + 4].try_into().unwrap())
+                    })
+                    .collect()
+            }
+        }
+
+        #[test]
+        fn concat_joins_both_strings() {
+            let mut h = Harness::new();
+            let a = h.alloc_string("foo");
+            let b = h.alloc_string("bar");
+            let result = h.call_i32("string.concat", &[a, b]);
+            assert_eq!(h.read_string(result), "foobar");
+        }
+
+        #[test]
+        fn chained_concat_does_not_alias_the_first_result() {
+            let mut h = Harness::new();
+            let a = h.alloc_string("a");
+            let b = h.alloc_string("b");
+            let c = h.alloc_string("c");
+
+            let ab = h.call_i32("string.concat", &[a, b]);
+            assert_eq!(h.read_string(ab), "ab");
+
+            let abc = h.call_i32("string.concat", &[ab, c]);
+            assert_eq!(h.read_string(abc), "abc");
+
+            // The first result must still read back correctly - a fixed
+            // scratch-address implementation would have overwritten it when
+            // computing `abc`.
+            assert_eq!(h.read_string(ab), "ab");
+        }
+
+        #[test]
+        fn to_upper_shifts_ascii_letters() {
+            let mut h = Harness::new();
+            let s = h.alloc_string("Hello, World!");
+            let result = h.call_i32("string_to_upper", &[s]);
+            assert_eq!(h.read_string(result), "HELLO, WORLD!");
+        }
+
+        #[test]
+        fn to_lower_shifts_ascii_letters() {
+            let mut h = Harness::new();
+            let s = h.alloc_string("Hello, World!");
+            let result = h.call_i32("string_to_lower", &[s]);
+            assert_eq!(h.read_string(result), "hello, world!");
+        }
+
+        #[test]
+        fn repeated_case_conversion_does_not_alias_prior_results() {
+            let mut h = Harness::new();
+            let a = h.alloc_string("abc");
+            let b = h.alloc_string("def");
+
+            let upper_a = h.call_i32("string_to_upper", &[a]);
+            let upper_b = h.call_i32("string_to_upper", &[b]);
+
+            assert_eq!(h.read_string(upper_a), "ABC");
+            assert_eq!(h.read_string(upper_b), "DEF");
+        }
+
+        #[test]
+        fn trim_removes_leading_and_trailing_whitespace() {
+            let mut h = Harness::new();
+            let s = h.alloc_string("  hello  ");
+            let result = h.call_i32("string_trim", &[s]);
+            assert_eq!(h.read_string(result), "hello");
+        }
+
+        #[test]
+        fn trim_start_removes_only_leading_whitespace() {
+            let mut h = Harness::new();
+            let s = h.alloc_string("  hello  ");
+            let result = h.call_i32("string_trim_start", &[s]);
+            assert_eq!(h.read_string(result), "hello  ");
+        }
+
+        #[test]
+        fn repeated_trim_does_not_alias_prior_results() {
+            let mut h = Harness::new();
+            let a = h.alloc_string("  a  ");
+            let b = h.alloc_string("  b  ");
+
+            let trimmed_a = h.call_i32("string_trim", &[a]);
+            let trimmed_b = h.call_i32("string_trim", &[b]);
+
+            assert_eq!(h.read_string(trimmed_a), "a");
+            assert_eq!(h.read_string(trimmed_b), "b");
+        }
+
+        #[test]
+        fn substring_extracts_the_requested_range() {
+            let mut h = Harness::new();
+            let s = h.alloc_string("hello world");
+            let result = h.call_i32("string_substring", &[s, 6, 11]);
+            assert_eq!(h.read_string(result), "world");
+        }
+
+        #[test]
+        fn repeated_substring_does_not_alias_prior_results() {
+            let mut h = Harness::new();
+            let s = h.alloc_string("hello world");
+            let first = h.call_i32("string_substring", &[s, 0, 5]);
+            let second = h.call_i32("string_substring", &[s, 6, 11]);
+
+            assert_eq!(h.read_string(first), "hello");
+            assert_eq!(h.read_string(second), "world");
+        }
+
+        #[test]
+        fn replace_substitutes_only_the_first_match() {
+            let mut h = Harness::new();
+            let s = h.alloc_string("aa-aa");
+            let old = h.alloc_string("aa");
+            let new = h.alloc_string("b");
+            let result = h.call_i32("string_replace", &[s, old, new]);
+            assert_eq!(h.read_string(result), "b-aa");
+        }
+
+        #[test]
+        fn replace_all_substitutes_every_match() {
+            let mut h = Harness::new();
+            let s = h.alloc_string("aa-aa");
+            let old = h.alloc_string("aa");
+            let new = h.alloc_string("b");
+            let result = h.call_i32("string_replace_all", &[s, old, new]);
+            assert_eq!(h.read_string(result), "b-b");
+        }
+
+        #[test]
+        fn repeated_replace_does_not_alias_prior_results() {
+            let mut h = Harness::new();
+            let s = h.alloc_string("xyxy");
+            let old = h.alloc_string("x");
+            let new_a = h.alloc_string("1");
+            let new_b = h.alloc_string("22");
+
+            let first = h.call_i32("string_replace_all", &[s, old, new_a]);
+            let second = h.call_i32("string_replace_all", &[s, old, new_b]);
+
+            assert_eq!(h.read_string(first), "1y1y");
+            assert_eq!(h.read_string(second), "22y22y");
+        }
+
+        #[test]
+        fn to_upper_folds_latin1_supplement() {
+            let mut h = Harness::new();
+            // à (U+00E0) -> À (U+00C0)
+            let s = h.alloc_string("\u{00e0}");
+            let result = h.call_i32("string_to_upper", &[s]);
+            assert_eq!(h.read_string(result), "\u{00c0}");
+        }
+
+        #[test]
+        fn to_lower_folds_latin1_supplement() {
+            let mut h = Harness::new();
+            // À (U+00C0) -> à (U+00E0)
+            let s = h.alloc_string("\u{00c0}");
+            let result = h.call_i32("string_to_lower", &[s]);
+            assert_eq!(h.read_string(result), "\u{00e0}");
+        }
+
+        #[test]
+        fn to_upper_folds_latin_extended_a() {
+            let mut h = Harness::new();
+            // ā (U+0101) -> Ā (U+0100)
+            let s = h.alloc_string("\u{0101}");
+            let result = h.call_i32("string_to_upper", &[s]);
+            assert_eq!(h.read_string(result), "\u{0100}");
+        }
+
+        #[test]
+        fn to_lower_folds_latin_extended_a() {
+            let mut h = Harness::new();
+            // Ā (U+0100) -> ā (U+0101)
+            let s = h.alloc_string("\u{0100}");
+            let result = h.call_i32("string_to_lower", &[s]);
+            assert_eq!(h.read_string(result), "\u{0101}");
+        }
+
+        #[test]
+        fn utf16_round_trip_recovers_the_original_string() {
+            let mut h = Harness::new();
+            // Mixes a BMP scalar (h) with a non-BMP one (the emoji, which
+            // needs a surrogate pair) so both `string_to_utf16` branches run.
+            let s = h.alloc_string("h\u{1F600}i");
+            let buf = h.call_i32("string_to_utf16", &[s]);
+            let back = h.call_i32("string_from_utf16", &[buf]);
+            assert_eq!(h.read_string(back), "h\u{1F600}i");
+        }
+
+        #[test]
+        fn repeated_utf16_round_trip_does_not_alias_prior_results() {
+            let mut h = Harness::new();
+            let a = h.alloc_string("alpha");
+            let b = h.alloc_string("beta");
+
+            let buf_a = h.call_i32("string_to_utf16", &[a]);
+            let buf_b = h.call_i32("string_to_utf16", &[b]);
+            let back_a = h.call_i32("string_from_utf16", &[buf_a]);
+            let back_b = h.call_i32("string_from_utf16", &[buf_b]);
+
+            assert_eq!(h.read_string(back_a), "alpha");
+            assert_eq!(h.read_string(back_b), "beta");
+        }
+
+        #[test]
+        fn split_produces_one_segment_per_delimiter_occurrence() {
+            let mut h = Harness::new();
+            let s = h.alloc_string("a,bb,ccc,dddd,eeeee");
+            let delim = h.alloc_string(",");
+            let list = h.call_i32("string.split", &[s, delim]);
+            let segments = h.read_i32_list(list);
+            assert_eq!(segments.len(), 5);
+            let words: Vec<String> = segments.into_iter().map(|p| h.read_string(p)).collect();
+            assert_eq!(words, vec!["a", "bb", "ccc", "dddd", "eeeee"]);
+        }
+
+        #[test]
+        fn split_does_not_truncate_more_segments_than_a_fixed_cap_would_hold() {
+            // Regression test: the list used to be capped at a fixed 64
+            // segments / 64-byte-per-segment scratch budget, silently
+            // dropping or corrupting anything beyond it.
+            let mut h = Harness::new();
+            let joined = (0..200).map(|i| i.to_string()).collect::<Vec<_>>().join(",");
+            let s = h.alloc_string(&joined);
+            let delim = h.alloc_string(",");
+            let list = h.call_i32("string.split", &[s, delim]);
+            let segments = h.read_i32_list(list);
+            assert_eq!(segments.len(), 200);
+            let words: Vec<String> = segments.into_iter().map(|p| h.read_string(p)).collect();
+            let expected: Vec<String> = (0..200).map(|i| i.to_string()).collect();
+            assert_eq!(words, expected);
+        }
+
+        #[test]
+        fn repeated_split_does_not_alias_prior_results() {
+            let mut h = Harness::new();
+            let a = h.alloc_string("a-b");
+            let b = h.alloc_string("x-y-z");
+            let delim = h.alloc_string("-");
+
+            let list_a = h.call_i32("string.split", &[a, delim]);
+            let list_b = h.call_i32("string.split", &[b, delim]);
+
+            let words_a: Vec<String> = h.read_i32_list(list_a).into_iter().map(|p| h.read_string(p)).collect();
+            let words_b: Vec<String> = h.read_i32_list(list_b).into_iter().map(|p| h.read_string(p)).collect();
+
+            assert_eq!(words_a, vec!["a", "b"]);
+            assert_eq!(words_b, vec!["x", "y", "z"]);
+        }
+
+        #[test]
+        fn index_of_finds_the_first_occurrence() {
+            let mut h = Harness::new();
+            let s = h.alloc_string("the quick brown fox, the lazy dog");
+            let needle = h.alloc_string("the");
+            assert_eq!(h.call_i32("string_index_of", &[s, needle]), 0);
+        }
+
+        #[test]
+        fn index_of_returns_negative_one_when_not_found() {
+            let mut h = Harness::new();
+            let s = h.alloc_string("hello world");
+            let needle = h.alloc_string("xyz");
+            assert_eq!(h.call_i32("string_index_of", &[s, needle]), -1);
+        }
+
+        #[test]
+        fn index_of_handles_a_needle_longer_than_the_haystack() {
+            let mut h = Harness::new();
+            let s = h.alloc_string("hi");
+            let needle = h.alloc_string("hello");
+            assert_eq!(h.call_i32("string_index_of", &[s, needle]), -1);
+        }
+
+        #[test]
+        fn index_of_finds_a_match_requiring_repeated_shift_table_lookups() {
+            // A needle whose bad-character table sends the window ahead by
+            // only 1 byte at a time for most of the scan - this is the case
+            // a naive single-byte-shift scan also gets right, but it
+            // exercises the shift table's per-byte entries thoroughly since
+            // almost every haystack byte recurs in the needle.
+            let mut h = Harness::new();
+            let s = h.alloc_string("aaaaaaaaaaaaaaaaaaaab");
+            let needle = h.alloc_string("aaab");
+            assert_eq!(h.call_i32("string_index_of", &[s, needle]), 17);
+        }
+
+        #[test]
+        fn contains_reports_true_and_false_correctly() {
+            let mut h = Harness::new();
+            let s = h.alloc_string("clean language");
+            let yes = h.alloc_string("language");
+            let no = h.alloc_string("rust");
+            assert_eq!(h.call_i32("string_contains", &[s, yes]), 1);
+            assert_eq!(h.call_i32("string_contains", &[s, no]), 0);
+        }
+
+        #[test]
+        fn last_index_of_finds_the_final_occurrence() {
+            let mut h = Harness::new();
+            let s = h.alloc_string("the quick brown fox, the lazy dog");
+            let needle = h.alloc_string("the");
+            assert_eq!(h.call_i32("string_last_index_of", &[s, needle]), 21);
+        }
+
+        #[test]
+        fn char_code_at_decodes_a_multibyte_scalar_by_character_index() {
+            let mut h = Harness::new();
+            // "a\u{00e9}b\u{1f600}c" - a, e-acute (2 bytes), b, grinning face
+            // (4 bytes), c. Indexing is by codepoint, not byte.
+            let s = h.alloc_string("a\u{00e9}b\u{1f600}c");
+            assert_eq!(h.call_i32("string_char_code_at", &[s, 0]), 'a' as i32);
+            assert_eq!(h.call_i32("string_char_code_at", &[s, 1]), '\u{00e9}' as i32);
+            assert_eq!(h.call_i32("string_char_code_at", &[s, 3]), '\u{1f600}' as i32);
+        }
+
+        #[test]
+        fn char_code_at_returns_negative_one_when_out_of_range() {
+            let mut h = Harness::new();
+            let s = h.alloc_string("abc");
+            assert_eq!(h.call_i32("string_char_code_at", &[s, 3]), -1);
+            assert_eq!(h.call_i32("string_char_code_at", &[s, 99]), -1);
+        }
+
+        #[test]
+        fn char_at_returns_the_one_character_string_at_a_codepoint_index() {
+            let mut h = Harness::new();
+            let s = h.alloc_string("a\u{00e9}b\u{1f600}c");
+            let got = h.call_i32("string_char_at", &[s, 3]);
+            assert_eq!(h.read_string(got), "\u{1f600}");
+        }
+
+        #[test]
+        fn char_at_returns_null_when_out_of_range() {
+            let mut h = Harness::new();
+            let s = h.alloc_string("abc");
+            assert_eq!(h.call_i32("string_char_at", &[s, 3]), 0);
+        }
+
+        #[test]
+        fn char_count_counts_codepoints_not_bytes() {
+            let mut h = Harness::new();
+            // 5 codepoints, but e-acute and the emoji each take more than one
+            // byte - `string_length` (byte length) would disagree with this.
+            let s = h.alloc_string("a\u{00e9}b\u{1f600}c");
+            assert_eq!(h.call_i32("string_char_count", &[s]), 5);
+            assert_ne!(h.call_i32("string_length", &[s]), 5);
+        }
+
+        #[test]
+        fn char_count_of_an_ascii_only_string_matches_its_byte_length() {
+            let mut h = Harness::new();
+            let s = h.alloc_string("hello");
+            assert_eq!(h.call_i32("string_char_count", &[s]), 5);
+        }
+
+        #[test]
+        fn hash_is_stable_and_distinguishes_different_content() {
+            let mut h = Harness::new();
+            let a = h.alloc_string("hello world");
+            let b = h.alloc_string("hello world");
+            let c = h.alloc_string("goodbye world");
+            assert_eq!(h.call_i32("string_hash", &[a]), h.call_i32("string_hash", &[b]));
+            assert_ne!(h.call_i32("string_hash", &[a]), h.call_i32("string_hash", &[c]));
+        }
+
+        #[test]
+        fn hash_is_cached_after_the_first_call() {
+            let mut h = Harness::new();
+            let s = h.alloc_string("cache me");
+            // Header offset 8 starts at 0 ("not yet computed") until string_hash runs once.
+            let before = {
+                let data = h.memory.data(&h.store);
+                i32::from_le_bytes(data[s as usize + 8..s as usize + 12].try_into().unwrap())
+            };
+            assert_eq!(before, 0);
+            let computed = h.call_i32("string_hash", &[s]);
+            let after = {
+                let data = h.memory.data(&h.store);
+                i32::from_le_bytes(data[s as usize + 8..s as usize + 12].try_into().unwrap())
+            };
+            assert_eq!(after, computed);
+        }
+
+        #[test]
+        fn compare_reports_equal_content_as_equal() {
+            let mut h = Harness::new();
+            let a = h.alloc_string("identical");
+            let b = h.alloc_string("identical");
+            assert_eq!(h.call_i32("string.compare", &[a, b]), 0);
+        }
+
+        #[test]
+        fn compare_uses_the_cached_hash_fast_path_for_same_length_mismatches() {
+            let mut h = Harness::new();
+            let a = h.alloc_string("aaaaa");
+            let b = h.alloc_string("bbbbb");
+            // Force both hashes to be cached before comparing, so the
+            // fast-path branch (both hashes non-zero and differing) fires.
+            h.call_i32("string_hash", &[a]);
+            h.call_i32("string_hash", &[b]);
+            assert_ne!(h.call_i32("string.compare", &[a, b]), 0);
+        }
+
+        #[test]
+        fn get_returns_the_byte_at_an_in_range_index() {
+            let mut h = Harness::new();
+            let s = h.alloc_string("abc");
+            assert_eq!(h.call_i32("string_get", &[s, 1]), b'b' as i32);
+        }
+
+        #[test]
+        fn get_traps_on_a_negative_index() {
+            let mut h = Harness::new();
+            let s = h.alloc_string("abc");
+            h.expect_trap("string_get", &[s, -1]);
+        }
+
+        #[test]
+        fn get_traps_on_an_index_past_the_end() {
+            let mut h = Harness::new();
+            let s = h.alloc_string("abc");
+            h.expect_trap("string_get", &[s, 3]);
+        }
+
+        #[test]
+        fn set_writes_the_byte_at_an_in_range_index() {
+            let mut h = Harness::new();
+            let s = h.alloc_string("abc");
+            h.call_i32("string_set", &[s, 1, b'Z' as i32]);
+            assert_eq!(h.read_string(s), "aZc");
+        }
+
+        #[test]
+        fn set_traps_on_an_out_of_range_index() {
+            let mut h = Harness::new();
+            let s = h.alloc_string("abc");
+            h.expect_trap("string_set", &[s, 3, b'Z' as i32]);
+        }
+    }
 }
 
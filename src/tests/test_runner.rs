@@ -4,7 +4,7 @@ use crate::parser::CleanParser;
 use crate::semantic::SemanticAnalyzer;
 use crate::codegen::CodeGenerator;
 use crate::error::CompilerError;
-use crate::runtime::CleanRuntime;
+use crate::runtime::{CleanRuntime, RuntimeLimits};
 
 /// Test result for a single test case
 #[derive(Debug, Clone)]
@@ -382,7 +382,7 @@ function start()
         let wasm_bytes = codegen.generate(&program)?;
 
         // Execute using the enhanced runtime
-        self.runtime.execute_async(&wasm_bytes).await?;
+        self.runtime.execute_async(&wasm_bytes, RuntimeLimits::default()).await?;
 
         Ok("Program executed successfully".to_string())
     }